@@ -3,7 +3,7 @@
     types::*,
     Error, Result, ThreadId,
 };
-use helix_core::syntax::DebuggerQuirks;
+use helix_core::syntax::{DebugAdapterConfig, DebuggerQuirks};
 
 use serde_json::Value;
 
@@ -39,6 +39,14 @@ pub struct Client {
     /// Currently active frame for the current thread.
     pub active_frame: Option<usize>,
     pub quirks: DebuggerQuirks,
+    /// Name of the configuration the session was started from.
+    pub name: String,
+    /// The session that asked for this one to be started, through `startDebugging`.
+    pub parent: Option<usize>,
+    /// The adapter the session was started with, which its child sessions use too.
+    pub config: Option<DebugAdapterConfig>,
+    /// Address of the adapter, if it was connected to by TCP.
+    pub socket: Option<SocketAddr>,
 }
 
 impl Client {
@@ -49,12 +57,14 @@ pub async fn process(
         args: Vec<&str>,
         port_arg: Option<&str>,
         id: usize,
-    ) -> Result<(Self, UnboundedReceiver<Payload>)> {
+    ) -> Result<(Self, UnboundedReceiver<(usize, Payload)>)> {
         if command.is_empty() {
             return Result::Err(Error::Other(anyhow!("Command not provided")));
         }
         if transport == "tcp" && port_arg.is_some() {
             Self::tcp_process(command, args, port_arg.unwrap(), id).await
+        } else if transport == "tcp-reverse" && port_arg.is_some() {
+            Self::tcp_reverse_process(command, args, port_arg.unwrap(), id).await
         } else if transport == "stdio" {
             Self::stdio(command, args, id)
         } else {
@@ -68,7 +78,7 @@ pub fn streams(
         err: Option<Box<dyn AsyncBufRead + Unpin + Send>>,
         id: usize,
         process: Option<Child>,
-    ) -> Result<(Self, UnboundedReceiver<Payload>)> {
+    ) -> Result<(Self, UnboundedReceiver<(usize, Payload)>)> {
         let (server_rx, server_tx) = Transport::start(rx, tx, err, id);
         let (client_tx, client_rx) = unbounded_channel();
 
@@ -84,9 +94,13 @@ pub fn streams(
             thread_id: None,
             active_frame: None,
             quirks: DebuggerQuirks::default(),
+            name: String::new(),
+            parent: None,
+            config: None,
+            socket: None,
         };
 
-        tokio::spawn(Self::recv(server_rx, client_tx));
+        tokio::spawn(Self::recv(id, server_rx, client_tx));
 
         Ok((client, client_rx))
     }
@@ -94,17 +108,20 @@ pub fn streams(
     pub async fn tcp(
         addr: std::net::SocketAddr,
         id: usize,
-    ) -> Result<(Self, UnboundedReceiver<Payload>)> {
+    ) -> Result<(Self, UnboundedReceiver<(usize, Payload)>)> {
         let stream = TcpStream::connect(addr).await?;
         let (rx, tx) = stream.into_split();
-        Self::streams(Box::new(BufReader::new(rx)), Box::new(tx), None, id, None)
+        let (mut client, incoming) =
+            Self::streams(Box::new(BufReader::new(rx)), Box::new(tx), None, id, None)?;
+        client.socket = Some(addr);
+        Ok((client, incoming))
     }
 
     pub fn stdio(
         cmd: &str,
         args: Vec<&str>,
         id: usize,
-    ) -> Result<(Self, UnboundedReceiver<Payload>)> {
+    ) -> Result<(Self, UnboundedReceiver<(usize, Payload)>)> {
         // Resolve path to the binary
         let cmd = which::which(cmd).map_err(|err| anyhow::anyhow!(err))?;
 
@@ -155,7 +172,7 @@ pub async fn tcp_process(
         args: Vec<&str>,
         port_format: &str,
         id: usize,
-    ) -> Result<(Self, UnboundedReceiver<Payload>)> {
+    ) -> Result<(Self, UnboundedReceiver<(usize, Payload)>)> {
         let port = Self::get_port().await.unwrap();
 
         let process = Command::new(cmd)
@@ -171,11 +188,48 @@ pub async fn tcp_process(
         // Wait for adapter to become ready for connection
         time::sleep(time::Duration::from_millis(500)).await;
 
-        let stream = TcpStream::connect(SocketAddr::new(
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), port);
+        let stream = TcpStream::connect(addr).await?;
+
+        let (rx, tx) = stream.into_split();
+        let (mut client, incoming) = Self::streams(
+            Box::new(BufReader::new(rx)),
+            Box::new(tx),
+            None,
+            id,
+            Some(process),
+        )?;
+        client.socket = Some(addr);
+        Ok((client, incoming))
+    }
+
+    /// Starts the adapter in reverse-connection mode, where it connects to a port Helix listens
+    /// on instead of listening itself.
+    pub async fn tcp_reverse_process(
+        cmd: &str,
+        args: Vec<&str>,
+        port_format: &str,
+        id: usize,
+    ) -> Result<(Self, UnboundedReceiver<(usize, Payload)>)> {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::new(
             IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
-            port,
+            0,
         ))
         .await?;
+        let port = listener.local_addr()?.port();
+
+        let process = Command::new(cmd)
+            .args(args)
+            .args(port_format.replace("{}", &port.to_string()).split(' '))
+            // silence messages
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let (stream, _) = time::timeout(time::Duration::from_secs(10), listener.accept())
+            .await
+            .map_err(|_| Error::Timeout)??;
 
         let (rx, tx) = stream.into_split();
         Self::streams(
@@ -187,16 +241,22 @@ pub async fn tcp_process(
         )
     }
 
-    async fn recv(mut server_rx: UnboundedReceiver<Payload>, client_tx: UnboundedSender<Payload>) {
+    async fn recv(
+        id: usize,
+        mut server_rx: UnboundedReceiver<Payload>,
+        client_tx: UnboundedSender<(usize, Payload)>,
+    ) {
         while let Some(msg) = server_rx.recv().await {
             match msg {
                 Payload::Event(ev) => {
-                    client_tx.send(Payload::Event(ev)).expect("Failed to send");
+                    client_tx
+                        .send((id, Payload::Event(ev)))
+                        .expect("Failed to send");
                 }
                 Payload::Response(_) => unreachable!(),
                 Payload::Request(req) => {
                     client_tx
-                        .send(Payload::Request(req))
+                        .send((id, Payload::Request(req)))
                         .expect("Failed to send");
                 }
             }
@@ -221,6 +281,12 @@ pub fn resume_application(&mut self) {
         self.thread_id = None;
     }
 
+    /// The selected stack frame of the stopped thread, which expressions are evaluated in.
+    pub fn current_stack_frame(&self) -> Option<&StackFrame> {
+        let thread_id = self.thread_id?;
+        self.stack_frames.get(&thread_id)?.get(self.active_frame?)
+    }
+
     /// Execute a RPC request on the debugger.
     pub fn call<R: crate::types::Request>(
         &self,
@@ -321,11 +387,12 @@ pub async fn initialize(&mut self, adapter_id: String) -> Result<()> {
             columns_start_at_one: Some(true),
             path_format: Some("path".to_owned()),
             supports_variable_type: Some(true),
-            supports_variable_paging: Some(false),
+            supports_variable_paging: Some(true),
             supports_run_in_terminal_request: Some(true),
-            supports_memory_references: Some(false),
+            supports_memory_references: Some(true),
             supports_progress_reporting: Some(false),
             supports_invalidated_event: Some(false),
+            supports_start_debugging_request: Some(true),
         };
 
         let response = self.request::<requests::Initialize>(args).await?;
@@ -346,11 +413,11 @@ pub fn attach(&self, args: serde_json::Value) -> impl Future<Output = Result<Val
         self.call::<requests::Attach>(args)
     }
 
-    pub async fn set_breakpoints(
+    pub fn set_breakpoints(
         &self,
         file: PathBuf,
         breakpoints: Vec<SourceBreakpoint>,
-    ) -> Result<Option<Vec<Breakpoint>>> {
+    ) -> impl Future<Output = Result<Option<Vec<Breakpoint>>>> {
         let args = requests::SetBreakpointsArguments {
             source: Source {
                 path: Some(file),
@@ -366,9 +433,11 @@ pub async fn set_breakpoints(
             source_modified: Some(false),
         };
 
-        let response = self.request::<requests::SetBreakpoints>(args).await?;
-
-        Ok(response.breakpoints)
+        let call = self.call::<requests::SetBreakpoints>(args);
+        async move {
+            let response: requests::SetBreakpointsResponse = serde_json::from_value(call.await?)?;
+            Ok(response.breakpoints)
+        }
     }
 
     pub async fn configuration_done(&self) -> Result<()> {
@@ -420,34 +489,206 @@ pub async fn variables(&self, variables_reference: usize) -> Result<Vec<Variable
         Ok(response.variables)
     }
 
-    pub fn step_in(&self, thread_id: ThreadId) -> impl Future<Output = Result<Value>> {
+    /// Fetches `count` of the indexed children starting at `start`, for paging through large
+    /// arrays.
+    pub async fn indexed_variables(
+        &self,
+        variables_reference: usize,
+        start: usize,
+        count: usize,
+    ) -> Result<Vec<Variable>> {
+        let args = requests::VariablesArguments {
+            variables_reference,
+            filter: Some("indexed".to_owned()),
+            start: Some(start),
+            count: Some(count),
+            format: None,
+        };
+
+        let response = self.request::<requests::Variables>(args).await?;
+        Ok(response.variables)
+    }
+
+    /// Fetches only the named children, leaving the indexed ones to be paged through.
+    pub async fn named_variables(&self, variables_reference: usize) -> Result<Vec<Variable>> {
+        let args = requests::VariablesArguments {
+            variables_reference,
+            filter: Some("named".to_owned()),
+            start: None,
+            count: None,
+            format: None,
+        };
+
+        let response = self.request::<requests::Variables>(args).await?;
+        Ok(response.variables)
+    }
+
+    pub async fn set_variable(
+        &self,
+        variables_reference: usize,
+        name: String,
+        value: String,
+    ) -> Result<requests::SetVariableResponse> {
+        let args = requests::SetVariableArguments {
+            variables_reference,
+            name,
+            value,
+            format: None,
+        };
+
+        self.request::<requests::SetVariable>(args).await
+    }
+
+    pub async fn set_expression(
+        &self,
+        expression: String,
+        value: String,
+        frame_id: Option<usize>,
+    ) -> Result<requests::SetExpressionResponse> {
+        let args = requests::SetExpressionArguments {
+            expression,
+            value,
+            frame_id,
+            format: None,
+        };
+
+        self.request::<requests::SetExpression>(args).await
+    }
+
+    pub fn step_in(
+        &self,
+        thread_id: ThreadId,
+        granularity: Option<String>,
+    ) -> impl Future<Output = Result<Value>> {
         let args = requests::StepInArguments {
             thread_id,
             target_id: None,
-            granularity: None,
+            granularity,
         };
 
         self.call::<requests::StepIn>(args)
     }
 
-    pub fn step_out(&self, thread_id: ThreadId) -> impl Future<Output = Result<Value>> {
+    pub fn step_out(
+        &self,
+        thread_id: ThreadId,
+        granularity: Option<String>,
+    ) -> impl Future<Output = Result<Value>> {
         let args = requests::StepOutArguments {
             thread_id,
-            granularity: None,
+            granularity,
         };
 
         self.call::<requests::StepOut>(args)
     }
 
-    pub fn next(&self, thread_id: ThreadId) -> impl Future<Output = Result<Value>> {
+    pub fn next(
+        &self,
+        thread_id: ThreadId,
+        granularity: Option<String>,
+    ) -> impl Future<Output = Result<Value>> {
         let args = requests::NextArguments {
             thread_id,
-            granularity: None,
+            granularity,
         };
 
         self.call::<requests::Next>(args)
     }
 
+    pub fn step_back(
+        &self,
+        thread_id: ThreadId,
+        granularity: Option<String>,
+    ) -> impl Future<Output = Result<Value>> {
+        let args = requests::StepBackArguments {
+            thread_id,
+            granularity,
+        };
+
+        self.call::<requests::StepBack>(args)
+    }
+
+    pub fn reverse_continue(&self, thread_id: ThreadId) -> impl Future<Output = Result<Value>> {
+        let args = requests::ReverseContinueArguments { thread_id };
+
+        self.call::<requests::ReverseContinue>(args)
+    }
+
+    pub fn restart_frame(&self, frame_id: usize) -> impl Future<Output = Result<Value>> {
+        let args = requests::RestartFrameArguments { frame_id };
+
+        self.call::<requests::RestartFrame>(args)
+    }
+
+    /// Lists the locations the instruction pointer can be moved to on the (1-indexed) `line` of
+    /// `file`.
+    pub async fn goto_targets(&self, file: PathBuf, line: usize) -> Result<Vec<GotoTarget>> {
+        let args = requests::GotoTargetsArguments {
+            source: Source {
+                path: Some(file),
+                name: None,
+                source_reference: None,
+                presentation_hint: None,
+                origin: None,
+                sources: None,
+                adapter_data: None,
+                checksums: None,
+            },
+            line,
+            column: None,
+        };
+
+        let response = self.request::<requests::GotoTargets>(args).await?;
+        Ok(response.targets)
+    }
+
+    pub fn goto(
+        &self,
+        thread_id: ThreadId,
+        target_id: usize,
+    ) -> impl Future<Output = Result<Value>> {
+        let args = requests::GotoArguments {
+            thread_id,
+            target_id,
+        };
+
+        self.call::<requests::Goto>(args)
+    }
+
+    /// Disassembles `count` instructions starting `instruction_offset` instructions after the
+    /// one at `memory_reference`, which may be negative.
+    pub async fn disassemble(
+        &self,
+        memory_reference: String,
+        instruction_offset: isize,
+        count: usize,
+    ) -> Result<Vec<DisassembledInstruction>> {
+        let args = requests::DisassembleArguments {
+            memory_reference,
+            offset: None,
+            instruction_offset: Some(instruction_offset),
+            instruction_count: count,
+            resolve_symbols: Some(true),
+        };
+
+        let response = self.request::<requests::Disassemble>(args).await?;
+        Ok(response.instructions)
+    }
+
+    pub async fn read_memory(
+        &self,
+        memory_reference: String,
+        count: usize,
+    ) -> Result<requests::ReadMemoryResponse> {
+        let args = requests::ReadMemoryArguments {
+            memory_reference,
+            offset: None,
+            count,
+        };
+
+        self.request::<requests::ReadMemory>(args).await
+    }
+
     pub fn pause(&self, thread_id: ThreadId) -> impl Future<Output = Result<Value>> {
         let args = requests::PauseArguments { thread_id };
 
@@ -458,23 +699,88 @@ pub async fn eval(
         &self,
         expression: String,
         frame_id: Option<usize>,
+        context: Option<String>,
     ) -> Result<requests::EvaluateResponse> {
         let args = requests::EvaluateArguments {
             expression,
             frame_id,
-            context: None,
+            context,
             format: None,
         };
 
         self.request::<requests::Evaluate>(args).await
     }
 
-    pub fn set_exception_breakpoints(
+    /// Requests completions for `text` with the cursor at the (0 indexed) char `column`.
+    pub async fn completions(
+        &self,
+        text: String,
+        column: usize,
+        frame_id: Option<usize>,
+    ) -> Result<Vec<CompletionItem>> {
+        let args = requests::CompletionsArguments {
+            frame_id,
+            text,
+            column: column + 1, // convert to 1-indexing
+            line: None,
+        };
+
+        let response = self.request::<requests::Completions>(args).await?;
+        Ok(response.targets)
+    }
+
+    /// Enables the exception `filters`, and the filters in `filter_options` with their
+    /// conditions if the debugger supports `supportsExceptionFilterOptions`.
+    pub async fn set_exception_breakpoints(
         &self,
         filters: Vec<String>,
-    ) -> impl Future<Output = Result<Value>> {
-        let args = requests::SetExceptionBreakpointsArguments { filters };
+        filter_options: Option<Vec<ExceptionFilterOptions>>,
+    ) -> Result<Option<Vec<Breakpoint>>> {
+        let args = requests::SetExceptionBreakpointsArguments {
+            filters,
+            filter_options,
+        };
+
+        let response = self
+            .request::<requests::SetExceptionBreakpoints>(args)
+            .await?;
+        Ok(response.breakpoints)
+    }
+
+    pub async fn set_function_breakpoints(
+        &self,
+        breakpoints: Vec<FunctionBreakpoint>,
+    ) -> Result<Vec<Breakpoint>> {
+        let args = requests::SetFunctionBreakpointsArguments { breakpoints };
+
+        let response = self
+            .request::<requests::SetFunctionBreakpoints>(args)
+            .await?;
+        Ok(response.breakpoints)
+    }
 
-        self.call::<requests::SetExceptionBreakpoints>(args)
+    /// Asks whether a data breakpoint can be set on the variable `name` in the container
+    /// `variables_reference`, or on the expression `name` if no container is given.
+    pub async fn data_breakpoint_info(
+        &self,
+        variables_reference: Option<usize>,
+        name: String,
+    ) -> Result<requests::DataBreakpointInfoResponse> {
+        let args = requests::DataBreakpointInfoArguments {
+            variables_reference,
+            name,
+        };
+
+        self.request::<requests::DataBreakpointInfo>(args).await
+    }
+
+    pub async fn set_data_breakpoints(
+        &self,
+        breakpoints: Vec<DataBreakpoint>,
+    ) -> Result<Vec<Breakpoint>> {
+        let args = requests::SetDataBreakpointsArguments { breakpoints };
+
+        let response = self.request::<requests::SetDataBreakpoints>(args).await?;
+        Ok(response.breakpoints)
     }
 }
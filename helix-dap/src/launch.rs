@@ -0,0 +1,412 @@
+//! Debug configurations of the workspace, read from VS Code's `.vscode/launch.json` and from
+//! `.helix/debug.toml`, which has the same structure. Tasks run before launching are read from
+//! `.vscode/tasks.json` and the `tasks` of `.helix/debug.toml`.
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchConfig {
+    pub name: String,
+    /// Type of the debug adapter, matched against the names of the adapters configured in
+    /// `languages.toml`.
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub request: String,
+    /// Label of the task to run before the debug session starts.
+    #[serde(default)]
+    pub pre_launch_task: Option<String>,
+    /// The remaining fields, sent as the arguments of the `launch` or `attach` request.
+    #[serde(flatten)]
+    pub args: Map<String, Value>,
+}
+
+impl LaunchConfig {
+    /// Applies the overrides for the current platform, like the `linux` field, to the arguments.
+    fn apply_platform_overrides(&mut self) {
+        let platform = if cfg!(windows) {
+            "windows"
+        } else if cfg!(target_os = "macos") {
+            "osx"
+        } else {
+            "linux"
+        };
+        let overrides = self.args.remove(platform);
+        for other in ["windows", "osx", "linux"] {
+            self.args.remove(other);
+        }
+        if let Some(Value::Object(overrides)) = overrides {
+            self.args.extend(overrides);
+        }
+    }
+}
+
+/// Launches several configurations at once.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Compound {
+    pub name: String,
+    /// Names of the configurations to launch.
+    pub configurations: Vec<String>,
+    #[serde(default)]
+    pub pre_launch_task: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub label: String,
+    /// Either `shell`, running the command in a shell, or `process`.
+    #[serde(rename = "type", default)]
+    pub ty: Option<String>,
+    /// Tasks without a command, like those provided by VS Code extensions, can't be run.
+    #[serde(default)]
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<Value>,
+    #[serde(default)]
+    pub options: TaskOptions,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct TaskOptions {
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: Map<String, Value>,
+}
+
+impl Task {
+    /// Builds the command running the task in the workspace, with the variables substituted.
+    pub fn command(&self, variables: &VariableContext) -> tokio::process::Command {
+        let program = variables.substitute_str(&self.command);
+        // arguments are either strings or `{ "value": ..., "quoting": ... }`
+        let args = self.args.iter().filter_map(|arg| match arg {
+            Value::String(arg) => Some(variables.substitute_str(arg)),
+            Value::Object(arg) => arg
+                .get("value")
+                .and_then(Value::as_str)
+                .map(|arg| variables.substitute_str(arg)),
+            _ => None,
+        });
+
+        let mut command = if self.ty.as_deref() == Some("process") {
+            let mut command = tokio::process::Command::new(program);
+            command.args(args);
+            command
+        } else {
+            // the command may use shell syntax, the arguments are quoted to be passed as they are
+            let line = std::iter::once(program)
+                .chain(args.map(|arg| quote_shell_arg(&arg).into_owned()))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let (shell, flag) = if cfg!(windows) {
+                ("cmd", "/C")
+            } else {
+                ("sh", "-c")
+            };
+            let mut command = tokio::process::Command::new(shell);
+            command.arg(flag).arg(line);
+            command
+        };
+
+        let cwd = match &self.options.cwd {
+            Some(cwd) => PathBuf::from(variables.substitute_str(cwd)),
+            None => variables.workspace_folder.clone(),
+        };
+        command.current_dir(cwd);
+        for (key, value) in &self.options.env {
+            if let Some(value) = value.as_str() {
+                command.env(key, variables.substitute_str(value));
+            }
+        }
+        command
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct LaunchConfigs {
+    #[serde(default)]
+    pub configurations: Vec<LaunchConfig>,
+    #[serde(default)]
+    pub compounds: Vec<Compound>,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+}
+
+impl LaunchConfigs {
+    /// Reads the configurations of the workspace at `root`. Those of `.helix/debug.toml` come
+    /// first, files that don't exist are skipped.
+    pub fn load(root: &Path) -> anyhow::Result<Self> {
+        let mut configs = Self::default();
+
+        let path = root.join(".helix").join("debug.toml");
+        if let Some(text) = read_if_exists(&path)? {
+            let helix: Self = toml::from_str(&text)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            configs.extend(helix);
+        }
+        for path in [
+            root.join(".vscode").join("launch.json"),
+            root.join(".vscode").join("tasks.json"),
+        ] {
+            if let Some(text) = read_if_exists(&path)? {
+                let vscode: Self = serde_json::from_str(&strip_json_comments(&text))
+                    .with_context(|| format!("Failed to parse {}", path.display()))?;
+                configs.extend(vscode);
+            }
+        }
+
+        for config in &mut configs.configurations {
+            config.apply_platform_overrides();
+        }
+        Ok(configs)
+    }
+
+    fn extend(&mut self, other: Self) {
+        self.configurations.extend(other.configurations);
+        self.compounds.extend(other.compounds);
+        self.tasks.extend(other.tasks);
+    }
+
+    pub fn task(&self, label: &str) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.label == label)
+    }
+
+    pub fn configuration(&self, name: &str) -> Option<&LaunchConfig> {
+        self.configurations
+            .iter()
+            .find(|config| config.name == name)
+    }
+}
+
+fn read_if_exists(path: &Path) -> anyhow::Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Removes the comments and trailing commas VS Code allows in its JSON files.
+pub fn strip_json_comments(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(ch) = chars.next() {
+        if in_string {
+            output.push(ch);
+            match ch {
+                '\\' => output.extend(chars.next()),
+                '"' => in_string = false,
+                _ => (),
+            }
+            continue;
+        }
+        match (ch, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(ch);
+            }
+            ('/', Some('/')) => {
+                for ch in chars.by_ref() {
+                    if ch == '\n' {
+                        output.push(ch);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = None;
+                for ch in chars.by_ref() {
+                    if previous == Some('*') && ch == '/' {
+                        break;
+                    }
+                    previous = Some(ch);
+                }
+            }
+            ('}' | ']', _) => {
+                let trimmed = output.trim_end().len();
+                if output[..trimmed].ends_with(',') {
+                    output.truncate(trimmed - 1);
+                }
+                output.push(ch);
+            }
+            _ => output.push(ch),
+        }
+    }
+    output
+}
+
+/// Quotes `arg` for the shell, `sh` or `cmd` on Windows, if it contains whitespace or special
+/// characters.
+pub fn quote_shell_arg(arg: &str) -> Cow<str> {
+    let is_plain = |ch: char| ch.is_ascii_alphanumeric() || "-_./=:,+@%".contains(ch);
+    if !arg.is_empty() && arg.chars().all(is_plain) {
+        arg.into()
+    } else if cfg!(windows) {
+        format!("\"{}\"", arg.replace('"', "\"\"")).into()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''")).into()
+    }
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Values of the `${...}` variables that can be used in configurations and tasks.
+#[derive(Debug, Clone, Default)]
+pub struct VariableContext {
+    pub workspace_folder: PathBuf,
+    /// The file of the current document.
+    pub file: Option<PathBuf>,
+    /// The (1-indexed) line of the cursor.
+    pub line_number: usize,
+    pub selected_text: String,
+}
+
+impl VariableContext {
+    fn resolve(&self, name: &str) -> Option<String> {
+        let file = self.file.as_deref();
+        let value = match name {
+            "workspaceFolder" | "workspaceRoot" => path_string(&self.workspace_folder),
+            "workspaceFolderBasename" => path_string(Path::new(self.workspace_folder.file_name()?)),
+            "file" => path_string(file?),
+            "fileBasename" => path_string(Path::new(file?.file_name()?)),
+            "fileBasenameNoExtension" => path_string(Path::new(file?.file_stem()?)),
+            "fileExtname" => file?
+                .extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy()))
+                .unwrap_or_default(),
+            "fileDirname" => path_string(file?.parent()?),
+            "relativeFile" => {
+                let file = file?;
+                path_string(file.strip_prefix(&self.workspace_folder).unwrap_or(file))
+            }
+            "cwd" => path_string(&std::env::current_dir().ok()?),
+            "lineNumber" => self.line_number.to_string(),
+            "selectedText" => self.selected_text.clone(),
+            "pathSeparator" => std::path::MAIN_SEPARATOR.to_string(),
+            _ => std::env::var(name.strip_prefix("env:")?).unwrap_or_default(),
+        };
+        Some(value)
+    }
+
+    /// Replaces the variables in `text`. Unknown variables, like `${input:...}`, are left as
+    /// they are.
+    pub fn substitute_str(&self, text: &str) -> String {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            output.push_str(&rest[..start]);
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+            match self.resolve(&rest[start + 2..end]) {
+                Some(value) => output.push_str(&value),
+                None => output.push_str(&rest[start..=end]),
+            }
+            rest = &rest[end + 1..];
+        }
+        output.push_str(rest);
+        output
+    }
+
+    /// Replaces the variables in all the strings of `value`.
+    pub fn substitute(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.substitute_str(text),
+            Value::Array(values) => values.iter_mut().for_each(|value| self.substitute(value)),
+            Value::Object(map) => map.values_mut().for_each(|value| self.substitute(value)),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn json_comments_and_trailing_commas_are_stripped() {
+        let text = r#"{
+            // a comment
+            "url": "http://localhost", /* another
+            comment */
+            "list": [1, 2,],
+        }"#;
+        let value: Value = serde_json::from_str(&strip_json_comments(text)).unwrap();
+        assert_eq!(value, json!({"url": "http://localhost", "list": [1, 2]}));
+    }
+
+    #[test]
+    fn launch_configurations_are_parsed() {
+        let text = r#"{
+            "version": "0.2.0",
+            "configurations": [{
+                "name": "Debug",
+                "type": "lldb",
+                "request": "launch",
+                "program": "${workspaceFolder}/target/debug/app",
+                "preLaunchTask": "build",
+                "linux": { "env": { "FOO": "bar" } },
+                "windows": { "program": "app.exe" },
+            }],
+            "compounds": [{ "name": "All", "configurations": ["Debug"] }],
+        }"#;
+        let mut configs: LaunchConfigs = serde_json::from_str(&strip_json_comments(text)).unwrap();
+        let config = &mut configs.configurations[0];
+        config.apply_platform_overrides();
+        assert_eq!(config.ty, "lldb");
+        assert_eq!(config.pre_launch_task.as_deref(), Some("build"));
+        assert!(!config.args.contains_key("windows"));
+        assert!(!config.args.contains_key("linux"));
+        assert!(config.args.contains_key("program"));
+        assert_eq!(configs.compounds[0].configurations, ["Debug"]);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn shell_task_arguments_are_quoted() {
+        let task = Task {
+            label: "test".into(),
+            ty: Some("shell".into()),
+            command: "cargo test".into(),
+            args: vec![json!("--"), json!("it's a test"), json!({ "value": "a b" })],
+            options: TaskOptions::default(),
+        };
+        let command = task.command(&VariableContext::default());
+        let args: Vec<_> = command.as_std().get_args().collect();
+        assert_eq!(args, ["-c", "cargo test -- 'it'\\''s a test' 'a b'"]);
+    }
+
+    #[test]
+    fn variables_are_substituted() {
+        let variables = VariableContext {
+            workspace_folder: PathBuf::from("/project"),
+            file: Some(PathBuf::from("/project/src/main.rs")),
+            line_number: 3,
+            selected_text: String::new(),
+        };
+        let mut value = json!({
+            "program": "${workspaceFolder}/bin/${fileBasenameNoExtension}",
+            "args": ["${relativeFile}:${lineNumber}", "${input:unknown}"],
+        });
+        variables.substitute(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "program": "/project/bin/main",
+                "args": ["src/main.rs:3", "${input:unknown}"],
+            })
+        );
+    }
+}
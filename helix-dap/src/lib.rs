@@ -1,9 +1,13 @@
 mod client;
+pub mod launch;
+pub mod process;
+mod registry;
 mod transport;
 mod types;
 
 pub use client::Client;
 pub use events::Event;
+pub use registry::Registry;
 pub use transport::{Payload, Response, Transport};
 pub use types::*;
 
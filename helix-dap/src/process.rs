@@ -0,0 +1,132 @@
+//! The processes running on the machine, which a debugger can be attached to.
+
+#[cfg(target_os = "linux")]
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Process {
+    pub pid: u32,
+    pub name: String,
+    /// The arguments the process was started with, separated by spaces.
+    pub command_line: String,
+    /// Name of the user owning the process, or their id if it is unknown.
+    pub user: String,
+}
+
+/// Lists the running processes, sorted by PID. Only Linux is supported for now, through `/proc`.
+#[cfg(target_os = "linux")]
+pub fn list() -> std::io::Result<Vec<Process>> {
+    use std::fs;
+
+    let users = fs::read_to_string("/etc/passwd")
+        .map(|passwd| parse_passwd(&passwd))
+        .unwrap_or_default();
+
+    let mut processes = Vec::new();
+    for entry in fs::read_dir("/proc")? {
+        let entry = entry?;
+        let pid = match entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse().ok())
+        {
+            Some(pid) => pid,
+            None => continue,
+        };
+        // processes may exit while they are listed
+        let status = match fs::read_to_string(entry.path().join("status")) {
+            Ok(status) => status,
+            Err(_) => continue,
+        };
+        let (name, uid) = parse_status(&status);
+        let command_line = fs::read(entry.path().join("cmdline"))
+            .map(|cmdline| parse_cmdline(&cmdline))
+            .unwrap_or_default();
+        let user = match uid {
+            Some(uid) => users.get(&uid).cloned().unwrap_or_else(|| uid.to_string()),
+            None => String::new(),
+        };
+        processes.push(Process {
+            pid,
+            name: name.unwrap_or_default(),
+            command_line,
+            user,
+        });
+    }
+    processes.sort_by_key(|process| process.pid);
+    Ok(processes)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn list() -> std::io::Result<Vec<Process>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Listing processes is only supported on Linux",
+    ))
+}
+
+/// Reads the name and the real user id from the contents of `/proc/<pid>/status`.
+#[cfg(target_os = "linux")]
+fn parse_status(status: &str) -> (Option<String>, Option<u32>) {
+    let mut name = None;
+    let mut uid = None;
+    for line in status.lines() {
+        if let Some(value) = line.strip_prefix("Name:") {
+            name = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("Uid:") {
+            uid = value
+                .split_whitespace()
+                .next()
+                .and_then(|uid| uid.parse().ok());
+        }
+    }
+    (name, uid)
+}
+
+/// The arguments of `/proc/<pid>/cmdline` are separated by NUL bytes.
+#[cfg(target_os = "linux")]
+fn parse_cmdline(cmdline: &[u8]) -> String {
+    cmdline
+        .split(|&byte| byte == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(String::from_utf8_lossy)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Maps the user ids of `/etc/passwd` to the user names.
+#[cfg(target_os = "linux")]
+fn parse_passwd(passwd: &str) -> HashMap<u32, String> {
+    passwd
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proc_files_are_parsed() {
+        let status =
+            "Name:\tcargo\nUmask:\t0022\nState:\tS (sleeping)\nUid:\t1000\t1000\t1000\t1000\n";
+        assert_eq!(
+            parse_status(status),
+            (Some("cargo".to_string()), Some(1000))
+        );
+        assert_eq!(
+            parse_cmdline(b"cargo\0run\0--release\0"),
+            "cargo run --release"
+        );
+        let users =
+            parse_passwd("root:x:0:0:root:/root:/bin/sh\nuser:x:1000:1000::/home/user:/bin/sh\n");
+        assert_eq!(users.get(&1000).map(String::as_str), Some("user"));
+        assert_eq!(users.get(&0).map(String::as_str), Some("root"));
+    }
+}
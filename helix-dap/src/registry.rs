@@ -0,0 +1,103 @@
+use crate::{Client, Payload};
+use futures_util::stream::SelectAll;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// The running debug sessions, one of which is active: commands and views of the editor act on
+/// the active session. Each session has a unique id assigned through [`Registry::next_id`],
+/// which its messages are tagged with.
+#[derive(Debug)]
+pub struct Registry {
+    /// In the order the sessions were started.
+    inner: Vec<Client>,
+    active: Option<usize>,
+
+    counter: usize,
+    pub incoming: SelectAll<UnboundedReceiverStream<(usize, Payload)>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self {
+            inner: Vec::new(),
+            active: None,
+            counter: 0,
+            incoming: SelectAll::new(),
+        }
+    }
+
+    /// The id to start the next client with.
+    pub fn next_id(&mut self) -> usize {
+        let id = self.counter;
+        self.counter += 1;
+        id
+    }
+
+    /// Adds a started session, which becomes the active one.
+    pub fn insert(&mut self, client: Client, incoming: UnboundedReceiver<(usize, Payload)>) {
+        self.active = Some(client.id());
+        self.inner.push(client);
+        self.incoming.push(UnboundedReceiverStream::new(incoming));
+    }
+
+    /// Removes the session, making the most recently started of the others active if it was.
+    pub fn remove(&mut self, id: usize) -> Option<Client> {
+        let index = self.inner.iter().position(|client| client.id() == id)?;
+        let client = self.inner.remove(index);
+        if self.active == Some(id) {
+            self.active = self.inner.last().map(Client::id);
+        }
+        Some(client)
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Client> {
+        self.inner.iter().find(|client| client.id() == id)
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Client> {
+        self.inner.iter_mut().find(|client| client.id() == id)
+    }
+
+    pub fn active_id(&self) -> Option<usize> {
+        self.active
+    }
+
+    pub fn get_active(&self) -> Option<&Client> {
+        self.get(self.active?)
+    }
+
+    pub fn get_active_mut(&mut self) -> Option<&mut Client> {
+        self.get_mut(self.active?)
+    }
+
+    /// Makes the session active, returning whether it exists.
+    pub fn set_active(&mut self, id: usize) -> bool {
+        let exists = self.get(id).is_some();
+        if exists {
+            self.active = Some(id);
+        }
+        exists
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Client> {
+        self.inner.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Client> {
+        self.inner.iter_mut()
+    }
+}
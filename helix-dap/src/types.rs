@@ -173,6 +173,73 @@ pub struct SourceBreakpoint {
     pub log_message: Option<String>,
 }
 
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionBreakpoint {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataBreakpoint {
+    pub data_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionFilterOptions {
+    pub filter_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembledInstruction {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_bytes: Option<String>,
+    pub instruction: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Source>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GotoTarget {
+    pub id: usize,
+    pub label: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_pointer_reference: Option<String>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Breakpoint {
@@ -308,6 +375,28 @@ pub struct Variable {
     pub memory_reference: Option<String>,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionItem {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_start: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_length: Option<usize>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Module {
@@ -362,6 +451,8 @@ pub struct InitializeArguments {
         pub supports_progress_reporting: Option<bool>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub supports_invalidated_event: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub supports_start_debugging_request: Option<bool>,
     }
 
     #[derive(Debug)]
@@ -436,6 +527,76 @@ impl Request for SetBreakpoints {
         const COMMAND: &'static str = "setBreakpoints";
     }
 
+    #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetFunctionBreakpointsArguments {
+        pub breakpoints: Vec<FunctionBreakpoint>,
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetFunctionBreakpointsResponse {
+        pub breakpoints: Vec<Breakpoint>,
+    }
+
+    #[derive(Debug)]
+    pub enum SetFunctionBreakpoints {}
+
+    impl Request for SetFunctionBreakpoints {
+        type Arguments = SetFunctionBreakpointsArguments;
+        type Result = SetFunctionBreakpointsResponse;
+        const COMMAND: &'static str = "setFunctionBreakpoints";
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DataBreakpointInfoArguments {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub variables_reference: Option<usize>,
+        pub name: String,
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DataBreakpointInfoResponse {
+        pub data_id: Option<String>,
+        pub description: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub access_types: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub can_persist: Option<bool>,
+    }
+
+    #[derive(Debug)]
+    pub enum DataBreakpointInfo {}
+
+    impl Request for DataBreakpointInfo {
+        type Arguments = DataBreakpointInfoArguments;
+        type Result = DataBreakpointInfoResponse;
+        const COMMAND: &'static str = "dataBreakpointInfo";
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetDataBreakpointsArguments {
+        pub breakpoints: Vec<DataBreakpoint>,
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetDataBreakpointsResponse {
+        pub breakpoints: Vec<Breakpoint>,
+    }
+
+    #[derive(Debug)]
+    pub enum SetDataBreakpoints {}
+
+    impl Request for SetDataBreakpoints {
+        type Arguments = SetDataBreakpointsArguments;
+        type Result = SetDataBreakpointsResponse;
+        const COMMAND: &'static str = "setDataBreakpoints";
+    }
+
     #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct ContinueArguments {
@@ -588,6 +749,152 @@ impl Request for StepOut {
         const COMMAND: &'static str = "stepOut";
     }
 
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StepBackArguments {
+        pub thread_id: ThreadId,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub granularity: Option<String>,
+    }
+
+    #[derive(Debug)]
+    pub enum StepBack {}
+
+    impl Request for StepBack {
+        type Arguments = StepBackArguments;
+        type Result = ();
+        const COMMAND: &'static str = "stepBack";
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ReverseContinueArguments {
+        pub thread_id: ThreadId,
+    }
+
+    #[derive(Debug)]
+    pub enum ReverseContinue {}
+
+    impl Request for ReverseContinue {
+        type Arguments = ReverseContinueArguments;
+        type Result = ();
+        const COMMAND: &'static str = "reverseContinue";
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RestartFrameArguments {
+        pub frame_id: usize,
+    }
+
+    #[derive(Debug)]
+    pub enum RestartFrame {}
+
+    impl Request for RestartFrame {
+        type Arguments = RestartFrameArguments;
+        type Result = ();
+        const COMMAND: &'static str = "restartFrame";
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DisassembleArguments {
+        pub memory_reference: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub offset: Option<isize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub instruction_offset: Option<isize>,
+        pub instruction_count: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub resolve_symbols: Option<bool>,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DisassembleResponse {
+        pub instructions: Vec<DisassembledInstruction>,
+    }
+
+    #[derive(Debug)]
+    pub enum Disassemble {}
+
+    impl Request for Disassemble {
+        type Arguments = DisassembleArguments;
+        type Result = DisassembleResponse;
+        const COMMAND: &'static str = "disassemble";
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ReadMemoryArguments {
+        pub memory_reference: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub offset: Option<isize>,
+        pub count: usize,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ReadMemoryResponse {
+        /// Address of the first byte, in hexadecimal.
+        pub address: String,
+        /// Number of bytes that couldn't be read after the returned ones.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub unreadable_bytes: Option<usize>,
+        /// The bytes read, encoded in base64.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub data: Option<String>,
+    }
+
+    #[derive(Debug)]
+    pub enum ReadMemory {}
+
+    impl Request for ReadMemory {
+        type Arguments = ReadMemoryArguments;
+        type Result = ReadMemoryResponse;
+        const COMMAND: &'static str = "readMemory";
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GotoTargetsArguments {
+        pub source: Source,
+        pub line: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub column: Option<usize>,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GotoTargetsResponse {
+        pub targets: Vec<GotoTarget>,
+    }
+
+    #[derive(Debug)]
+    pub enum GotoTargets {}
+
+    impl Request for GotoTargets {
+        type Arguments = GotoTargetsArguments;
+        type Result = GotoTargetsResponse;
+        const COMMAND: &'static str = "gotoTargets";
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GotoArguments {
+        pub thread_id: ThreadId,
+        pub target_id: usize,
+    }
+
+    #[derive(Debug)]
+    pub enum Goto {}
+
+    impl Request for Goto {
+        type Arguments = GotoArguments;
+        type Result = ();
+        const COMMAND: &'static str = "goto";
+    }
+
     #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct NextArguments {
@@ -658,12 +965,82 @@ impl Request for Evaluate {
         const COMMAND: &'static str = "evaluate";
     }
 
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetVariableArguments {
+        pub variables_reference: usize,
+        pub name: String,
+        pub value: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub format: Option<ValueFormat>,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetVariableResponse {
+        pub value: String,
+        #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+        pub ty: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub variables_reference: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub named_variables: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub indexed_variables: Option<usize>,
+    }
+
+    #[derive(Debug)]
+    pub enum SetVariable {}
+
+    impl Request for SetVariable {
+        type Arguments = SetVariableArguments;
+        type Result = SetVariableResponse;
+        const COMMAND: &'static str = "setVariable";
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetExpressionArguments {
+        pub expression: String,
+        pub value: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub frame_id: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub format: Option<ValueFormat>,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetExpressionResponse {
+        pub value: String,
+        #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+        pub ty: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub presentation_hint: Option<VariablePresentationHint>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub variables_reference: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub named_variables: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub indexed_variables: Option<usize>,
+    }
+
+    #[derive(Debug)]
+    pub enum SetExpression {}
+
+    impl Request for SetExpression {
+        type Arguments = SetExpressionArguments;
+        type Result = SetExpressionResponse;
+        const COMMAND: &'static str = "setExpression";
+    }
+
     #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct SetExceptionBreakpointsArguments {
         pub filters: Vec<String>,
-        // pub filterOptions: Option<Vec<ExceptionFilterOptions>>, // needs capability
-        // pub exceptionOptions: Option<Vec<ExceptionOptions>>, // needs capability
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub filter_options: Option<Vec<ExceptionFilterOptions>>, // needs capability
+                                                                 // pub exceptionOptions: Option<Vec<ExceptionOptions>>, // needs capability
     }
 
     #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
@@ -682,6 +1059,32 @@ impl Request for SetExceptionBreakpoints {
         const COMMAND: &'static str = "setExceptionBreakpoints";
     }
 
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CompletionsArguments {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub frame_id: Option<usize>,
+        pub text: String,
+        pub column: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub line: Option<usize>,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CompletionsResponse {
+        pub targets: Vec<CompletionItem>,
+    }
+
+    #[derive(Debug)]
+    pub enum Completions {}
+
+    impl Request for Completions {
+        type Arguments = CompletionsArguments;
+        type Result = CompletionsResponse;
+        const COMMAND: &'static str = "completions";
+    }
+
     // Reverse Requests
 
     #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
@@ -714,6 +1117,23 @@ impl Request for RunInTerminal {
         type Result = RunInTerminalResponse;
         const COMMAND: &'static str = "runInTerminal";
     }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StartDebuggingRequestArguments {
+        /// Arguments of the `launch` or `attach` request of the new session.
+        pub configuration: serde_json::Map<String, Value>,
+        pub request: String,
+    }
+
+    #[derive(Debug)]
+    pub enum StartDebugging {}
+
+    impl Request for StartDebugging {
+        type Arguments = StartDebuggingRequestArguments;
+        type Result = ();
+        const COMMAND: &'static str = "startDebugging";
+    }
 }
 
 // Events
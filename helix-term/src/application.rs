@@ -8,9 +8,10 @@
 use helix_lsp::{lsp, util::lsp_pos_to_pos, LspProgressMap};
 use helix_view::{
     align_view,
-    document::DocumentSavedEventResult,
+    document::{DocumentSavedEventResult, Mode},
     editor::{ConfigEvent, EditorEvent},
     graphics::Rect,
+    terminal::TerminalEvent,
     theme,
     tree::Layout,
     Align, Editor,
@@ -25,12 +26,13 @@
     config::Config,
     job::Jobs,
     keymap::Keymaps,
-    ui::{self, overlay::overlayed},
+    ui,
 };
 
 use log::{debug, error, warn};
 use std::{
     io::{stdin, stdout, Write},
+    path::Path,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -183,18 +185,46 @@ pub fn new(
         let editor_view = Box::new(ui::EditorView::new(Keymaps::new(keys)));
         compositor.push(editor_view);
 
+        let mut jobs = Jobs::new();
         if args.load_tutor {
             let path = helix_loader::runtime_dir().join("tutor");
             editor.open(&path, Action::VerticalSplit)?;
             // Unset path to prevent accidentally saving to the original tutor file.
             doc_mut!(editor).set_path(None)?;
+        } else if args.diff {
+            // the old file is shown read-only, like when used as the difftool of git
+            let (old, new) = (&args.files[0].0, &args.files[1].0);
+            let old_id = editor
+                .open(old, Action::VerticalSplit)
+                .context(format!("open '{}'", old.to_string_lossy()))?;
+            doc_mut!(editor, &old_id).readonly = true;
+            let new_id = editor
+                .open(new, Action::Load)
+                .context(format!("open '{}'", new.to_string_lossy()))?;
+            editor.diff_split(old_id, new_id);
+        } else if args.merge {
+            // like when used as the mergetool of git, the merged file is shown in between the
+            // local and remote files, and the common ancestor is only loaded
+            let open = |editor: &mut Editor, file: &Path, action| {
+                editor
+                    .open(file, action)
+                    .context(format!("open '{}'", file.to_string_lossy()))
+            };
+            let local_id = open(&mut editor, &args.files[1].0, Action::VerticalSplit)?;
+            let base_id = open(&mut editor, &args.files[0].0, Action::Load)?;
+            open(&mut editor, &args.files[3].0, Action::VerticalSplit)?;
+            let merged_view = editor.tree.focus;
+            let remote_id = open(&mut editor, &args.files[2].0, Action::VerticalSplit)?;
+            for id in [local_id, base_id, remote_id] {
+                doc_mut!(editor, &id).readonly = true;
+            }
+            editor.focus(merged_view);
         } else if !args.files.is_empty() {
             let first = &args.files[0].0; // we know it's not empty
             if first.is_dir() {
                 std::env::set_current_dir(first).context("set current dir")?;
                 editor.new_file(Action::VerticalSplit);
-                let picker = ui::file_picker(".".into(), &config.load().editor);
-                compositor.push(Box::new(overlayed(picker)));
+                ui::open_file_picker(&mut jobs, &editor, ".".into());
             } else {
                 let nr_of_files = args.files.len();
                 for (i, (file, pos)) in args.files.into_iter().enumerate() {
@@ -252,6 +282,13 @@ pub fn new(
 
         editor.set_theme(theme);
 
+        // tests shouldn't pick up or overwrite the breakpoints of the user
+        if !cfg!(feature = "integration") {
+            if let Err(err) = editor.load_breakpoints() {
+                log::error!("Failed to load breakpoints: {}", err);
+            }
+        }
+
         #[cfg(windows)]
         let signals = futures_util::stream::empty();
         #[cfg(not(windows))]
@@ -269,7 +306,7 @@ pub fn new(
             syn_loader,
 
             signals,
-            jobs: Jobs::new(),
+            jobs,
             lsp_progress: LspProgressMap::new(),
             last_render: Instant::now(),
         };
@@ -351,10 +388,12 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
                 }
                 Some(callback) = self.jobs.futures.next() => {
                     self.jobs.handle_callback(&mut self.editor, &mut self.compositor, callback);
+                    self.editor.remap_breakpoints();
                     self.render().await;
                 }
                 Some(callback) = self.jobs.wait_futures.next() => {
                     self.jobs.handle_callback(&mut self.editor, &mut self.compositor, callback);
+                    self.editor.remap_breakpoints();
                     self.render().await;
                 }
                 event = self.editor.wait_event() => {
@@ -378,6 +417,18 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
         }
     }
 
+    fn handle_terminal_event(&mut self, event: TerminalEvent) {
+        // output only needs a redraw
+        if let TerminalEvent::Exit(view_id) = event {
+            if let Some(terminal) = self.editor.terminals.get_mut(&view_id) {
+                terminal.set_exited();
+                if self.editor.tree.focus == view_id && self.editor.mode == Mode::Terminal {
+                    self.editor.enter_normal_mode();
+                }
+            }
+        }
+    }
+
     pub fn handle_config_events(&mut self, config_event: ConfigEvent) {
         match config_event {
             ConfigEvent::Refresh => self.refresh_config(),
@@ -496,6 +547,7 @@ pub async fn handle_idle_timeout(&mut self) {
             scroll: None,
         };
         let should_render = self.compositor.handle_event(&Event::IdleTimeout, &mut cx);
+        crate::commands::send_moved_breakpoints(&mut self.editor, &mut self.jobs);
         if should_render || self.editor.needs_redraw {
             self.render().await;
         }
@@ -554,6 +606,8 @@ pub fn handle_document_write(&mut self, doc_save_event: DocumentSavedEventResult
             let _ = self.editor.refresh_language_server(id);
         }
 
+        self.editor.refresh_repo_status(doc_save_event.doc_id);
+
         // TODO: fix being overwritten by lsp
         self.editor.set_status(format!(
             "'{}' written, {}L {}B",
@@ -586,12 +640,20 @@ pub async fn handle_editor_event(&mut self, event: EditorEvent) -> bool {
                     self.last_render = Instant::now();
                 }
             }
-            EditorEvent::DebuggerEvent(payload) => {
-                let needs_render = self.editor.handle_debugger_message(payload).await;
+            EditorEvent::DebuggerEvent((id, payload)) => {
+                let needs_render = self.editor.handle_debugger_message(id, payload).await;
                 if needs_render {
                     self.render().await;
                 }
             }
+            EditorEvent::TerminalEvent(event) => {
+                self.handle_terminal_event(event);
+                self.render().await;
+            }
+            EditorEvent::ReplEvent(event) => {
+                self.editor.handle_repl_event(event);
+                self.render().await;
+            }
             EditorEvent::IdleTimer => {
                 self.editor.clear_idle_timer();
                 self.handle_idle_timeout().await;
@@ -631,6 +693,7 @@ pub async fn handle_terminal_events(
             }
             event => self.compositor.handle_event(&event.into(), &mut cx),
         };
+        self.editor.remap_breakpoints();
 
         if should_redraw && !self.editor.should_close() {
             self.render().await;
@@ -1110,6 +1173,14 @@ pub async fn close(&mut self) -> Vec<anyhow::Error> {
             errs.push(err);
         }
 
+        if !cfg!(feature = "integration") {
+            self.editor.remap_breakpoints();
+            if let Err(err) = self.editor.save_breakpoints() {
+                log::error!("Error saving breakpoints: {}", err);
+                errs.push(err);
+            }
+        }
+
         if self.editor.close_language_servers(None).await.is_err() {
             log::error!("Timed out waiting for language servers to shutdown");
             errs.push(anyhow::format_err!(
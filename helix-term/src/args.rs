@@ -13,6 +13,8 @@ pub struct Args {
     pub fetch_grammars: bool,
     pub build_grammars: bool,
     pub split: Option<Layout>,
+    pub diff: bool,
+    pub merge: bool,
     pub verbosity: u64,
     pub log_file: Option<PathBuf>,
     pub config_file: Option<PathBuf>,
@@ -40,6 +42,8 @@ pub fn parse_args() -> Result<Args> {
                     Some(_) => anyhow::bail!("can only set a split once of a specific type"),
                     None => args.split = Some(Layout::Horizontal),
                 },
+                "--diff" => args.diff = true,
+                "--merge" => args.merge = true,
                 "--health" => {
                     args.health = true;
                     args.health_arg = argv.next_if(|opt| !opt.starts_with('-'));
@@ -82,6 +86,13 @@ pub fn parse_args() -> Result<Args> {
             args.files.push(parse_file(&arg));
         }
 
+        if args.diff && args.files.len() != 2 {
+            anyhow::bail!("--diff must be followed by two files to compare");
+        }
+        if args.merge && args.files.len() != 4 {
+            anyhow::bail!("--merge must be followed by the base, local, remote and merged files");
+        }
+
         Ok(args)
     }
 }
@@ -1,12 +1,14 @@
 pub(crate) mod dap;
 pub(crate) mod lsp;
 pub(crate) mod typed;
+pub(crate) mod vcs;
 
 pub use dap::*;
 use helix_vcs::Hunk;
 pub use lsp::*;
 use tui::widgets::Row;
 pub use typed::*;
+pub use vcs::*;
 
 use helix_core::{
     comment, coords_at_pos, encoding, find_first_non_whitespace_char, find_root, graphemes,
@@ -28,7 +30,7 @@
 use helix_view::{
     clipboard::ClipboardType,
     document::{FormatterError, Mode, SCRATCH_BUFFER_NAME},
-    editor::{Action, Motion},
+    editor::{Action, FilePickerConfig, Motion, SearchBufferLine},
     info::Info,
     input::KeyEvent,
     keyboard::KeyCode,
@@ -53,7 +55,10 @@
 use crate::job::{self, Jobs};
 use futures_util::StreamExt;
 use std::{collections::HashMap, fmt, future::Future};
-use std::{collections::HashSet, num::NonZeroUsize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    num::NonZeroUsize,
+};
 
 use std::{
     borrow::Cow,
@@ -251,6 +256,8 @@ pub fn doc(&self) -> &str {
         search_selection, "Use current selection as search pattern",
         make_search_word_bounded, "Modify current search to make it word bounded",
         global_search, "Global search in workspace folder",
+        global_replace, "Global search and replace in workspace folder",
+        global_search_buffer, "Global search in workspace folder, listing matches in an editable buffer",
         extend_line, "Select current line, if already selected, extend to another line based on the anchor",
         extend_line_below, "Select current line, if already selected, extend to next line",
         extend_line_above, "Select current line, if already selected, extend to previous line",
@@ -268,6 +275,9 @@ pub fn doc(&self) -> &str {
         command_mode, "Enter command mode",
         file_picker, "Open file picker",
         file_picker_in_current_directory, "Open file picker at current working directory",
+        toggle_file_explorer, "Open, focus or close the file explorer",
+        terminal_send_selection, "Send selections to the last used terminal",
+        repl_send_selection, "Send selections to the REPL of the language",
         code_action, "Perform code action",
         buffer_picker, "Open buffer picker",
         jumplist_picker, "Open jumplist picker",
@@ -312,6 +322,18 @@ pub fn doc(&self) -> &str {
         goto_prev_change, "Goto previous change",
         goto_first_change, "Goto first change",
         goto_last_change, "Goto last change",
+        blame_line, "Show the commit that last changed the line",
+        revert_hunk, "Revert the change at the cursor",
+        stage_hunk, "Stage the change at the cursor",
+        unstage_hunk, "Unstage the change at the cursor",
+        preview_hunk, "Show the change at the cursor",
+        changed_file_picker, "Open changed file picker",
+        goto_next_conflict, "Goto next conflict",
+        goto_prev_conflict, "Goto previous conflict",
+        conflict_accept_ours, "Resolve the conflict at the cursor with our side",
+        conflict_accept_theirs, "Resolve the conflict at the cursor with their side",
+        conflict_accept_both, "Resolve the conflict at the cursor with both sides",
+        conflict_accept_base, "Resolve the conflict at the cursor with the common ancestor",
         goto_line_start, "Goto line start",
         goto_line_end, "Goto line end",
         goto_next_buffer, "Goto next buffer",
@@ -418,20 +440,34 @@ pub fn doc(&self) -> &str {
         goto_next_paragraph, "Goto next paragraph",
         goto_prev_paragraph, "Goto previous paragraph",
         dap_launch, "Launch debug target",
+        dap_attach, "Attach debugger to a running process",
         dap_toggle_breakpoint, "Toggle breakpoint",
         dap_continue, "Continue program execution",
         dap_pause, "Pause program execution",
         dap_step_in, "Step in",
         dap_step_out, "Step out",
         dap_next, "Step to next",
-        dap_variables, "List variables",
+        dap_run_to_cursor, "Continue to the line of the cursor",
+        dap_goto_cursor, "Jump execution to the line of the cursor",
+        dap_restart_frame, "Restart current stack frame",
+        dap_step_back, "Step back",
+        dap_reverse_continue, "Continue program execution backwards",
+        dap_disassembly, "Show disassembly around the current instruction",
+        dap_variables, "Browse variables",
         dap_terminate, "End debug session",
         dap_edit_condition, "Edit breakpoint condition on current line",
         dap_edit_log, "Edit breakpoint log message on current line",
+        dap_breakpoint_picker, "Open breakpoint picker",
+        dap_console, "Open debug console",
+        dap_watches, "Open watched expressions",
+        dap_toggle_inline_values, "Toggle inline variable values",
         dap_switch_thread, "Switch current thread",
         dap_switch_stack_frame, "Switch stack frame",
+        dap_switch_session, "Switch debug session",
         dap_enable_exceptions, "Enable exception breakpoints",
         dap_disable_exceptions, "Disable exception breakpoints",
+        dap_exception_breakpoints, "Pick exception breakpoint filters",
+        dap_toggle_function_breakpoint, "Toggle breakpoint on function",
         shell_pipe, "Pipe selections through shell command",
         shell_pipe_to, "Pipe selections into shell command ignoring output",
         shell_insert_output, "Insert shell command output before selections",
@@ -1855,6 +1891,81 @@ fn make_search_word_bounded(cx: &mut Context) {
     cx.editor.set_status(msg);
 }
 
+/// Searches every file in the current working directory for lines matching `regex`,
+/// honouring the ignore settings of the file picker. `on_match` is called (possibly
+/// from several threads at once) with the path, the 0 indexed line number and the
+/// contents of each matching line.
+fn search_workspace<F>(
+    regex: &Regex,
+    smart_case: bool,
+    file_picker_config: &FilePickerConfig,
+    on_match: F,
+) where
+    F: Fn(&Path, usize, &str) + Clone + Send,
+{
+    let matcher = match RegexMatcherBuilder::new()
+        .case_smart(smart_case)
+        .build(regex.as_str())
+    {
+        Ok(matcher) => matcher,
+        // Otherwise do nothing
+        // log::warn!("Global Search Invalid Pattern")
+        Err(_) => return,
+    };
+
+    let searcher = SearcherBuilder::new()
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .build();
+
+    let search_root =
+        std::env::current_dir().expect("Global search error: Failed to get current dir");
+    WalkBuilder::new(search_root)
+        .hidden(file_picker_config.hidden)
+        .parents(file_picker_config.parents)
+        .ignore(file_picker_config.ignore)
+        .follow_links(file_picker_config.follow_symlinks)
+        .git_ignore(file_picker_config.git_ignore)
+        .git_global(file_picker_config.git_global)
+        .git_exclude(file_picker_config.git_exclude)
+        .max_depth(file_picker_config.max_depth)
+        // We always want to ignore the .git directory, otherwise if
+        // `ignore` is turned off above, we end up with a lot of noise
+        // in our picker.
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build_parallel()
+        .run(|| {
+            let mut searcher = searcher.clone();
+            let matcher = matcher.clone();
+            let on_match = on_match.clone();
+            Box::new(move |entry: Result<DirEntry, ignore::Error>| -> WalkState {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(_) => return WalkState::Continue,
+                };
+
+                match entry.file_type() {
+                    Some(entry) if entry.is_file() => {}
+                    // skip everything else
+                    _ => return WalkState::Continue,
+                };
+
+                let result = searcher.search_path(
+                    &matcher,
+                    entry.path(),
+                    sinks::UTF8(|line_num, line| {
+                        on_match(entry.path(), line_num as usize - 1, line);
+                        Ok(true)
+                    }),
+                );
+
+                if let Err(err) = result {
+                    log::error!("Global search error: {}, {}", entry.path().display(), err);
+                }
+                WalkState::Continue
+            })
+        });
+}
+
 fn global_search(cx: &mut Context) {
     #[derive(Debug)]
     struct FileResult {
@@ -1915,72 +2026,17 @@ fn format(&self, current_path: &Self::Data) -> Row {
                 return;
             }
 
-            if let Ok(matcher) = RegexMatcherBuilder::new()
-                .case_smart(smart_case)
-                .build(regex.as_str())
-            {
-                let searcher = SearcherBuilder::new()
-                    .binary_detection(BinaryDetection::quit(b'\x00'))
-                    .build();
-
-                let search_root = std::env::current_dir()
-                    .expect("Global search error: Failed to get current dir");
-                WalkBuilder::new(search_root)
-                    .hidden(file_picker_config.hidden)
-                    .parents(file_picker_config.parents)
-                    .ignore(file_picker_config.ignore)
-                    .follow_links(file_picker_config.follow_symlinks)
-                    .git_ignore(file_picker_config.git_ignore)
-                    .git_global(file_picker_config.git_global)
-                    .git_exclude(file_picker_config.git_exclude)
-                    .max_depth(file_picker_config.max_depth)
-                    // We always want to ignore the .git directory, otherwise if
-                    // `ignore` is turned off above, we end up with a lot of noise
-                    // in our picker.
-                    .filter_entry(|entry| entry.file_name() != ".git")
-                    .build_parallel()
-                    .run(|| {
-                        let mut searcher = searcher.clone();
-                        let matcher = matcher.clone();
-                        let all_matches_sx = all_matches_sx.clone();
-                        Box::new(move |entry: Result<DirEntry, ignore::Error>| -> WalkState {
-                            let entry = match entry {
-                                Ok(entry) => entry,
-                                Err(_) => return WalkState::Continue,
-                            };
-
-                            match entry.file_type() {
-                                Some(entry) if entry.is_file() => {}
-                                // skip everything else
-                                _ => return WalkState::Continue,
-                            };
-
-                            let result = searcher.search_path(
-                                &matcher,
-                                entry.path(),
-                                sinks::UTF8(|line_num, _| {
-                                    all_matches_sx
-                                        .send(FileResult::new(entry.path(), line_num as usize - 1))
-                                        .unwrap();
-
-                                    Ok(true)
-                                }),
-                            );
-
-                            if let Err(err) = result {
-                                log::error!(
-                                    "Global search error: {}, {}",
-                                    entry.path().display(),
-                                    err
-                                );
-                            }
-                            WalkState::Continue
-                        })
-                    });
-            } else {
-                // Otherwise do nothing
-                // log::warn!("Global Search Invalid Pattern")
-            }
+            let all_matches_sx = all_matches_sx.clone();
+            search_workspace(
+                &regex,
+                smart_case,
+                &file_picker_config,
+                move |path, line_num, _line| {
+                    all_matches_sx
+                        .send(FileResult::new(path, line_num))
+                        .unwrap();
+                },
+            );
         },
     );
 
@@ -2000,35 +2056,349 @@ fn format(&self, current_path: &Self::Data) -> Row {
                     all_matches,
                     current_path,
                     move |cx, FileResult { path, line_num }, action| {
-                        match cx.editor.open(path, action) {
-                            Ok(_) => {}
-                            Err(e) => {
-                                cx.editor.set_error(format!(
-                                    "Failed to open file '{}': {}",
-                                    path.display(),
-                                    e
-                                ));
-                                return;
-                            }
-                        }
+                        open_file_at_line(cx.editor, path, *line_num, action)
+                    },
+                    |_editor, FileResult { path, line_num }| {
+                        Some((path.clone().into(), Some((*line_num, *line_num))))
+                    },
+                );
+                compositor.push(Box::new(overlayed(picker)));
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(show_picker);
+}
+
+fn global_search_buffer(cx: &mut Context) {
+    let (all_matches_sx, all_matches_rx) =
+        tokio::sync::mpsc::unbounded_channel::<(PathBuf, usize, String)>();
+    let config = cx.editor.config();
+    let smart_case = config.search.smart_case;
+    let file_picker_config = config.file_picker.clone();
+
+    let reg = cx.register.unwrap_or('/');
+
+    let completions = search_completions(cx, Some(reg));
+    ui::regex_prompt(
+        cx,
+        "global-search:".into(),
+        Some(reg),
+        move |_editor: &Editor, input: &str| {
+            completions
+                .iter()
+                .filter(|comp| comp.starts_with(input))
+                .map(|comp| (0.., std::borrow::Cow::Owned(comp.clone())))
+                .collect()
+        },
+        move |_editor, regex, event| {
+            if event != PromptEvent::Validate {
+                return;
+            }
+
+            let all_matches_sx = all_matches_sx.clone();
+            search_workspace(
+                &regex,
+                smart_case,
+                &file_picker_config,
+                move |path, line_num, line| {
+                    all_matches_sx
+                        .send((path.to_path_buf(), line_num, line.to_owned()))
+                        .unwrap();
+                },
+            );
+        },
+    );
+
+    let show_buffer = async move {
+        let mut all_matches: Vec<_> = UnboundedReceiverStream::new(all_matches_rx).collect().await;
+        all_matches.sort_unstable();
+        let call: job::Callback = Callback::Editor(Box::new(move |editor: &mut Editor| {
+            if all_matches.is_empty() {
+                editor.set_status("No matches found");
+                return;
+            }
 
-                        let line_num = *line_num;
-                        let (view, doc) = current!(cx.editor);
-                        let text = doc.text();
-                        if line_num >= text.len_lines() {
-                            cx.editor.set_error("The line you jumped to does not exist anymore because the file has changed.");
+            let mut contents = String::new();
+            let mut lines = HashMap::new();
+            for (path, line_num, line) in all_matches {
+                let key = format!(
+                    "{}:{}",
+                    helix_core::path::get_relative_path(&path).display(),
+                    line_num + 1
+                );
+                contents.push_str(&format!("{}: {}\n", key, trim_line_ending(&line)));
+                lines.insert(
+                    key,
+                    SearchBufferLine {
+                        path,
+                        line_num,
+                        line,
+                    },
+                );
+            }
+
+            let doc = Document::from(Rope::from(contents), None);
+            let doc_id = editor.new_file_from_document(Action::Replace, doc);
+            editor.search_buffers.insert(doc_id, lines);
+        }));
+        Ok(call)
+    };
+    cx.jobs.callback(show_buffer);
+}
+
+fn trim_line_ending(line: &str) -> &str {
+    match get_line_ending_of_str(line) {
+        Some(ending) => &line[..line.len() - ending.as_str().len()],
+        None => line,
+    }
+}
+
+/// Writes the lines edited in a search results buffer back to the files they were found in.
+/// Lines whose `path:line` prefix was edited are ignored, and lines that changed in their file
+/// since the search are skipped and reported. The files open in the editor are changed through
+/// their documents, which are saved too. Returns the documents that were saved.
+fn write_search_buffer(editor: &mut Editor, doc_id: DocumentId) -> Vec<DocumentId> {
+    let doc = doc!(editor, &doc_id);
+    let lines = match editor.search_buffers.get(&doc_id) {
+        Some(lines) => lines,
+        None => return Vec::new(),
+    };
+
+    let mut edited = HashSet::new();
+    let mut edits = Vec::new();
+    let mut edited_keys = Vec::new();
+    for buffer_line in doc.text().lines() {
+        let buffer_line = String::from(buffer_line);
+        let buffer_line = trim_line_ending(&buffer_line);
+        // Paths and lines may both contain `: `, so look for the first known prefix
+        let (key, text) = match buffer_line
+            .match_indices(": ")
+            .map(|(i, _)| (&buffer_line[..i], &buffer_line[i + 2..]))
+            .find(|(key, _)| lines.contains_key(*key))
+        {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        // the same line may only be written back once
+        if !edited.insert(key.to_owned()) {
+            continue;
+        }
+
+        let original = &lines[key];
+        let original_text = trim_line_ending(&original.line);
+        if text == original_text {
+            continue;
+        }
+
+        edits.push(ui::ReplaceMatch {
+            path: original.path.clone(),
+            line_num: original.line_num,
+            line: original.line.clone(),
+            range: 0..original_text.len(),
+            replacement: text.to_owned(),
+            enabled: true,
+        });
+        edited_keys.push(key.to_owned());
+    }
+
+    if edits.is_empty() {
+        editor.set_status("No changes to write");
+        doc_mut!(editor, &doc_id).reset_modified();
+        return Vec::new();
+    }
+
+    let edited_lines: Vec<_> = edited_keys.into_iter().zip(edits.iter().cloned()).collect();
+    edits.sort_by(|a, b| (&a.path, a.line_num).cmp(&(&b.path, b.line_num)));
+    let applied: HashSet<_> = apply_replacements(editor, edits)
+        .into_iter()
+        .map(|replace_match| (replace_match.path, replace_match.line_num))
+        .collect();
+
+    // the replacements in open documents are only in memory until they're saved
+    let mut saved: Vec<_> = applied
+        .iter()
+        .filter_map(|(path, _)| editor.document_by_path(path).map(|doc| doc.id()))
+        .collect();
+    saved.sort_unstable();
+    saved.dedup();
+    for &id in &saved {
+        if let Err(err) = editor.save::<PathBuf>(id, None, false) {
+            editor.set_error(format!("Failed to save the replacements: {}", err));
+        }
+    }
+
+    let lines = editor.search_buffers.get_mut(&doc_id).unwrap();
+    let mut skipped = Vec::new();
+    for (key, edit) in edited_lines {
+        if !applied.contains(&(edit.path, edit.line_num)) {
+            skipped.push(key);
+            continue;
+        }
+        // Later writes compare against the contents written now
+        let original = lines.get_mut(&key).unwrap();
+        let line_ending = &original.line[edit.range.end..];
+        original.line = format!("{}{}", edit.replacement, line_ending);
+    }
+
+    if skipped.is_empty() {
+        doc_mut!(editor, &doc_id).reset_modified();
+    } else {
+        editor.set_error(format!(
+            "Skipped lines that changed since the search: {}",
+            skipped.join(", ")
+        ));
+    }
+    saved
+}
+
+/// Opens the file at `path` and selects its (0 indexed) line `line_num`.
+fn open_file_at_line(editor: &mut Editor, path: &Path, line_num: usize, action: Action) {
+    if let Err(e) = editor.open(path, action) {
+        editor.set_error(format!("Failed to open file '{}': {}", path.display(), e));
+        return;
+    }
+
+    let (view, doc) = current!(editor);
+    let text = doc.text();
+    if line_num >= text.len_lines() {
+        editor.set_error(
+            "The line you jumped to does not exist anymore because the file has changed.",
+        );
+        return;
+    }
+    let start = text.line_to_char(line_num);
+    let end = text.line_to_char((line_num + 1).min(text.len_lines()));
+
+    doc.set_selection(view.id, Selection::single(start, end));
+    align_view(doc, view, Align::Center);
+}
+
+fn global_replace(cx: &mut Context) {
+    let (regex_sx, mut regex_rx) = tokio::sync::mpsc::unbounded_channel::<Regex>();
+    let config = cx.editor.config();
+    let smart_case = config.search.smart_case;
+    let file_picker_config = config.file_picker.clone();
+
+    let reg = cx.register.unwrap_or('/');
+
+    let completions = search_completions(cx, Some(reg));
+    ui::regex_prompt(
+        cx,
+        "global-replace:".into(),
+        Some(reg),
+        move |_editor: &Editor, input: &str| {
+            completions
+                .iter()
+                .filter(|comp| comp.starts_with(input))
+                .map(|comp| (0.., std::borrow::Cow::Owned(comp.clone())))
+                .collect()
+        },
+        move |_editor, regex, event| {
+            if event == PromptEvent::Validate {
+                let _ = regex_sx.send(regex);
+            }
+        },
+    );
+
+    let show_replace_prompt = async move {
+        // The sender is dropped without sending anything if the search prompt is aborted
+        let regex = match regex_rx.recv().await {
+            Some(regex) => regex,
+            None => return Ok(Callback::Editor(Box::new(|_editor: &mut Editor| {}))),
+        };
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |_editor: &mut Editor, compositor: &mut Compositor| {
+                let prompt = Prompt::new(
+                    "replace-with:".into(),
+                    None,
+                    ui::completers::none,
+                    move |cx: &mut compositor::Context, input: &str, event: PromptEvent| {
+                        if event != PromptEvent::Validate {
                             return;
                         }
-                        let start = text.line_to_char(line_num);
-                        let end = text.line_to_char((line_num + 1).min(text.len_lines()));
+                        global_replace_impl(cx, &regex, input, smart_case, &file_picker_config);
+                    },
+                );
+                compositor.push(Box::new(prompt));
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(show_replace_prompt);
+}
+
+fn global_replace_impl(
+    cx: &mut compositor::Context,
+    regex: &Regex,
+    replacement: &str,
+    smart_case: bool,
+    file_picker_config: &FilePickerConfig,
+) {
+    let (all_matches_sx, all_matches_rx) = std::sync::mpsc::channel::<ui::ReplaceMatch>();
+    let line_regex = regex.clone();
+    let replacement = replacement.to_owned();
+    search_workspace(
+        regex,
+        smart_case,
+        file_picker_config,
+        move |path, line_num, line| {
+            for captures in line_regex.captures_iter(line) {
+                let range = captures.get(0).unwrap().range();
+                let mut expanded = String::new();
+                captures.expand(&replacement, &mut expanded);
+                all_matches_sx
+                    .send(ui::ReplaceMatch {
+                        path: path.to_path_buf(),
+                        line_num,
+                        line: line.to_owned(),
+                        range,
+                        replacement: expanded,
+                        enabled: true,
+                    })
+                    .unwrap();
+            }
+        },
+    );
+
+    // The search is done once `search_workspace` returns, and all senders have been dropped
+    let mut all_matches: Vec<_> = all_matches_rx.into_iter().collect();
+    // Replacements within a file are expected to be in order when they are applied
+    all_matches.sort_by(|a, b| {
+        (&a.path, a.line_num, a.range.start).cmp(&(&b.path, b.line_num, b.range.start))
+    });
+
+    let show_picker = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                if all_matches.is_empty() {
+                    editor.set_status("No matches found");
+                    return;
+                }
 
-                        doc.set_selection(view.id, Selection::single(start, end));
-                        align_view(doc, view, Align::Center);
+                let picker = FilePicker::new(
+                    all_matches,
+                    (),
+                    |cx, replace_match: &ui::ReplaceMatch, action| {
+                        open_file_at_line(
+                            cx.editor,
+                            &replace_match.path,
+                            replace_match.line_num,
+                            action,
+                        )
                     },
-                    |_editor, FileResult { path, line_num }| {
-                        Some((path.clone().into(), Some((*line_num, *line_num))))
+                    |_editor, replace_match| {
+                        let line_num = replace_match.line_num;
+                        Some((
+                            replace_match.path.clone().into(),
+                            Some((line_num, line_num)),
+                        ))
                     },
                 );
+                let picker = ui::replace_picker(picker, |editor, matches| {
+                    apply_replacements(editor, matches);
+                });
                 compositor.push(Box::new(overlayed(picker)));
             },
         ));
@@ -2037,6 +2407,149 @@ fn format(&self, current_path: &Self::Data) -> Row {
     cx.jobs.callback(show_picker);
 }
 
+/// Applies the replacements, grouped by file. Documents open in the editor are changed
+/// through a [`Transaction`] so the replacements can be undone, other files are rewritten
+/// on disk. Matches on lines that changed since the search are skipped.
+/// Returns the replacements that were applied.
+fn apply_replacements(
+    editor: &mut Editor,
+    matches: Vec<ui::ReplaceMatch>,
+) -> Vec<ui::ReplaceMatch> {
+    let mut matches_by_path: BTreeMap<PathBuf, Vec<ui::ReplaceMatch>> = BTreeMap::new();
+    for replace_match in matches {
+        matches_by_path
+            .entry(replace_match.path.clone())
+            .or_default()
+            .push(replace_match);
+    }
+
+    let mut applied = Vec::new();
+    let mut skipped = 0;
+    let mut changed_files = 0;
+    let mut failed_files = 0;
+    for (path, matches) in matches_by_path {
+        let result = match editor.document_by_path(&path).map(|doc| doc.id()) {
+            Some(doc_id) => Ok(replace_in_document(editor, doc_id, &matches)),
+            None => replace_in_file(&path, &matches),
+        };
+
+        match result {
+            Ok(indices) => {
+                skipped += matches.len() - indices.len();
+                if !indices.is_empty() {
+                    changed_files += 1;
+                }
+                applied.extend(indices.into_iter().map(|i| matches[i].clone()));
+            }
+            Err(err) => {
+                log::error!("Failed to replace in '{}': {}", path.display(), err);
+                skipped += matches.len();
+                failed_files += 1;
+            }
+        }
+    }
+
+    let mut summary = format!(
+        "Replaced {} matches in {} files",
+        applied.len(),
+        changed_files
+    );
+    if skipped > 0 {
+        summary.push_str(&format!(", skipped {}", skipped));
+    }
+    if failed_files > 0 {
+        summary.push_str(&format!(", failed to write {} files", failed_files));
+        editor.set_error(summary);
+    } else {
+        editor.set_status(summary);
+    }
+    applied
+}
+
+/// Applies the replacements to an open document and returns the indices of those that were
+/// applied.
+fn replace_in_document(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    matches: &[ui::ReplaceMatch],
+) -> Vec<usize> {
+    let current_view_id = view!(editor).id;
+    let doc = doc_mut!(editor, &doc_id);
+    let text = doc.text();
+
+    let (indices, changes): (Vec<_>, Vec<_>) = matches
+        .iter()
+        .enumerate()
+        .filter_map(|(i, replace_match)| {
+            let line = text.get_line(replace_match.line_num)?;
+            // the line was edited since the search
+            if line != replace_match.line.as_str() {
+                return None;
+            }
+            let line_start = text.line_to_char(replace_match.line_num);
+            let from = line_start + line.byte_to_char(replace_match.range.start);
+            let to = line_start + line.byte_to_char(replace_match.range.end);
+            let replacement = Tendril::from(replace_match.replacement.as_str());
+            Some((i, (from, to, Some(replacement))))
+        })
+        .unzip();
+
+    if indices.is_empty() {
+        return indices;
+    }
+
+    // Need to determine a view for apply/append_changes_to_history
+    let selections = doc.selections();
+    let view_id = if selections.contains_key(&current_view_id) || selections.is_empty() {
+        current_view_id
+    } else {
+        selections.keys().next().copied().unwrap()
+    };
+    doc.ensure_view_init(view_id);
+
+    let transaction = Transaction::change(doc.text(), changes.into_iter());
+    let view = view_mut!(editor, view_id);
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+    indices
+}
+
+/// Applies the replacements to a file that is not open in the editor and returns the indices
+/// of those that were applied.
+fn replace_in_file(path: &Path, matches: &[ui::ReplaceMatch]) -> anyhow::Result<Vec<usize>> {
+    let mut contents = std::fs::read_to_string(path)?;
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(contents.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+
+    let edits: Vec<_> = matches
+        .iter()
+        .enumerate()
+        .filter_map(|(i, replace_match)| {
+            let start = *line_starts.get(replace_match.line_num)?;
+            let end = line_starts
+                .get(replace_match.line_num + 1)
+                .copied()
+                .unwrap_or(contents.len());
+            // the line was edited since the search
+            if contents[start..end] != replace_match.line {
+                return None;
+            }
+            let range = start + replace_match.range.start..start + replace_match.range.end;
+            Some((i, range, replace_match.replacement.as_str()))
+        })
+        .collect();
+
+    // Replace back to front so the offsets of the remaining edits stay valid
+    for (_, range, replacement) in edits.iter().rev() {
+        contents.replace_range(range.clone(), replacement);
+    }
+    if !edits.is_empty() {
+        std::fs::write(path, contents)?;
+    }
+    Ok(edits.into_iter().map(|(i, _, _)| i).collect())
+}
+
 enum Extend {
     Above,
     Below,
@@ -2249,8 +2762,24 @@ fn enter_insert_mode(cx: &mut Context) {
     cx.editor.mode = Mode::Insert;
 }
 
+/// Enters terminal mode if the focused view is a terminal whose process is still running.
+fn enter_terminal_mode(cx: &mut Context) -> bool {
+    let view_id = view!(cx.editor).id;
+    match cx.editor.terminals.get(&view_id) {
+        Some(terminal) if !terminal.is_exited() => {
+            cx.editor.mode = Mode::Terminal;
+            cx.editor.last_terminal = Some(view_id);
+            true
+        }
+        _ => false,
+    }
+}
+
 // inserts at the start of each selection
 fn insert_mode(cx: &mut Context) {
+    if enter_terminal_mode(cx) {
+        return;
+    }
     enter_insert_mode(cx);
     let (view, doc) = current!(cx.editor);
 
@@ -2270,6 +2799,9 @@ fn insert_mode(cx: &mut Context) {
 
 // inserts at the end of each selection
 fn append_mode(cx: &mut Context) {
+    if enter_terminal_mode(cx) {
+        return;
+    }
     enter_insert_mode(cx);
     let (view, doc) = current!(cx.editor);
     doc.restore_cursor = true;
@@ -2304,14 +2836,97 @@ fn file_picker(cx: &mut Context) {
     // We don't specify language markers, root will be the root of the current
     // git repo or the current dir if we're not in a repo
     let root = find_root(None, &[]);
-    let picker = ui::file_picker(root, &cx.editor.config());
-    cx.push_layer(Box::new(overlayed(picker)));
+    ui::open_file_picker(cx.jobs, cx.editor, root);
 }
 
 fn file_picker_in_current_directory(cx: &mut Context) {
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("./"));
-    let picker = ui::file_picker(cwd, &cx.editor.config());
-    cx.push_layer(Box::new(overlayed(picker)));
+    ui::open_file_picker(cx.jobs, cx.editor, cwd);
+}
+
+fn toggle_file_explorer(cx: &mut Context) {
+    cx.callback = Some(Box::new(
+        |compositor: &mut Compositor, cx: &mut compositor::Context| {
+            let editor_view = match compositor.find::<ui::EditorView>() {
+                Some(editor_view) => editor_view,
+                None => return,
+            };
+            match &mut editor_view.explorer {
+                Some(explorer) if explorer.is_focused() => editor_view.explorer = None,
+                Some(explorer) => explorer.focus(),
+                None => {
+                    let root = find_root(None, &[]);
+                    let explorer = ui::Explorer::new(root, &cx.editor.config().file_picker);
+                    editor_view.explorer = Some(explorer);
+                }
+            }
+        },
+    ));
+}
+
+fn terminal_send_selection(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text().slice(..);
+    let fragments: Vec<_> = doc.selection(view.id).fragments(text).collect();
+    let contents = fragments.join(doc.line_ending.as_str());
+
+    let terminal = cx
+        .editor
+        .last_terminal
+        .and_then(|view_id| cx.editor.terminals.get_mut(&view_id))
+        .filter(|terminal| !terminal.is_exited());
+    let terminal = match terminal {
+        Some(terminal) => terminal,
+        None => {
+            cx.editor
+                .set_error("No terminal is running, open one with :terminal");
+            return;
+        }
+    };
+
+    // the line ending is sent separately so that it also submits bracketed pastes
+    let contents = contents.trim_end_matches(|ch| ch == '\r' || ch == '\n');
+    if let Err(err) = terminal.paste(contents).and_then(|_| terminal.write(b"\r")) {
+        cx.editor
+            .set_error(format!("Failed to write to the terminal: {}", err));
+    }
+}
+
+fn repl_send_selection(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let language = match doc.language_config() {
+        Some(language) => language,
+        None => {
+            cx.editor
+                .set_error("No language set for the current buffer");
+            return;
+        }
+    };
+    let config = match &language.repl {
+        Some(config) => config.clone(),
+        None => {
+            let msg = format!("No REPL configured for {}", language.language_id);
+            cx.editor.set_error(msg);
+            return;
+        }
+    };
+    let language_id = language.language_id.clone();
+
+    let text = doc.text().slice(..);
+    let fragments: Vec<_> = doc.selection(view.id).fragments(text).collect();
+    let line_ending = doc.line_ending.as_str();
+    let mut contents = fragments.join(line_ending);
+    if !contents.ends_with(line_ending) {
+        contents.push_str(line_ending);
+    }
+
+    if let Err(err) = cx
+        .editor
+        .send_to_repl(&language_id, &config, contents.into())
+    {
+        cx.editor
+            .set_error(format!("Failed to send to the REPL: {}", err));
+    }
 }
 
 fn buffer_picker(cx: &mut Context) {
@@ -3647,7 +4262,7 @@ pub(crate) fn paste_bracketed_value(cx: &mut Context, contents: String) {
     let count = cx.count();
     let paste = match cx.editor.mode {
         Mode::Insert | Mode::Select => Paste::Cursor,
-        Mode::Normal => Paste::Before,
+        Mode::Normal | Mode::Terminal => Paste::Before,
     };
     let (view, doc) = current!(cx.editor);
     paste_impl(&[contents], doc, view, paste, count, cx.editor.mode);
@@ -2,17 +2,26 @@
 use crate::{
     compositor::{self, Compositor},
     job::{Callback, Jobs},
-    ui::{self, overlay::overlayed, FilePicker, Picker, Popup, Prompt, PromptEvent, Text},
+    ui::{self, overlay::overlayed, FilePicker, Picker, Prompt, PromptEvent},
 };
 use dap::{StackFrame, Thread, ThreadStates};
-use helix_core::syntax::{DebugArgumentValue, DebugConfigCompletion, DebugTemplate};
-use helix_dap::{self as dap, Client};
+use helix_core::{
+    find_root,
+    syntax::{DebugArgumentValue, DebugConfigCompletion, DebugTemplate},
+};
+use helix_dap::{
+    self as dap,
+    launch::{Compound, LaunchConfig, LaunchConfigs, VariableContext},
+    process::Process,
+};
 use helix_lsp::block_on;
-use helix_view::editor::Breakpoint;
+use helix_view::{
+    debug_console::OutputCategory,
+    editor::{Breakpoint, ExceptionBreakpoint, FunctionBreakpoint},
+};
 
 use serde_json::{to_value, Value};
-use tokio_stream::wrappers::UnboundedReceiverStream;
-use tui::{text::Spans, widgets::Row};
+use tui::widgets::{Cell, Row};
 
 use std::collections::HashMap;
 use std::future::Future;
@@ -20,7 +29,9 @@
 
 use anyhow::{anyhow, bail};
 
-use helix_view::handlers::dap::{breakpoints_changed, jump_to_stack_frame, select_thread_id};
+use helix_view::handlers::dap::{
+    jump_to_stack_frame, select_thread_id, set_breakpoint_states, source_breakpoints,
+};
 
 impl ui::menu::Item for StackFrame {
     type Data = ();
@@ -30,11 +41,43 @@ fn format(&self, _data: &Self::Data) -> Row {
     }
 }
 
-impl ui::menu::Item for DebugTemplate {
+impl ui::menu::Item for dap::GotoTarget {
+    type Data = ();
+
+    fn format(&self, _data: &Self::Data) -> Row {
+        self.label.as_str().into()
+    }
+}
+
+impl ui::menu::Item for Process {
     type Data = ();
 
     fn format(&self, _data: &Self::Data) -> Row {
-        self.name.as_str().into()
+        Row::new(vec![
+            Cell::from(self.pid.to_string()),
+            Cell::from(self.name.clone()),
+            Cell::from(self.user.clone()),
+            Cell::from(self.command_line.clone()),
+        ])
+    }
+}
+
+impl ui::menu::Item for dap::ExceptionBreakpointsFilter {
+    /// The enabled filters.
+    type Data = Vec<ExceptionBreakpoint>;
+
+    fn format(&self, enabled: &Self::Data) -> Row {
+        let breakpoint = enabled.iter().find(|b| b.filter == self.filter);
+        let marker = if breakpoint.is_some() { "[x]" } else { "[ ]" };
+        let label = match breakpoint.and_then(|b| b.condition.as_ref()) {
+            Some(condition) => format!("{} if {}", self.label, condition),
+            None => self.label.clone(),
+        };
+        Row::new(vec![
+            Cell::from(marker),
+            Cell::from(label),
+            Cell::from(self.description.clone().unwrap_or_default()),
+        ])
     }
 }
 
@@ -78,7 +121,11 @@ fn thread_picker(
                 thread_states,
                 move |cx, thread, _action| callback_fn(cx.editor, thread),
                 move |editor, thread| {
-                    let frames = editor.debugger.as_ref()?.stack_frames.get(&thread.id)?;
+                    let frames = editor
+                        .debuggers
+                        .get_active()?
+                        .stack_frames
+                        .get(&thread.id)?;
                     let frame = frames.get(0)?;
                     let path = frame.source.as_ref()?.path.clone()?;
                     let pos = Some((
@@ -139,32 +186,9 @@ pub fn dap_start_impl(
 
     let config = doc
         .language_config()
-        .and_then(|config| config.debugger.as_ref())
+        .and_then(|config| config.debugger.clone())
         .ok_or_else(|| anyhow!("No debug adapter available for language"))?;
 
-    let result = match socket {
-        Some(socket) => block_on(Client::tcp(socket, 0)),
-        None => block_on(Client::process(
-            &config.transport,
-            &config.command,
-            config.args.iter().map(|arg| arg.as_str()).collect(),
-            config.port_arg.as_deref(),
-            0,
-        )),
-    };
-
-    let (mut debugger, events) = match result {
-        Ok(r) => r,
-        Err(e) => bail!("Failed to start debug session: {}", e),
-    };
-
-    let request = debugger.initialize(config.name.clone());
-    if let Err(e) = block_on(request) {
-        bail!("Failed to initialize debug adapter: {}", e);
-    }
-
-    debugger.quirks = config.quirks.clone();
-
     // TODO: avoid refetching all of this... pass a config in
     let template = match name {
         Some(name) => config.templates.iter().find(|t| t.name == name),
@@ -222,73 +246,222 @@ pub fn dap_start_impl(
     args.insert("cwd", to_value(std::env::current_dir().unwrap())?);
 
     let args = to_value(args).unwrap();
+    let request = template.request.clone();
+    let name = template.name.clone();
+    let call = cx
+        .editor
+        .start_debug_session(name, &config, socket, &request, args)?;
 
     let callback = |_editor: &mut Editor, _compositor: &mut Compositor, _response: Value| {
         // if let Err(e) = result {
         //     editor.set_error(format!("Failed {} target: {}", template.request, e));
         // }
     };
-
-    match &template.request[..] {
-        "launch" => {
-            let call = debugger.launch(args);
-            dap_callback(cx.jobs, call, callback);
-        }
-        "attach" => {
-            let call = debugger.attach(args);
-            dap_callback(cx.jobs, call, callback);
-        }
-        request => bail!("Unsupported request '{}'", request),
-    };
-
-    // TODO: either await "initialized" or buffer commands until event is received
-    cx.editor.debugger = Some(debugger);
-    let stream = UnboundedReceiverStream::new(events);
-    cx.editor.debugger_events.push(stream);
+    dap_callback(cx.jobs, call, callback);
     Ok(())
 }
 
-pub fn dap_launch(cx: &mut Context) {
-    if cx.editor.debugger.is_some() {
-        cx.editor.set_error("Debugger is already running");
-        return;
-    }
+/// Entries of the `dap_launch` picker.
+enum LaunchItem {
+    /// A configuration of the workspace.
+    Config(LaunchConfig),
+    Compound(Compound),
+    /// A template of the debug adapter of the current language.
+    Template(DebugTemplate),
+}
 
-    let doc = doc!(cx.editor);
+impl ui::menu::Item for LaunchItem {
+    type Data = ();
 
-    let config = match doc
-        .language_config()
-        .and_then(|config| config.debugger.as_ref())
-    {
-        Some(c) => c,
-        None => {
-            cx.editor
-                .set_error("No debug adapter available for language");
-            return;
+    fn format(&self, _data: &Self::Data) -> Row {
+        match self {
+            Self::Config(config) => format!("{} ({})", config.name, config.ty).into(),
+            Self::Compound(compound) => format!("{} (compound)", compound.name).into(),
+            Self::Template(template) => template.name.as_str().into(),
+        }
+    }
+}
+
+pub fn dap_launch(cx: &mut Context) {
+    let configs = match LaunchConfigs::load(&find_root(None, &[])) {
+        Ok(configs) => configs,
+        Err(err) => {
+            cx.editor.set_error(format!("{:#}", err));
+            LaunchConfigs::default()
         }
     };
+    let templates = doc!(cx.editor)
+        .language_config()
+        .and_then(|config| config.debugger.as_ref())
+        .map(|config| config.templates.clone())
+        .unwrap_or_default();
 
-    let templates = config.templates.clone();
+    let items: Vec<_> = configs
+        .configurations
+        .iter()
+        .cloned()
+        .map(LaunchItem::Config)
+        .chain(configs.compounds.iter().cloned().map(LaunchItem::Compound))
+        .chain(templates.into_iter().map(LaunchItem::Template))
+        .collect();
+    if items.is_empty() {
+        cx.editor
+            .set_error("No debug adapter available for language");
+        return;
+    }
 
     cx.push_layer(Box::new(overlayed(Picker::new(
-        templates,
+        items,
         (),
-        |cx, template, _action| {
-            let completions = template.completion.clone();
-            let name = template.name.clone();
-            let callback = Box::pin(async move {
-                let call: Callback =
-                    Callback::EditorCompositor(Box::new(move |_editor, compositor| {
-                        let prompt = debug_parameter_prompt(completions, name, Vec::new());
-                        compositor.push(Box::new(prompt));
-                    }));
-                Ok(call)
-            });
-            cx.jobs.callback(callback);
+        move |cx, item, _action| match item {
+            LaunchItem::Config(config) => {
+                if let Err(err) = launch_workspace_configs(cx, &configs, &[config], None) {
+                    cx.editor.set_error(err.to_string());
+                }
+            }
+            LaunchItem::Compound(compound) => {
+                let result = compound
+                    .configurations
+                    .iter()
+                    .map(|name| {
+                        configs
+                            .configuration(name)
+                            .ok_or_else(|| anyhow!("No configuration named '{}' was found", name))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .and_then(|launches| {
+                        let task = compound.pre_launch_task.as_deref();
+                        launch_workspace_configs(cx, &configs, &launches, task)
+                    });
+                if let Err(err) = result {
+                    cx.editor.set_error(err.to_string());
+                }
+            }
+            LaunchItem::Template(template) => {
+                let completions = template.completion.clone();
+                let name = template.name.clone();
+                push_debug_parameter(cx.jobs, completions, name, Vec::new());
+            }
         },
     ))));
 }
 
+/// The values of the variables used in workspace configurations, like `${file}`.
+fn variable_context(editor: &Editor) -> VariableContext {
+    let (view, doc) = current_ref!(editor);
+    let text = doc.text().slice(..);
+    let selection = doc.selection(view.id).primary();
+    VariableContext {
+        workspace_folder: find_root(None, &[]),
+        file: doc.path().cloned(),
+        line_number: selection.cursor_line(text) + 1,
+        selected_text: selection.fragment(text).into(),
+    }
+}
+
+/// Starts a debug session for each of the configurations of the workspace in `launches`, once
+/// `task` and their `preLaunchTask`s finished successfully.
+fn launch_workspace_configs(
+    cx: &mut compositor::Context,
+    configs: &LaunchConfigs,
+    launches: &[&LaunchConfig],
+    task: Option<&str>,
+) -> Result<(), anyhow::Error> {
+    let variables = variable_context(cx.editor);
+    let mut labels: Vec<String> = task.map(String::from).into_iter().collect();
+    let mut sessions = Vec::new();
+    for config in launches {
+        // adapters are looked up by name, falling back to the one of the current language
+        let adapter = cx
+            .editor
+            .syn_loader
+            .language_configs()
+            .filter_map(|config| config.debugger.as_ref())
+            .find(|adapter| adapter.name == config.ty)
+            .cloned()
+            .or_else(|| doc!(cx.editor).language_config()?.debugger.clone())
+            .ok_or_else(|| anyhow!("No debug adapter named '{}' is configured", config.ty))?;
+        let mut args = Value::Object(config.args.clone());
+        variables.substitute(&mut args);
+
+        if let Some(label) = &config.pre_launch_task {
+            if !labels.contains(label) {
+                labels.push(label.clone());
+            }
+        }
+        sessions.push((config.name.clone(), adapter, config.request.clone(), args));
+    }
+
+    if labels.is_empty() {
+        for (name, adapter, request, args) in sessions {
+            let call = cx
+                .editor
+                .start_debug_session(name, &adapter, None, &request, args)?;
+            dap_callback(cx.jobs, call, |_editor, _compositor, _response: Value| {});
+        }
+        return Ok(());
+    }
+
+    let mut commands = Vec::new();
+    for label in &labels {
+        let task = configs
+            .task(label)
+            .ok_or_else(|| anyhow!("No task labeled '{}' was found", label))?;
+        if task.command.is_empty() {
+            bail!("The task '{}' has no command to run", label);
+        }
+        commands.push((label.clone(), task.command(&variables)));
+    }
+
+    cx.editor
+        .set_status(format!("Running task '{}'", labels.join("', '")));
+    let callback = async move {
+        // the tasks run one after the other, until one fails
+        let mut outputs = Vec::new();
+        for (label, mut command) in commands {
+            let output = command.output().await?;
+            let success = output.status.success();
+            outputs.push((label, output));
+            if !success {
+                break;
+            }
+        }
+        let call: Callback = Callback::EditorCompositor(Box::new(move |editor, _compositor| {
+            for (label, output) in &outputs {
+                // the output of the task is kept in the debug console
+                for text in [&output.stdout, &output.stderr] {
+                    let text = String::from_utf8_lossy(text);
+                    if !text.is_empty() {
+                        editor
+                            .debug_console
+                            .push_output(OutputCategory::Console, &text);
+                    }
+                }
+                if !output.status.success() {
+                    editor.set_error(format!("Task '{}' failed: {}", label, output.status));
+                    return;
+                }
+            }
+            for (name, adapter, request, args) in sessions {
+                match editor.start_debug_session(name, &adapter, None, &request, args) {
+                    // without access to the jobs here, failures of the request are only logged
+                    Ok(call) => {
+                        tokio::spawn(async move {
+                            if let Err(err) = call.await {
+                                log::error!("Failed to {} the debuggee: {}", request, err);
+                            }
+                        });
+                    }
+                    Err(err) => editor.set_error(err.to_string()),
+                }
+            }
+        }));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+    Ok(())
+}
+
 fn debug_parameter_prompt(
     completions: Vec<DebugConfigCompletion>,
     config_name: String,
@@ -330,32 +503,120 @@ fn debug_parameter_prompt(
                 value = default_val.clone();
             }
             params.push(value);
-
-            if params.len() < completions.len() {
-                let completions = completions.clone();
-                let config_name = config_name.clone();
-                let params = params.clone();
-                let callback = Box::pin(async move {
-                    let call: Callback =
-                        Callback::EditorCompositor(Box::new(move |_editor, compositor| {
-                            let prompt = debug_parameter_prompt(completions, config_name, params);
-                            compositor.push(Box::new(prompt));
-                        }));
-                    Ok(call)
-                });
-                cx.jobs.callback(callback);
-            } else if let Err(err) = dap_start_impl(
-                cx,
-                Some(&config_name),
-                None,
-                Some(params.iter().map(|x| x.into()).collect()),
-            ) {
-                cx.editor.set_error(err.to_string());
-            }
+            next_debug_parameter(cx, &completions, &config_name, params.clone());
         },
     )
 }
 
+/// Picks a process for the `pid` parameter of a template.
+fn debug_process_picker(
+    completions: Vec<DebugConfigCompletion>,
+    config_name: String,
+    params: Vec<String>,
+    processes: Vec<Process>,
+) -> impl compositor::Component {
+    overlayed(Picker::new(
+        processes,
+        (),
+        move |cx, process: &Process, _action| {
+            let mut params = params.clone();
+            params.push(process.pid.to_string());
+            next_debug_parameter(cx, &completions, &config_name, params);
+        },
+    ))
+}
+
+fn is_pid_parameter(completion: &DebugConfigCompletion) -> bool {
+    match completion {
+        DebugConfigCompletion::Named(name) => name == "pid",
+        DebugConfigCompletion::Advanced(cfg) => cfg.completion.as_deref() == Some("pid"),
+    }
+}
+
+/// Asks for the parameter of the template following `params`, with a prompt or with a process
+/// picker for `pid` parameters.
+fn push_debug_parameter(
+    jobs: &mut Jobs,
+    completions: Vec<DebugConfigCompletion>,
+    config_name: String,
+    params: Vec<String>,
+) {
+    let callback = Box::pin(async move {
+        let call: Callback = Callback::EditorCompositor(Box::new(move |editor, compositor| {
+            if !completions
+                .get(params.len())
+                .map_or(false, is_pid_parameter)
+            {
+                let prompt = debug_parameter_prompt(completions, config_name, params);
+                compositor.push(Box::new(prompt));
+                return;
+            }
+            match dap::process::list() {
+                Ok(processes) => compositor.push(Box::new(debug_process_picker(
+                    completions,
+                    config_name,
+                    params,
+                    processes,
+                ))),
+                // fall back to typing the PID
+                Err(err) => {
+                    editor.set_error(format!("Failed to list the processes: {}", err));
+                    let prompt = debug_parameter_prompt(completions, config_name, params);
+                    compositor.push(Box::new(prompt));
+                }
+            }
+        }));
+        Ok(call)
+    });
+    jobs.callback(callback);
+}
+
+/// Starts the debug session once all the parameters of the template are known.
+fn next_debug_parameter(
+    cx: &mut compositor::Context,
+    completions: &[DebugConfigCompletion],
+    config_name: &str,
+    params: Vec<String>,
+) {
+    if params.len() < completions.len() {
+        push_debug_parameter(
+            cx.jobs,
+            completions.to_vec(),
+            config_name.to_string(),
+            params,
+        );
+    } else if let Err(err) = dap_start_impl(
+        cx,
+        Some(config_name),
+        None,
+        Some(params.iter().map(|x| x.into()).collect()),
+    ) {
+        cx.editor.set_error(err.to_string());
+    }
+}
+
+/// Attaches to a running process picked from a list, through the first `attach` template of the
+/// current language taking a `pid` parameter.
+pub fn dap_attach(cx: &mut Context) {
+    let template = doc!(cx.editor)
+        .language_config()
+        .and_then(|config| config.debugger.as_ref())
+        .and_then(|config| {
+            config.templates.iter().find(|template| {
+                template.request == "attach" && template.completion.iter().any(is_pid_parameter)
+            })
+        });
+    let (completions, name) = match template {
+        Some(template) => (template.completion.clone(), template.name.clone()),
+        None => {
+            cx.editor
+                .set_error("No attach template taking a pid is available for language");
+            return;
+        }
+    };
+    push_debug_parameter(cx.jobs, completions, name, Vec::new());
+}
+
 pub fn dap_toggle_breakpoint(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
     let path = match doc.path() {
@@ -372,9 +633,6 @@ pub fn dap_toggle_breakpoint(cx: &mut Context) {
 }
 
 pub fn dap_toggle_breakpoint_impl(cx: &mut Context, path: PathBuf, line: usize) {
-    // TODO: need to map breakpoints over edits and update them?
-    // we shouldn't really allow editing while debug is running though
-
     let breakpoints = cx.editor.breakpoints.entry(path.clone()).or_default();
     // TODO: always keep breakpoints sorted and use binary search to determine insertion point
     if let Some(pos) = breakpoints
@@ -389,14 +647,85 @@ pub fn dap_toggle_breakpoint_impl(cx: &mut Context, path: PathBuf, line: usize)
         });
     }
 
-    let debugger = debugger!(cx.editor);
+    update_breakpoints(cx.editor, path);
+}
 
-    if let Err(e) = breakpoints_changed(debugger, path, breakpoints) {
-        cx.editor
-            .set_error(format!("Failed to set breakpoints: {}", e));
+/// Persists the breakpoints after those of `path` were changed, and sends them to the debugger.
+fn update_breakpoints(editor: &mut Editor, path: PathBuf) {
+    if let Err(e) = editor.save_breakpoints() {
+        log::error!("Failed to save breakpoints: {}", e);
+    }
+
+    if let Err(e) = editor.send_breakpoints(path) {
+        editor.set_error(format!("Failed to set breakpoints: {}", e));
     }
 }
 
+/// Sends the breakpoints that moved with edits to the debuggers, without waiting for them to
+/// respond. Called once editing pauses.
+pub fn send_moved_breakpoints(editor: &mut Editor, jobs: &mut Jobs) {
+    for path in std::mem::take(&mut editor.moved_breakpoints) {
+        let breakpoints = match editor.breakpoints.get_mut(&path) {
+            Some(breakpoints) => breakpoints,
+            None => continue,
+        };
+        let source_breakpoints = source_breakpoints(breakpoints);
+
+        for debugger in editor.debuggers.iter() {
+            let request = debugger.set_breakpoints(path.clone(), source_breakpoints.clone());
+            let session = debugger.id();
+            let path = path.clone();
+            jobs.callback(async move {
+                let dap_breakpoints = request.await?.unwrap_or_default();
+                let call: Callback = Callback::Editor(Box::new(move |editor: &mut Editor| {
+                    // the breakpoints moved again while the request was in flight, the states
+                    // will come with the next one
+                    if editor.moved_breakpoints.contains(&path) {
+                        return;
+                    }
+                    if let Some(breakpoints) = editor.breakpoints.get_mut(&path) {
+                        set_breakpoint_states(session, breakpoints, dap_breakpoints);
+                    }
+                }));
+                Ok(call)
+            });
+        }
+    }
+}
+
+pub fn dap_breakpoint_picker(cx: &mut Context) {
+    let mut breakpoints: Vec<_> = cx
+        .editor
+        .breakpoints
+        .iter()
+        .flat_map(|(path, breakpoints)| {
+            breakpoints.iter().map(|breakpoint| ui::BreakpointItem {
+                path: path.clone(),
+                breakpoint: breakpoint.clone(),
+            })
+        })
+        .collect();
+    if breakpoints.is_empty() {
+        cx.editor.set_status("No breakpoints set");
+        return;
+    }
+    breakpoints.sort_by(|a, b| (&a.path, a.breakpoint.line).cmp(&(&b.path, b.breakpoint.line)));
+
+    let picker = FilePicker::new(
+        breakpoints,
+        (),
+        |cx, item: &ui::BreakpointItem, action| {
+            super::open_file_at_line(cx.editor, &item.path, item.breakpoint.line, action)
+        },
+        |_editor, item| {
+            let line = item.breakpoint.line;
+            Some((item.path.clone().into(), Some((line, line))))
+        },
+    );
+    let picker = ui::breakpoint_picker(picker, update_breakpoints);
+    cx.push_layer(Box::new(overlayed(picker)));
+}
+
 pub fn dap_continue(cx: &mut Context) {
     let debugger = debugger!(cx.editor);
 
@@ -427,11 +756,27 @@ pub fn dap_pause(cx: &mut Context) {
     })
 }
 
+/// Steps by instruction while the disassembly is focused, if the debugger supports it.
+fn step_granularity(editor: &Editor) -> Option<String> {
+    let disassembly = editor.disassembly.as_ref()?;
+    if view!(editor).doc != disassembly.doc {
+        return None;
+    }
+    editor
+        .debuggers
+        .get_active()?
+        .capabilities()
+        .supports_stepping_granularity
+        .unwrap_or_default()
+        .then(|| "instruction".to_string())
+}
+
 pub fn dap_step_in(cx: &mut Context) {
+    let granularity = step_granularity(cx.editor);
     let debugger = debugger!(cx.editor);
 
     if let Some(thread_id) = debugger.thread_id {
-        let request = debugger.step_in(thread_id);
+        let request = debugger.step_in(thread_id, granularity);
 
         dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
             debugger!(editor).resume_application();
@@ -443,10 +788,11 @@ pub fn dap_step_in(cx: &mut Context) {
 }
 
 pub fn dap_step_out(cx: &mut Context) {
+    let granularity = step_granularity(cx.editor);
     let debugger = debugger!(cx.editor);
 
     if let Some(thread_id) = debugger.thread_id {
-        let request = debugger.step_out(thread_id);
+        let request = debugger.step_out(thread_id, granularity);
         dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
             debugger!(editor).resume_application();
         });
@@ -457,10 +803,11 @@ pub fn dap_step_out(cx: &mut Context) {
 }
 
 pub fn dap_next(cx: &mut Context) {
+    let granularity = step_granularity(cx.editor);
     let debugger = debugger!(cx.editor);
 
     if let Some(thread_id) = debugger.thread_id {
-        let request = debugger.next(thread_id);
+        let request = debugger.next(thread_id, granularity);
         dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
             debugger!(editor).resume_application();
         });
@@ -470,113 +817,466 @@ pub fn dap_next(cx: &mut Context) {
     }
 }
 
-pub fn dap_variables(cx: &mut Context) {
+pub fn dap_step_back(cx: &mut Context) {
+    let granularity = step_granularity(cx.editor);
     let debugger = debugger!(cx.editor);
 
-    if debugger.thread_id.is_none() {
+    if !debugger
+        .capabilities()
+        .supports_step_back
+        .unwrap_or_default()
+    {
         cx.editor
-            .set_status("Cannot access variables while target is running");
+            .set_error("The debugger does not support stepping back");
         return;
     }
-    let (frame, thread_id) = match (debugger.active_frame, debugger.thread_id) {
-        (Some(frame), Some(thread_id)) => (frame, thread_id),
-        _ => {
+    if let Some(thread_id) = debugger.thread_id {
+        let request = debugger.step_back(thread_id, granularity);
+        dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
+            debugger!(editor).resume_application();
+        });
+    } else {
+        cx.editor
+            .set_error("Currently active thread is not stopped. Switch the thread.");
+    }
+}
+
+pub fn dap_reverse_continue(cx: &mut Context) {
+    let debugger = debugger!(cx.editor);
+
+    if !debugger
+        .capabilities()
+        .supports_step_back
+        .unwrap_or_default()
+    {
+        cx.editor
+            .set_error("The debugger does not support running backwards");
+        return;
+    }
+    if let Some(thread_id) = debugger.thread_id {
+        let request = debugger.reverse_continue(thread_id);
+        dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
+            debugger!(editor).resume_application();
+        });
+    } else {
+        cx.editor
+            .set_error("Currently active thread is not stopped. Switch the thread.");
+    }
+}
+
+pub fn dap_disassembly(cx: &mut Context) {
+    if let Err(err) = block_on(cx.editor.show_disassembly()) {
+        cx.editor.set_error(err.to_string());
+    }
+}
+
+pub fn dap_restart_frame(cx: &mut Context) {
+    let debugger = debugger!(cx.editor);
+
+    if !debugger
+        .capabilities()
+        .supports_restart_frame
+        .unwrap_or_default()
+    {
+        cx.editor
+            .set_error("The debugger does not support restarting frames");
+        return;
+    }
+    let frame = match debugger.current_stack_frame() {
+        Some(frame) => frame,
+        None => {
             cx.editor
-                .set_status("Cannot find current stack frame to access variables");
+                .set_error("Cannot find current stack frame to restart");
             return;
         }
     };
+    if frame.can_restart == Some(false) {
+        cx.editor
+            .set_error("The current stack frame can't be restarted");
+        return;
+    }
+    let request = debugger.restart_frame(frame.id);
+    dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
+        debugger!(editor).resume_application();
+    });
+}
 
-    let frame_id = debugger.stack_frames[&thread_id][frame].id;
-    let scopes = match block_on(debugger.scopes(frame_id)) {
-        Ok(s) => s,
-        Err(e) => {
-            cx.editor.set_error(format!("Failed to get scopes: {}", e));
+/// Continues until the line of the cursor is reached, through a breakpoint that is removed once
+/// the debugger stops.
+pub fn dap_run_to_cursor(cx: &mut Context) {
+    let thread_id = match debugger!(cx.editor).thread_id {
+        Some(thread_id) => thread_id,
+        None => {
+            cx.editor
+                .set_error("Currently active thread is not stopped. Switch the thread.");
             return;
         }
     };
+    let (view, doc) = current!(cx.editor);
+    let path = match doc.path() {
+        Some(path) => path.clone(),
+        None => {
+            cx.editor
+                .set_error("Can't run to the cursor in a buffer without a file");
+            return;
+        }
+    };
+    let line = doc
+        .selection(view.id)
+        .primary()
+        .cursor_line(doc.text().slice(..));
 
-    // TODO: allow expanding variables into sub-fields
-    let mut variables = Vec::new();
-
-    let theme = &cx.editor.theme;
-    let scope_style = theme.get("ui.linenr.selected");
-    let type_style = theme.get("ui.text");
-    let text_style = theme.get("ui.text.focus");
-
-    for scope in scopes.iter() {
-        // use helix_view::graphics::Style;
-        use tui::text::Span;
-        let response = block_on(debugger.variables(scope.variables_reference));
-
-        variables.push(Spans::from(Span::styled(
-            format!("▸ {}", scope.name),
-            scope_style,
-        )));
-
-        if let Ok(vars) = response {
-            variables.reserve(vars.len());
-            for var in vars {
-                let mut spans = Vec::with_capacity(5);
-
-                spans.push(Span::styled(var.name.to_owned(), text_style));
-                if let Some(ty) = var.ty {
-                    spans.push(Span::raw(": "));
-                    spans.push(Span::styled(ty.to_owned(), type_style));
-                }
-                spans.push(Span::raw(" = "));
-                spans.push(Span::styled(var.value.to_owned(), text_style));
-                variables.push(Spans::from(spans));
+    let breakpoints = cx.editor.breakpoints.entry(path.clone()).or_default();
+    match breakpoints
+        .iter()
+        .find(|b| b.line == line)
+        .map(|b| b.enabled)
+    {
+        // the debugger stops there anyway
+        Some(true) => (),
+        Some(false) => {
+            cx.editor
+                .set_error("Can't run to a line with a disabled breakpoint");
+            return;
+        }
+        None => {
+            breakpoints.push(Breakpoint {
+                line,
+                temporary: true,
+                ..Default::default()
+            });
+            if let Err(e) = cx.editor.send_breakpoints(path) {
+                cx.editor
+                    .set_error(format!("Failed to set the breakpoint: {}", e));
+                return;
             }
         }
     }
 
-    let contents = Text::from(tui::text::Text::from(variables));
-    let popup = Popup::new("dap-variables", contents);
-    cx.push_layer(Box::new(popup));
+    let request = debugger!(cx.editor).continue_thread(thread_id);
+    dap_callback(
+        cx.jobs,
+        request,
+        |editor, _compositor, _response: dap::requests::ContinueResponse| {
+            debugger!(editor).resume_application();
+        },
+    );
 }
 
-pub fn dap_terminate(cx: &mut Context) {
+/// Moves the execution to the line of the cursor without running the code in between, letting
+/// the user pick a location if there are several on the line.
+pub fn dap_goto_cursor(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let path = match doc.path() {
+        Some(path) => path.clone(),
+        None => {
+            cx.editor
+                .set_error("Can't jump to the cursor in a buffer without a file");
+            return;
+        }
+    };
+    let line = doc
+        .selection(view.id)
+        .primary()
+        .cursor_line(doc.text().slice(..));
+
     let debugger = debugger!(cx.editor);
+    if !debugger
+        .capabilities()
+        .supports_goto_targets_request
+        .unwrap_or_default()
+    {
+        cx.editor
+            .set_error("The debugger does not support jumping to a line");
+        return;
+    }
+    let thread_id = match debugger.thread_id {
+        Some(thread_id) => thread_id,
+        None => {
+            cx.editor
+                .set_error("Currently active thread is not stopped. Switch the thread.");
+            return;
+        }
+    };
 
-    let request = debugger.disconnect();
-    dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
-        // editor.set_error(format!("Failed to disconnect: {}", e));
-        editor.debugger = None;
+    let targets = match block_on(debugger.goto_targets(path, line + 1)) {
+        Ok(targets) => targets,
+        Err(e) => {
+            cx.editor
+                .set_error(format!("Failed to get the jump targets: {}", e));
+            return;
+        }
+    };
+    match targets.len() {
+        0 => cx.editor.set_error("Can't jump to this line"),
+        1 => goto_target(cx.editor, cx.jobs, thread_id, targets[0].id),
+        _ => cx.push_layer(Box::new(overlayed(Picker::new(
+            targets,
+            (),
+            move |cx, target: &dap::GotoTarget, _action| {
+                goto_target(cx.editor, cx.jobs, thread_id, target.id)
+            },
+        )))),
+    }
+}
+
+fn goto_target(editor: &mut Editor, jobs: &mut Jobs, thread_id: dap::ThreadId, target_id: usize) {
+    let request = debugger!(editor).goto(thread_id, target_id);
+    dap_callback(jobs, request, |editor, _compositor, _response: ()| {
+        debugger!(editor).resume_application();
     });
 }
 
-pub fn dap_enable_exceptions(cx: &mut Context) {
+pub fn dap_variables(cx: &mut Context) {
     let debugger = debugger!(cx.editor);
 
-    let filters = match &debugger.capabilities().exception_breakpoint_filters {
-        Some(filters) => filters.iter().map(|f| f.filter.clone()).collect(),
-        None => return,
+    if debugger.thread_id.is_none() {
+        cx.editor
+            .set_status("Cannot access variables while target is running");
+        return;
+    }
+    if debugger.current_stack_frame().is_none() {
+        cx.editor
+            .set_status("Cannot find current stack frame to access variables");
+        return;
+    }
+
+    // the panels share the right side of the screen
+    cx.callback = Some(Box::new(|compositor: &mut Compositor, _| {
+        compositor.remove(ui::Watches::ID);
+        compositor.replace_or_push(ui::Variables::ID, ui::Variables::default())
+    }));
+}
+
+pub fn dap_console(cx: &mut Context) {
+    cx.callback = Some(Box::new(|compositor: &mut Compositor, _| {
+        compositor.replace_or_push(ui::DebugConsole::ID, ui::DebugConsole::new())
+    }));
+}
+
+pub fn dap_watches(cx: &mut Context) {
+    cx.callback = Some(Box::new(|compositor: &mut Compositor, _| {
+        compositor.remove(ui::Variables::ID);
+        compositor.replace_or_push(ui::Watches::ID, ui::Watches::default())
+    }));
+}
+
+pub fn dap_toggle_inline_values(cx: &mut Context) {
+    cx.editor.show_inline_values = !cx.editor.show_inline_values;
+    block_on(cx.editor.refresh_debug_values());
+    let status = if cx.editor.show_inline_values {
+        "Showing inline values"
+    } else {
+        "Hiding inline values"
     };
+    cx.editor.set_status(status);
+}
 
-    let request = debugger.set_exception_breakpoints(filters);
+pub fn dap_terminate(cx: &mut Context) {
+    let debugger = debugger!(cx.editor);
 
+    let id = debugger.id();
+    let request = debugger.disconnect();
     dap_callback(
         cx.jobs,
         request,
-        |_editor, _compositor, _response: dap::requests::SetExceptionBreakpointsResponse| {
-            // editor.set_error(format!("Failed to set up exception breakpoints: {}", e));
+        move |editor, _compositor, _response: ()| {
+            // editor.set_error(format!("Failed to disconnect: {}", e));
+            editor.remove_debug_session(id);
         },
-    )
+    );
+}
+
+/// Entries of the `dap_switch_session` picker.
+struct SessionItem {
+    id: usize,
+    name: String,
+    parent: Option<String>,
+    /// The stopped thread, if any.
+    thread_id: Option<dap::ThreadId>,
+}
+
+impl ui::menu::Item for SessionItem {
+    /// The id of the active session.
+    type Data = Option<usize>;
+
+    fn format(&self, active: &Self::Data) -> Row {
+        let marker = if *active == Some(self.id) { "*" } else { " " };
+        let state = match self.thread_id {
+            Some(thread_id) => format!("stopped (thread {})", thread_id),
+            None => "running".to_string(),
+        };
+        let parent = match &self.parent {
+            Some(parent) => format!("started by {}", parent),
+            None => String::new(),
+        };
+        Row::new(vec![
+            Cell::from(marker),
+            Cell::from(self.name.clone()),
+            Cell::from(state),
+            Cell::from(parent),
+        ])
+    }
+}
+
+pub fn dap_switch_session(cx: &mut Context) {
+    let debuggers = &cx.editor.debuggers;
+    if debuggers.is_empty() {
+        cx.editor.set_error("No debug session is running");
+        return;
+    }
+    let sessions = debuggers
+        .iter()
+        .map(|debugger| SessionItem {
+            id: debugger.id(),
+            name: debugger.name.clone(),
+            parent: debugger
+                .parent
+                .and_then(|parent| debuggers.get(parent))
+                .map(|parent| parent.name.clone()),
+            thread_id: debugger.thread_id,
+        })
+        .collect();
+    let active = debuggers.active_id();
+
+    cx.push_layer(Box::new(overlayed(Picker::new(
+        sessions,
+        active,
+        |cx, session: &SessionItem, _action| {
+            block_on(cx.editor.select_debug_session(session.id));
+        },
+    ))));
+}
+
+fn update_exception_breakpoints(editor: &mut Editor) {
+    if let Err(e) = editor.send_exception_breakpoints() {
+        editor.set_error(e.to_string());
+    }
+}
+
+pub fn dap_enable_exceptions(cx: &mut Context) {
+    let debugger = debugger!(cx.editor);
+
+    let filters = match &debugger.capabilities().exception_breakpoint_filters {
+        Some(filters) => filters
+            .iter()
+            .map(|f| ExceptionBreakpoint {
+                filter: f.filter.clone(),
+                condition: None,
+            })
+            .collect(),
+        None => return,
+    };
+    cx.editor.exception_breakpoints = filters;
+    update_exception_breakpoints(cx.editor);
 }
 
 pub fn dap_disable_exceptions(cx: &mut Context) {
+    cx.editor.exception_breakpoints.clear();
+    update_exception_breakpoints(cx.editor);
+}
+
+pub fn dap_exception_breakpoints(cx: &mut Context) {
     let debugger = debugger!(cx.editor);
 
-    let request = debugger.set_exception_breakpoints(Vec::new());
+    let filters = match &debugger.capabilities().exception_breakpoint_filters {
+        Some(filters) if !filters.is_empty() => filters.clone(),
+        _ => {
+            cx.editor
+                .set_status("The debugger does not advertise exception filters");
+            return;
+        }
+    };
+    let supports_conditions = debugger
+        .capabilities()
+        .supports_exception_filter_options
+        .unwrap_or_default();
+
+    let picker = Picker::new(
+        filters,
+        cx.editor.exception_breakpoints.clone(),
+        move |cx, filter: &dap::ExceptionBreakpointsFilter, _action| {
+            let breakpoints = &mut cx.editor.exception_breakpoints;
+            if let Some(i) = breakpoints.iter().position(|b| b.filter == filter.filter) {
+                breakpoints.remove(i);
+                update_exception_breakpoints(cx.editor);
+                return;
+            }
+            if !(supports_conditions && filter.supports_condition.unwrap_or_default()) {
+                breakpoints.push(ExceptionBreakpoint {
+                    filter: filter.filter.clone(),
+                    condition: None,
+                });
+                update_exception_breakpoints(cx.editor);
+                return;
+            }
 
-    dap_callback(
-        cx.jobs,
-        request,
-        |_editor, _compositor, _response: dap::requests::SetExceptionBreakpointsResponse| {
-            // editor.set_error(format!("Failed to set up exception breakpoints: {}", e));
+            let filter_id = filter.filter.clone();
+            let label = filter
+                .condition_description
+                .as_deref()
+                .map_or_else(|| "condition:".into(), |desc| format!("{}:", desc));
+            let callback = Box::pin(async move {
+                let call: Callback =
+                    Callback::EditorCompositor(Box::new(move |_editor, compositor| {
+                        let prompt = Prompt::new(
+                            label.into(),
+                            None,
+                            ui::completers::none,
+                            move |cx, input: &str, event: PromptEvent| {
+                                if event != PromptEvent::Validate {
+                                    return;
+                                }
+                                cx.editor.exception_breakpoints.push(ExceptionBreakpoint {
+                                    filter: filter_id.clone(),
+                                    condition: (!input.is_empty()).then(|| input.to_owned()),
+                                });
+                                update_exception_breakpoints(cx.editor);
+                            },
+                        );
+                        compositor.push(Box::new(prompt));
+                    }));
+                Ok(call)
+            });
+            cx.jobs.callback(callback);
         },
-    )
+    );
+    cx.push_layer(Box::new(overlayed(picker)));
+}
+
+pub fn dap_toggle_function_breakpoint(cx: &mut Context) {
+    let prompt = Prompt::new(
+        "function:".into(),
+        None,
+        ui::completers::none,
+        |cx, input: &str, event: PromptEvent| {
+            if event != PromptEvent::Validate || input.is_empty() {
+                return;
+            }
+            let editor = &mut *cx.editor;
+            let breakpoints = &mut editor.function_breakpoints;
+            let status = match breakpoints.iter().position(|b| b.name == input) {
+                Some(i) => {
+                    breakpoints.remove(i);
+                    format!("Removed the breakpoint on {}", input)
+                }
+                None => {
+                    breakpoints.push(FunctionBreakpoint {
+                        name: input.to_owned(),
+                        ..Default::default()
+                    });
+                    format!("Added a breakpoint on {}", input)
+                }
+            };
+            // sessions started later get them once initialized
+            if let Err(e) = editor.send_function_breakpoints() {
+                editor.set_error(e.to_string());
+                return;
+            }
+            editor.set_status(status);
+        },
+    );
+    cx.push_layer(Box::new(prompt));
 }
 
 // TODO: both edit condition and edit log need to be stable: we might get new breakpoints from the debugger which can change offsets
@@ -597,18 +1297,13 @@ pub fn dap_edit_condition(cx: &mut Context) {
                             return;
                         }
 
-                        let breakpoints = &mut cx.editor.breakpoints.get_mut(&path).unwrap();
+                        let breakpoints = cx.editor.breakpoints.get_mut(&path).unwrap();
                         breakpoints[pos].condition = match input {
                             "" => None,
                             input => Some(input.to_owned()),
                         };
 
-                        let debugger = debugger!(cx.editor);
-
-                        if let Err(e) = breakpoints_changed(debugger, path.clone(), breakpoints) {
-                            cx.editor
-                                .set_error(format!("Failed to set breakpoints: {}", e));
-                        }
+                        update_breakpoints(cx.editor, path.clone());
                     },
                 );
                 if let Some(condition) = breakpoint.condition {
@@ -639,17 +1334,13 @@ pub fn dap_edit_log(cx: &mut Context) {
                             return;
                         }
 
-                        let breakpoints = &mut cx.editor.breakpoints.get_mut(&path).unwrap();
+                        let breakpoints = cx.editor.breakpoints.get_mut(&path).unwrap();
                         breakpoints[pos].log_message = match input {
                             "" => None,
                             input => Some(input.to_owned()),
                         };
 
-                        let debugger = debugger!(cx.editor);
-                        if let Err(e) = breakpoints_changed(debugger, path.clone(), breakpoints) {
-                            cx.editor
-                                .set_error(format!("Failed to set breakpoints: {}", e));
-                        }
+                        update_breakpoints(cx.editor, path.clone());
                     },
                 );
                 if let Some(log_message) = breakpoint.log_message {
@@ -698,6 +1389,7 @@ pub fn dap_switch_stack_frame(cx: &mut Context) {
             if let Some(frame) = &frame {
                 jump_to_stack_frame(cx.editor, frame);
             }
+            block_on(cx.editor.refresh_debug_values());
         },
         move |_editor, frame| {
             frame
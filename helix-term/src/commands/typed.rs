@@ -4,6 +4,8 @@
 
 use super::*;
 
+use helix_view::debug_console::ConsoleEntry;
+use helix_view::document::DiffBase;
 use helix_view::editor::{Action, CloseError, ConfigEvent};
 use ui::completers::{self, Completer};
 
@@ -66,16 +68,7 @@ fn open(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) ->
         // If the path is a directory, open a file picker on that directory and update the status
         // message
         if let Ok(true) = std::fs::canonicalize(&path).map(|p| p.is_dir()) {
-            let callback = async move {
-                let call: job::Callback = job::Callback::EditorCompositor(Box::new(
-                    move |editor: &mut Editor, compositor: &mut Compositor| {
-                        let picker = ui::file_picker(path, &editor.config());
-                        compositor.push(Box::new(overlayed(picker)));
-                    },
-                ));
-                Ok(call)
-            };
-            cx.jobs.callback(callback);
+            ui::open_file_picker(cx.jobs, cx.editor, path);
         } else {
             // Otherwise, just open the file
             let _ = cx.editor.open(&path, Action::Replace)?;
@@ -283,6 +276,12 @@ fn write_impl(
     path: Option<&Cow<str>>,
     force: bool,
 ) -> anyhow::Result<()> {
+    let doc_id = doc!(cx.editor).id();
+    if path.is_none() && cx.editor.search_buffers.contains_key(&doc_id) {
+        write_search_buffer(cx.editor, doc_id);
+        return Ok(());
+    }
+
     let editor_auto_fmt = cx.editor.config().auto_format;
     let jobs = &mut cx.jobs;
     let (view, doc) = current!(cx.editor);
@@ -581,9 +580,24 @@ pub fn write_all_impl(
     write_scratch: bool,
 ) -> anyhow::Result<()> {
     let mut errors: Vec<&'static str> = Vec::new();
+
+    // search results buffers are written back to the files they list instead
+    let search_buffers: Vec<_> = cx
+        .editor
+        .search_buffers
+        .keys()
+        .copied()
+        .filter(|doc_id| doc!(cx.editor, doc_id).is_modified())
+        .collect();
+    let mut saved = Vec::new();
+    for doc_id in search_buffers {
+        saved.extend(write_search_buffer(cx.editor, doc_id));
+    }
+
     let auto_format = cx.editor.config().auto_format;
     let jobs = &mut cx.jobs;
     let current_view = view!(cx.editor);
+    let search_buffers = &cx.editor.search_buffers;
 
     // save all documents
     let saves: Vec<_> = cx
@@ -591,7 +605,10 @@ pub fn write_all_impl(
         .documents
         .values_mut()
         .filter_map(|doc| {
-            if !doc.is_modified() {
+            if !doc.is_modified()
+                || search_buffers.contains_key(&doc.id())
+                || saved.contains(&doc.id())
+            {
                 return None;
             }
             if doc.path().is_none() {
@@ -1047,7 +1064,11 @@ fn reload(
     doc.reload(view, &cx.editor.diff_providers, redraw_handle)
         .map(|_| {
             view.ensure_cursor_in_view(doc, scrolloff);
-        })
+        })?;
+    let doc_id = doc.id();
+    cx.editor.refresh_blame(doc_id);
+    cx.editor.refresh_repo_status(doc_id);
+    Ok(())
 }
 
 fn reload_all(
@@ -1095,6 +1116,8 @@ fn reload_all(
                 view.ensure_cursor_in_view(doc, scrolloff);
             }
         }
+        cx.editor.refresh_blame(doc_id);
+        cx.editor.refresh_repo_status(doc_id);
     }
 
     Ok(())
@@ -1330,6 +1353,42 @@ fn hsplit_new(
     Ok(())
 }
 
+fn open_terminal(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    action: Action,
+) -> anyhow::Result<()> {
+    // the program is run with the arguments as they were quoted, without one the default
+    // shell is started
+    let command: Vec<_> = args.iter().map(|arg| arg.to_string()).collect();
+    cx.editor.open_terminal(action, &command)?;
+    Ok(())
+}
+
+fn terminal(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    open_terminal(cx, args, Action::HorizontalSplit)
+}
+
+fn vterminal(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    open_terminal(cx, args, Action::VerticalSplit)
+}
+
 fn debug_eval(
     cx: &mut compositor::Context,
     args: &[Cow<str>],
@@ -1339,23 +1398,86 @@ fn debug_eval(
         return Ok(());
     }
 
-    if let Some(debugger) = cx.editor.debugger.as_mut() {
-        let (frame, thread_id) = match (debugger.active_frame, debugger.thread_id) {
-            (Some(frame), Some(thread_id)) => (frame, thread_id),
-            _ => {
-                bail!("Cannot find current stack frame to access variables")
-            }
+    if let Some(debugger) = cx.editor.debuggers.get_active() {
+        if debugger.current_stack_frame().is_none() {
+            bail!("Cannot find current stack frame to access variables")
+        }
+
+        // the evaluation is recorded in the debug console, where the result can be expanded
+        let console = &mut cx.editor.debug_console;
+        console.evaluate(Some(debugger), args.join(" "));
+        let status = match console.entries.last() {
+            Some(ConsoleEntry::Value(value)) => value.value.clone(),
+            Some(ConsoleEntry::Error(err)) => bail!("{}", err),
+            _ => return Ok(()),
         };
+        cx.editor.set_status(status);
+    }
+    Ok(())
+}
 
-        // TODO: support no frame_id
+fn debug_watch(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
 
-        let frame_id = debugger.stack_frames[&thread_id][frame].id;
-        let response = helix_lsp::block_on(debugger.eval(args.join(" "), Some(frame_id)))?;
-        cx.editor.set_status(response.result);
+    if args.is_empty() {
+        bail!("Expected an expression to watch");
     }
+    cx.editor.add_watch(args.join(" "));
     Ok(())
 }
 
+fn debug_memory(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    // the byte count is optional, after an expression which may contain spaces
+    let (args, count) = match args.split_last() {
+        Some((count, rest)) if !rest.is_empty() => match count.parse() {
+            Ok(count) => (rest, count),
+            Err(_) => (args, 256),
+        },
+        _ => (args, 256),
+    };
+    if args.is_empty() {
+        bail!("Expected an expression or an address");
+    }
+    let expression = args.join(" ");
+
+    let debugger = match cx.editor.debuggers.get_active() {
+        Some(debugger) => debugger,
+        None => bail!("Debugger is not running"),
+    };
+    let memory_reference = if expression.starts_with("0x") {
+        expression
+    } else {
+        let frame_id = match debugger.current_stack_frame() {
+            Some(frame) => frame.id,
+            None => bail!("Cannot find current stack frame to evaluate the expression"),
+        };
+        let response = helix_lsp::block_on(debugger.eval(
+            expression.clone(),
+            Some(frame_id),
+            Some("watch".to_string()),
+        ))?;
+        match response.memory_reference {
+            Some(memory_reference) => memory_reference,
+            None => bail!("`{}` does not refer to memory", expression),
+        }
+    };
+    helix_lsp::block_on(cx.editor.show_memory(memory_reference, count))
+}
+
 fn debug_start(
     cx: &mut compositor::Context,
     args: &[Cow<str>],
@@ -1822,6 +1944,140 @@ fn run_shell_command(
     Ok(())
 }
 
+fn diff_base(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    ensure!(args.len() <= 1, ":diff-base takes at most one argument");
+
+    let diff_base = match args.first() {
+        Some(revision) => DiffBase::Revision(revision.to_string()),
+        None => DiffBase::Head,
+    };
+    let redraw_handle = cx.editor.redraw_handle.clone();
+    let doc = doc_mut!(cx.editor);
+    ensure!(doc.path().is_some(), "Buffer has no path");
+    if !doc.load_diff_base(diff_base, &cx.editor.diff_providers, redraw_handle) {
+        bail!("'{}' not found in the version control history", args[0]);
+    }
+    Ok(())
+}
+
+fn diff_buffer(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    ensure!(args.len() == 1, ":diff-buffer takes one argument");
+
+    let path = Path::new(args[0].as_ref());
+    let other_id = cx.editor.documents().find_map(|doc| {
+        let arg_path = Some(path);
+        if doc.path().map(|p| p.as_path()) == arg_path || doc.relative_path().as_deref() == arg_path
+        {
+            Some(doc.id())
+        } else {
+            None
+        }
+    });
+    let other_id = match other_id {
+        Some(other_id) => other_id,
+        None => cx.editor.open(path, Action::Load)?,
+    };
+    let (view, doc) = current!(cx.editor);
+    let (view_id, doc_id) = (view.id, doc.id());
+    ensure!(other_id != doc_id, "Can't compare a buffer to itself");
+
+    // show the other buffer next to the current one, unless it is already shown
+    if !cx.editor.tree.views().any(|(view, _)| view.doc == other_id) {
+        cx.editor.switch(other_id, Action::VerticalSplit);
+        cx.editor.focus(view_id);
+    }
+
+    cx.editor.set_diff_base_document(doc_id, other_id);
+    Ok(())
+}
+
+fn diff_split(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc = doc!(cx.editor);
+    let diff_base = match doc.diff_handle() {
+        Some(diff_handle) => diff_handle.diff_base(),
+        None => bail!("Diff is not available in current buffer"),
+    };
+    let mut base = Document::from(diff_base, Some(doc.encoding()));
+    if let Some(language_config) = doc.language_config() {
+        base.set_language_by_language_id(
+            &language_config.language_id,
+            cx.editor.syn_loader.clone(),
+        )?;
+    }
+    base.readonly = true;
+
+    let doc_id = doc.id();
+    let base_id = cx.editor.new_file_from_document(Action::Load, base);
+    cx.editor.diff_split(base_id, doc_id);
+    Ok(())
+}
+
+fn git_log(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    ensure!(args.len() <= 1, ":git-log takes at most one argument");
+    let set_diff_base = match args.first().map(|arg| arg.as_ref()) {
+        None => false,
+        Some("diff-base") => true,
+        Some(arg) => bail!("Unknown argument '{}', expected 'diff-base'", arg),
+    };
+
+    let doc = doc!(cx.editor);
+    let path = match doc.path() {
+        Some(path) => path.clone(),
+        None => bail!("Buffer has no path"),
+    };
+    let doc_id = doc.id();
+    let providers = cx.editor.diff_providers.clone();
+    let callback = async move {
+        let history = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || providers.file_history(&path)
+        })
+        .await?;
+
+        let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| match history {
+                Some(commits) if !commits.is_empty() => {
+                    let picker = file_history_picker(commits, path, doc_id, set_diff_base);
+                    compositor.push(Box::new(overlayed(picker)));
+                }
+                _ => editor.set_error("No history found for this file"),
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+    Ok(())
+}
+
 pub const TYPABLE_COMMAND_LIST: &[TypableCommand] = &[
         TypableCommand {
             name: "quit",
@@ -2190,6 +2446,20 @@ fn run_shell_command(
             fun: debug_eval,
             completer: None,
         },
+        TypableCommand {
+            name: "debug-watch",
+            aliases: &[],
+            doc: "Watch an expression, evaluating it whenever the debugger stops.",
+            fun: debug_watch,
+            completer: None,
+        },
+        TypableCommand {
+            name: "debug-memory",
+            aliases: &[],
+            doc: "Show the memory an expression or address refers to, optionally followed by the number of bytes (256 by default).",
+            fun: debug_memory,
+            completer: None,
+        },
         TypableCommand {
             name: "vsplit",
             aliases: &["vs"],
@@ -2218,6 +2488,20 @@ fn run_shell_command(
             fun: hsplit_new,
             completer: None,
         },
+        TypableCommand {
+            name: "terminal",
+            aliases: &["term"],
+            doc: "Open a terminal in a horizontal split, running the given program or the default shell.",
+            fun: terminal,
+            completer: Some(completers::filename),
+        },
+        TypableCommand {
+            name: "vterminal",
+            aliases: &["vterm"],
+            doc: "Open a terminal in a vertical split, running the given program or the default shell.",
+            fun: vterminal,
+            completer: Some(completers::filename),
+        },
         TypableCommand {
             name: "tutor",
             aliases: &[],
@@ -2337,6 +2621,34 @@ fn run_shell_command(
             fun: run_shell_command,
             completer: Some(completers::filename),
         },
+        TypableCommand {
+            name: "diff-base",
+            aliases: &[],
+            doc: "Compare the current buffer to a revision (a branch, tag, commit or `index`), or to the last commit if no revision is given.",
+            fun: diff_base,
+            completer: None,
+        },
+        TypableCommand {
+            name: "diff-buffer",
+            aliases: &[],
+            doc: "Compare the current buffer to another buffer shown beside it, scrolling both together.",
+            fun: diff_buffer,
+            completer: Some(completers::buffer),
+        },
+        TypableCommand {
+            name: "diff-split",
+            aliases: &[],
+            doc: "Show the diff base of the current buffer in a read-only buffer beside it, side by side.",
+            fun: diff_split,
+            completer: None,
+        },
+        TypableCommand {
+            name: "git-log",
+            aliases: &[],
+            doc: "Pick a commit that changed the current file to open the file as of it, or with `diff-base` to compare the buffer to it.",
+            fun: git_log,
+            completer: None,
+        },
     ];
 
 pub static TYPABLE_COMMAND_MAP: Lazy<HashMap<&'static str, &'static TypableCommand>> =
@@ -0,0 +1,454 @@
+use super::Context;
+use crate::{
+    job::Callback,
+    ui::{self, overlay::overlayed, FilePicker, Popup},
+};
+use std::ops::Range as LineRange;
+use std::path::{Path, PathBuf};
+
+use helix_core::{find_root, movement::Direction, Range, Rope, Tendril, Transaction};
+use helix_vcs::{hunk_patch, BlameCommit, CommitDiff, Conflict, Hunk};
+use helix_view::{
+    document::{from_reader, DiffBase, Mode},
+    editor::{Action, Motion},
+    Document, DocumentId, Editor, View,
+};
+use tui::widgets::{Cell, Row};
+
+/// Shows the commit that last changed the line of the cursor in a popup, with its message and
+/// the changes it made to the file.
+pub fn blame_line(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let path = match doc.path() {
+        Some(path) => path.clone(),
+        None => {
+            cx.editor.set_error("Buffer has no path");
+            return;
+        }
+    };
+    if doc.diff_base != DiffBase::Head {
+        cx.editor
+            .set_error("Blame is only available when comparing to the last commit");
+        return;
+    }
+    let line = doc
+        .selection(view.id)
+        .primary()
+        .cursor_line(doc.text().slice(..)) as u32;
+    // the blame is computed for the diff base, which the uncommitted changes are relative to
+    let base_line = match doc.diff_handle() {
+        Some(diff_handle) => diff_handle.hunks().base_line(line),
+        None => Some(line),
+    };
+    let base_line = match base_line {
+        Some(base_line) => base_line,
+        None => {
+            cx.editor.set_status("Not committed yet");
+            return;
+        }
+    };
+
+    let blame = doc.blame().cloned();
+    let providers = cx.editor.diff_providers.clone();
+    let callback = async move {
+        let commit = tokio::task::spawn_blocking(move || {
+            let blame = blame.or_else(|| providers.blame(&path))?;
+            let commit = blame.base_line_commit(base_line)?.clone();
+            let diff = providers.commit_diff(&path, &commit.id);
+            Some((commit, diff))
+        })
+        .await?;
+
+        let call = Callback::EditorCompositor(Box::new(move |editor, compositor| {
+            let (commit, diff) = match commit {
+                Some(commit) => commit,
+                None => {
+                    editor.set_error("No commit found for this line");
+                    return;
+                }
+            };
+            let contents = ui::Markdown::new(
+                blame_markdown(&commit, diff.as_ref()),
+                editor.syn_loader.clone(),
+            );
+            let popup = Popup::new("blame", contents).auto_close(true);
+            compositor.replace_or_push("blame", popup);
+        }));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+}
+
+fn blame_markdown(commit: &BlameCommit, diff: Option<&CommitDiff>) -> String {
+    let header = format!("`{}` {} {}", commit.id, commit.author, commit.date());
+    match diff {
+        Some(diff) => format!(
+            "{}\n\n{}\n\n```diff\n{}```",
+            header,
+            diff.message.trim_end(),
+            diff.diff
+        ),
+        None => format!("{}\n\n{}", header, commit.summary),
+    }
+}
+
+/// Returns the change at the cursor along with the diff base it was made to.
+fn cursor_hunk(doc: &Document, view: &View) -> Result<(Hunk, Rope), &'static str> {
+    let diff_handle = doc
+        .diff_handle()
+        .ok_or("Diff is not available in current buffer")?;
+    let line = doc
+        .selection(view.id)
+        .primary()
+        .cursor_line(doc.text().slice(..)) as u32;
+    let hunks = diff_handle.hunks();
+    let hunk = hunks
+        .hunk_at(line, true)
+        .map(|hunk| hunks.nth_hunk(hunk))
+        .ok_or("There is no change at the cursor")?;
+    Ok((hunk, diff_handle.diff_base()))
+}
+
+/// Replaces the change at the cursor with the text of the diff base.
+pub fn revert_hunk(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let (hunk, diff_base) = match cursor_hunk(doc, view) {
+        Ok(hunk) => hunk,
+        Err(err) => {
+            cx.editor.set_status(err);
+            return;
+        }
+    };
+
+    let before_start = diff_base.line_to_char(hunk.before.start as usize);
+    let before_end = diff_base.line_to_char(hunk.before.end as usize);
+    let text: Tendril = diff_base.slice(before_start..before_end).to_string().into();
+    let start = doc.text().line_to_char(hunk.after.start as usize);
+    let end = doc.text().line_to_char(hunk.after.end as usize);
+    let transaction = Transaction::change(doc.text(), std::iter::once((start, end, Some(text))));
+    doc.apply(&transaction, view.id);
+}
+
+pub fn stage_hunk(cx: &mut Context) {
+    apply_hunk_to_index(cx, false)
+}
+
+pub fn unstage_hunk(cx: &mut Context) {
+    apply_hunk_to_index(cx, true)
+}
+
+/// Adds the change at the cursor to the index of changes to commit, or removes it with
+/// `reverse`.
+fn apply_hunk_to_index(cx: &mut Context, reverse: bool) {
+    let (view, doc) = current_ref!(cx.editor);
+    let path = match doc.path() {
+        Some(path) => path.clone(),
+        None => {
+            cx.editor.set_error("Buffer has no path");
+            return;
+        }
+    };
+    let (hunk, diff_base) = match cursor_hunk(doc, view) {
+        Ok(hunk) => hunk,
+        Err(err) => {
+            cx.editor.set_status(err);
+            return;
+        }
+    };
+    let patch = hunk_patch(&diff_base, doc.text(), &hunk);
+
+    match cx
+        .editor
+        .diff_providers
+        .apply_to_index(&path, &patch, reverse)
+    {
+        Ok(()) if reverse => cx.editor.set_status("Unstaged the change"),
+        Ok(()) => cx.editor.set_status("Staged the change"),
+        Err(err) if reverse => cx
+            .editor
+            .set_error(format!("Failed to unstage the change: {}", err)),
+        Err(err) => cx
+            .editor
+            .set_error(format!("Failed to stage the change: {}", err)),
+    }
+}
+
+/// Shows the change at the cursor as a diff in a popup.
+pub fn preview_hunk(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let (hunk, diff_base) = match cursor_hunk(doc, view) {
+        Ok(hunk) => hunk,
+        Err(err) => {
+            cx.editor.set_status(err);
+            return;
+        }
+    };
+    let patch = hunk_patch(&diff_base, doc.text(), &hunk);
+
+    let contents = ui::Markdown::new(
+        format!("```diff\n{}```", patch),
+        cx.editor.syn_loader.clone(),
+    );
+    let popup = Popup::new("hunk", contents).auto_close(true);
+    cx.push_layer(Box::new(popup));
+}
+
+/// Opens a picker of the files changed since the last commit in the current repository.
+pub fn changed_file_picker(cx: &mut Context) {
+    let root = find_root(None, &[]);
+    let providers = cx.editor.diff_providers.clone();
+    let callback = async move {
+        let changes = tokio::task::spawn_blocking({
+            let root = root.clone();
+            move || providers.changed_files(&root)
+        })
+        .await?;
+        let call = Callback::EditorCompositor(Box::new(move |editor, compositor| {
+            if changes.is_empty() {
+                editor.set_status("There are no changed files");
+                return;
+            }
+            let picker = ui::changed_file_picker(changes, root);
+            compositor.push(Box::new(overlayed(picker)));
+        }));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+}
+
+impl ui::menu::Item for BlameCommit {
+    type Data = ();
+
+    fn format(&self, _data: &Self::Data) -> Row {
+        Row::new(vec![
+            Cell::from(self.short_id().to_owned()),
+            Cell::from(self.date()),
+            Cell::from(self.author.clone()),
+            Cell::from(self.summary.clone()),
+        ])
+    }
+}
+
+/// A picker of the `commits` that changed the file of the document `doc_id`, previewing the
+/// changes each made. Picking a commit opens the file as of the commit in a read-only buffer, or
+/// compares the document to it with `set_diff_base`.
+pub fn file_history_picker(
+    commits: Vec<BlameCommit>,
+    path: PathBuf,
+    doc_id: DocumentId,
+    set_diff_base: bool,
+) -> FilePicker<BlameCommit> {
+    let preview_path = path.clone();
+    let diff_path = path.clone();
+    FilePicker::new(
+        commits,
+        (),
+        move |cx, commit: &BlameCommit, action| {
+            if set_diff_base {
+                compare_to_commit(cx.editor, doc_id, commit);
+            } else {
+                open_file_at_commit(cx.editor, doc_id, &path, commit, action);
+            }
+        },
+        move |_editor, _commit| Some((preview_path.clone().into(), None)),
+    )
+    .with_diff_preview(move |editor, commit| {
+        let diff = editor.diff_providers.commit_diff(&diff_path, &commit.id)?;
+        Some(diff.diff)
+    })
+}
+
+/// Opens the file of the document `doc_id` as of `commit` in a read-only scratch buffer.
+fn open_file_at_commit(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    path: &Path,
+    commit: &BlameCommit,
+    action: Action,
+) {
+    let data = match editor.diff_providers.get_file_at(path, &commit.id) {
+        Some(data) => data,
+        None => {
+            editor.set_error(format!("File not found in commit {}", commit.short_id()));
+            return;
+        }
+    };
+    let (encoding, language_id) = match editor.documents.get(&doc_id) {
+        Some(doc) => (
+            Some(doc.encoding()),
+            doc.language_config()
+                .map(|config| config.language_id.clone()),
+        ),
+        None => (None, None),
+    };
+    let (text, encoding) = match from_reader(&mut data.as_slice(), encoding) {
+        Ok(text) => text,
+        Err(err) => {
+            editor.set_error(err.to_string());
+            return;
+        }
+    };
+    let mut doc = Document::from(text, Some(encoding));
+    if let Some(language_id) = language_id {
+        let _ = doc.set_language_by_language_id(&language_id, editor.syn_loader.clone());
+    }
+    doc.readonly = true;
+    editor.new_file_from_document(action, doc);
+    editor.set_status(format!(
+        "{} as of {} {}",
+        path.display(),
+        commit.short_id(),
+        commit.summary
+    ));
+}
+
+/// Compares the document `doc_id` to its file as of `commit`.
+fn compare_to_commit(editor: &mut Editor, doc_id: DocumentId, commit: &BlameCommit) {
+    let redraw_handle = editor.redraw_handle.clone();
+    let doc = match editor.documents.get_mut(&doc_id) {
+        Some(doc) => doc,
+        None => {
+            editor.set_error("The buffer was closed");
+            return;
+        }
+    };
+    let diff_base = DiffBase::Revision(commit.id.clone());
+    if doc.load_diff_base(diff_base, &editor.diff_providers, redraw_handle) {
+        editor.set_status(format!("Comparing to {}", commit.short_id()));
+    } else {
+        editor.set_error(format!("File not found in commit {}", commit.short_id()));
+    }
+}
+
+pub fn goto_next_conflict(cx: &mut Context) {
+    goto_conflict_impl(cx, Direction::Forward)
+}
+
+pub fn goto_prev_conflict(cx: &mut Context) {
+    goto_conflict_impl(cx, Direction::Backward)
+}
+
+/// Selects the next or previous conflict left by a merge, including its markers.
+fn goto_conflict_impl(cx: &mut Context, direction: Direction) {
+    let count = cx.count() - 1;
+    let motion = move |editor: &mut Editor| {
+        let (view, doc) = current!(editor);
+        let text = doc.text().slice(..);
+        let conflicts = doc.conflicts();
+        if conflicts.is_empty() {
+            editor.set_status("There are no conflicts in current buffer");
+            return;
+        }
+
+        let selection = doc.selection(view.id).clone().transform(|range| {
+            let cursor_line = range.cursor_line(text);
+            let conflict = match direction {
+                Direction::Forward => conflicts
+                    .iter()
+                    .position(|conflict| conflict.lines.start > cursor_line)
+                    .map(|idx| (idx + count).min(conflicts.len() - 1)),
+                Direction::Backward => conflicts
+                    .iter()
+                    .rposition(|conflict| conflict.lines.end <= cursor_line)
+                    .map(|idx| idx.saturating_sub(count)),
+            };
+            let conflict = match conflict {
+                Some(conflict) => &conflicts[conflict],
+                None => return range,
+            };
+
+            let new_range = Range::new(
+                text.line_to_char(conflict.lines.start),
+                text.line_to_char(conflict.lines.end),
+            );
+            if editor.mode == Mode::Select {
+                let head = if new_range.head < range.anchor {
+                    new_range.anchor
+                } else {
+                    new_range.head
+                };
+
+                Range::new(range.anchor, head)
+            } else {
+                new_range.with_direction(direction)
+            }
+        });
+
+        doc.set_selection(view.id, selection)
+    };
+    motion(cx.editor);
+    cx.editor.last_motion = Some(Motion(Box::new(motion)));
+}
+
+pub fn conflict_accept_ours(cx: &mut Context) {
+    resolve_conflicts(cx, |conflict| Some(vec![conflict.ours.clone()]))
+}
+
+pub fn conflict_accept_theirs(cx: &mut Context) {
+    resolve_conflicts(cx, |conflict| Some(vec![conflict.theirs.clone()]))
+}
+
+pub fn conflict_accept_both(cx: &mut Context) {
+    resolve_conflicts(cx, |conflict| {
+        Some(vec![conflict.ours.clone(), conflict.theirs.clone()])
+    })
+}
+
+pub fn conflict_accept_base(cx: &mut Context) {
+    resolve_conflicts(cx, |conflict| conflict.base.clone().map(|base| vec![base]))
+}
+
+/// Replaces the conflicts at the cursors with the lines `keep` returns for each, or leaves
+/// them be if it returns `None`.
+fn resolve_conflicts(cx: &mut Context, keep: fn(&Conflict) -> Option<Vec<LineRange<usize>>>) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let selection = doc.selection(view.id);
+    let conflicts: Vec<_> = doc
+        .conflicts()
+        .iter()
+        .filter(|conflict| {
+            selection
+                .iter()
+                .any(|range| conflict.lines.contains(&range.cursor_line(text)))
+        })
+        .collect();
+    if conflicts.is_empty() {
+        cx.editor.set_status("There is no conflict at the cursor");
+        return;
+    }
+
+    let mut missing_base = false;
+    let changes: Vec<_> = conflicts
+        .into_iter()
+        .filter_map(|conflict| {
+            let lines = match keep(conflict) {
+                Some(lines) => lines,
+                None => {
+                    missing_base = true;
+                    return None;
+                }
+            };
+            let resolution: String = lines
+                .into_iter()
+                .map(|lines| {
+                    text.slice(text.line_to_char(lines.start)..text.line_to_char(lines.end))
+                        .to_string()
+                })
+                .collect();
+            Some((
+                text.line_to_char(conflict.lines.start),
+                text.line_to_char(conflict.lines.end),
+                Some(Tendril::from(resolution)),
+            ))
+        })
+        .collect();
+
+    let transaction = Transaction::change(doc.text(), changes.into_iter());
+    doc.apply(&transaction, view.id);
+    if missing_base {
+        // conflicts only have a base in the diff3 style of git's merge.conflictStyle
+        cx.editor
+            .set_error("The conflict has no base, set merge.conflictStyle to diff3");
+    }
+}
@@ -104,6 +104,7 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "D" => goto_first_diag,
             "g" => goto_prev_change,
             "G" => goto_first_change,
+            "n" => goto_prev_conflict,
             "f" => goto_prev_function,
             "t" => goto_prev_class,
             "a" => goto_prev_parameter,
@@ -117,6 +118,7 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "D" => goto_last_diag,
             "g" => goto_next_change,
             "G" => goto_last_change,
+            "n" => goto_next_conflict,
             "f" => goto_next_function,
             "t" => goto_next_class,
             "a" => goto_next_parameter,
@@ -211,6 +213,9 @@ pub fn default() -> HashMap<Mode, Keymap> {
         "space" => { "Space"
             "f" => file_picker,
             "F" => file_picker_in_current_directory,
+            "e" => toggle_file_explorer,
+            "t" => terminal_send_selection,
+            "x" => repl_send_selection,
             "b" => buffer_picker,
             "j" => jumplist_picker,
             "s" => symbol_picker,
@@ -219,25 +224,51 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "D" => workspace_diagnostics_picker,
             "a" => code_action,
             "'" => last_picker,
+            "v" => { "Version control"
+                "b" => blame_line,
+                "r" => revert_hunk,
+                "s" => stage_hunk,
+                "u" => unstage_hunk,
+                "p" => preview_hunk,
+                "f" => changed_file_picker,
+                "o" => conflict_accept_ours,
+                "t" => conflict_accept_theirs,
+                "a" => conflict_accept_both,
+                "c" => conflict_accept_base,
+            },
             "g" => { "Debug (experimental)" sticky=true
                 "l" => dap_launch,
+                "a" => dap_attach,
                 "b" => dap_toggle_breakpoint,
+                "B" => dap_breakpoint_picker,
                 "c" => dap_continue,
                 "h" => dap_pause,
                 "i" => dap_step_in,
                 "o" => dap_step_out,
                 "n" => dap_next,
+                "u" => dap_run_to_cursor,
+                "j" => dap_goto_cursor,
+                "R" => dap_restart_frame,
+                "p" => dap_step_back,
+                "P" => dap_reverse_continue,
+                "d" => dap_disassembly,
                 "v" => dap_variables,
+                "r" => dap_console,
+                "w" => dap_watches,
+                "V" => dap_toggle_inline_values,
                 "t" => dap_terminate,
                 "C-c" => dap_edit_condition,
                 "C-l" => dap_edit_log,
                 "s" => { "Switch"
                     "t" => dap_switch_thread,
                     "f" => dap_switch_stack_frame,
+                    "s" => dap_switch_session,
                     // sl, sb
                 },
                 "e" => dap_enable_exceptions,
                 "E" => dap_disable_exceptions,
+                "x" => dap_exception_breakpoints,
+                "f" => dap_toggle_function_breakpoint,
             },
             "w" => { "Window"
                 "C-w" | "w" => rotate_view,
@@ -371,9 +402,16 @@ pub fn default() -> HashMap<Mode, Keymap> {
         "home" => goto_line_start,
         "end" => goto_line_end_newline,
     });
+    let terminal = keymap!({ "Terminal mode"
+        // most terminals report C-\ as C-4
+        "C-\\" | "C-4" => { "Terminal"
+            "C-n" | "n" => normal_mode,
+        },
+    });
     hashmap!(
         Mode::Normal => Keymap::new(normal),
         Mode::Select => Keymap::new(select),
         Mode::Insert => Keymap::new(insert),
+        Mode::Terminal => Keymap::new(terminal),
     )
 }
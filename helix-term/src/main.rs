@@ -73,6 +73,10 @@ async fn main_impl() -> Result<i32> {
     -V, --version                  Prints version information
     --vsplit                       Splits all given files vertically into different windows
     --hsplit                       Splits all given files horizontally into different windows
+    --diff <old> <new>             Compares two files side by side
+    --merge <base> <local> <remote> <merged>
+                                   Shows the local and remote files around the
+                                   merged file to resolve its conflicts
 ",
         env!("CARGO_PKG_NAME"),
         VERSION_AND_GIT_HASH,
@@ -0,0 +1,111 @@
+use std::{borrow::Cow, path::PathBuf};
+
+use helix_core::movement::Direction;
+use helix_view::{editor::Breakpoint, Editor};
+use tui::widgets::{Cell, Row};
+
+use crate::{
+    alt,
+    compositor::EventResult,
+    ctrl,
+    ui::{menu::Item, FilePicker},
+};
+
+/// A breakpoint of any file, as listed by the [`breakpoint_picker`].
+#[derive(Debug, Clone)]
+pub struct BreakpointItem {
+    pub path: PathBuf,
+    pub breakpoint: Breakpoint,
+}
+
+impl BreakpointItem {
+    fn location(&self) -> String {
+        let relative_path = helix_core::path::get_relative_path(&self.path);
+        format!("{}:{}", relative_path.display(), self.breakpoint.line + 1)
+    }
+
+    fn details(&self) -> String {
+        let breakpoint = &self.breakpoint;
+        let mut details = Vec::new();
+        if let Some(condition) = &breakpoint.condition {
+            details.push(format!("condition: {}", condition));
+        }
+        if let Some(hit_condition) = &breakpoint.hit_condition {
+            details.push(format!("hit condition: {}", hit_condition));
+        }
+        if let Some(log_message) = &breakpoint.log_message {
+            details.push(format!("log: {}", log_message));
+        }
+        details.join(", ")
+    }
+}
+
+impl Item for BreakpointItem {
+    type Data = ();
+
+    fn format(&self, _data: &Self::Data) -> Row {
+        let marker = if self.breakpoint.enabled {
+            "[x]"
+        } else {
+            "[ ]"
+        };
+        Row::new(vec![
+            Cell::from(marker),
+            Cell::from(self.location()),
+            Cell::from(self.details()),
+        ])
+    }
+
+    fn filter_text(&self, _data: &Self::Data) -> Cow<str> {
+        format!("{} {}", self.location(), self.details()).into()
+    }
+}
+
+/// Lists the breakpoints of the workspace. Breakpoints can be enabled or disabled with
+/// `C-space` and deleted with `A-d`. `update_fn` is called once the breakpoints of a file
+/// were changed.
+pub fn breakpoint_picker(
+    file_picker: FilePicker<BreakpointItem>,
+    update_fn: impl Fn(&mut Editor, PathBuf) + 'static,
+) -> FilePicker<BreakpointItem> {
+    file_picker.with_key_handler(move |picker, key_event, cx| {
+        match key_event {
+            ctrl!(' ') => {
+                let item = match picker.selection_mut() {
+                    Some(item) => item,
+                    None => return Some(EventResult::Consumed(None)),
+                };
+                item.breakpoint.enabled = !item.breakpoint.enabled;
+                let (path, line, enabled) = (
+                    item.path.clone(),
+                    item.breakpoint.line,
+                    item.breakpoint.enabled,
+                );
+                picker.move_by(1, Direction::Forward);
+
+                if let Some(breakpoint) = cx
+                    .editor
+                    .breakpoints
+                    .get_mut(&path)
+                    .and_then(|breakpoints| breakpoints.iter_mut().find(|b| b.line == line))
+                {
+                    breakpoint.enabled = enabled;
+                }
+                update_fn(cx.editor, path);
+            }
+            alt!('d') => {
+                let item = match picker.remove_selection() {
+                    Some(item) => item,
+                    None => return Some(EventResult::Consumed(None)),
+                };
+                if let Some(breakpoints) = cx.editor.breakpoints.get_mut(&item.path) {
+                    breakpoints.retain(|b| b.line != item.breakpoint.line);
+                }
+                update_fn(cx.editor, item.path);
+            }
+            _ => return None,
+        }
+
+        Some(EventResult::Consumed(None))
+    })
+}
@@ -0,0 +1,341 @@
+use helix_core::Position;
+use helix_lsp::block_on;
+use helix_view::{
+    debug_console::{ConsoleEntry, OutputCategory, VariableNode},
+    graphics::{CursorKind, Modifier, Rect},
+    Editor, Theme,
+};
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Tree, TreeItem, TreeState},
+};
+
+use super::prompt::Completion;
+use crate::{
+    alt,
+    compositor::{Component, Compositor, Context, Event, EventResult},
+    ctrl, key,
+    ui::{Prompt, PromptEvent},
+};
+
+/// Register the evaluated expressions are stored in.
+const HISTORY_REGISTER: char = '>';
+
+/// Identifies a value shown in a tree: the index of its root (like a console entry) and the
+/// indices of the children leading to it.
+pub(crate) type ValuePath = (usize, Vec<usize>);
+
+/// Shows the output of the debugged program and the debug adapter above an input line that
+/// evaluates expressions in the current stack frame. Values are expanded with `A-l` or `Enter`
+/// on an empty line, and `A-c` cycles through the output categories that are shown.
+pub struct DebugConsole {
+    prompt: Prompt,
+    state: TreeState,
+    /// Only output of this category is shown, all of it when `None`.
+    filter: Option<OutputCategory>,
+    /// Number of lines shown, known once rendered.
+    height: usize,
+}
+
+impl DebugConsole {
+    pub const ID: &'static str = "debug-console";
+
+    pub fn new() -> Self {
+        let prompt = Prompt::new(
+            "> ".into(),
+            Some(HISTORY_REGISTER),
+            completions,
+            |_cx: &mut Context, _input: &str, _event: PromptEvent| {},
+        );
+        Self {
+            prompt,
+            state: TreeState::default(),
+            filter: None,
+            height: 0,
+        }
+    }
+
+    fn lines(&self, editor: &Editor) -> Vec<(TreeItem<'static>, Option<ValuePath>)> {
+        let theme = &editor.theme;
+        let mut lines = Vec::new();
+        for (i, entry) in editor.debug_console.entries.iter().enumerate() {
+            match entry {
+                ConsoleEntry::Output { category, text } => {
+                    if self.filter.map_or(false, |filter| filter != *category) {
+                        continue;
+                    }
+                    let style = theme.get(match category {
+                        OutputCategory::Stdout => "ui.text",
+                        OutputCategory::Stderr => "error",
+                        OutputCategory::Important => "warning",
+                        OutputCategory::Console | OutputCategory::Other => "ui.text.inactive",
+                    });
+                    for line in text.lines() {
+                        let item = TreeItem::leaf(Span::styled(line.to_string(), style), 0);
+                        lines.push((item, None));
+                    }
+                }
+                _ if self.filter.is_some() => (),
+                ConsoleEntry::Input(expression) => {
+                    let content = Span::styled(format!("> {}", expression), theme.get("ui.text"));
+                    lines.push((TreeItem::leaf(content, 0), None));
+                }
+                ConsoleEntry::Value(node) => {
+                    push_value(&mut lines, theme, node, (i, Vec::new()));
+                }
+                ConsoleEntry::Error(err) => {
+                    let content = Span::styled(err.clone(), theme.get("error"));
+                    lines.push((TreeItem::leaf(content, 0), None));
+                }
+            }
+        }
+        lines
+    }
+
+    fn move_by(&mut self, editor: &Editor, amount: isize) {
+        let len = self.lines(editor).len();
+        if len == 0 {
+            return;
+        }
+        let selected = self.state.selected().unwrap_or(len) as isize + amount;
+        self.state
+            .select(Some(selected.clamp(0, len as isize - 1) as usize));
+    }
+
+    /// Expands or collapses the selected value, or only expands or collapses it if `expand` is
+    /// given.
+    fn toggle_selected(&mut self, editor: &mut Editor, expand: Option<bool>) {
+        let lines = self.lines(editor);
+        let (entry, path) = match self
+            .state
+            .selected()
+            .and_then(|selected| lines.into_iter().nth(selected))
+            .and_then(|(_, value_path)| value_path)
+        {
+            Some(value_path) => value_path,
+            None => return,
+        };
+
+        let node = match editor.debug_console.entries.get_mut(entry) {
+            Some(ConsoleEntry::Value(node)) => node.get_mut(&path),
+            _ => None,
+        };
+        let result = match node {
+            Some(node) if expand.map_or(true, |expand| expand != node.expanded) => {
+                node.toggle(editor.debuggers.get_active())
+            }
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            editor.set_error(format!("Failed to fetch variables: {}", err));
+        }
+    }
+
+    fn cycle_filter(&mut self) {
+        let categories = OutputCategory::ALL;
+        self.filter = match self.filter {
+            None => Some(categories[0]),
+            Some(filter) => {
+                let i = categories.iter().position(|c| *c == filter).unwrap();
+                categories.get(i + 1).copied()
+            }
+        };
+        self.state.select(None);
+    }
+
+    /// The area of the output, the lower half of the screen above the prompt.
+    fn output_area(area: Rect) -> Rect {
+        let area = area.clip_bottom(1);
+        area.clip_top(area.height / 2)
+    }
+}
+
+impl Default for DebugConsole {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Adds the lines showing `node` and its expanded children.
+pub(crate) fn push_value(
+    lines: &mut Vec<(TreeItem<'static>, Option<ValuePath>)>,
+    theme: &Theme,
+    node: &VariableNode,
+    (entry, mut path): ValuePath,
+) {
+    let depth = path.len();
+    let content = value_spans(theme, node);
+    let item = if node.is_expandable() {
+        TreeItem::branch(content, depth, node.expanded)
+    } else {
+        TreeItem::leaf(content, depth)
+    };
+    lines.push((item, Some((entry, path.clone()))));
+
+    if let (true, Some(children)) = (node.expanded, &node.children) {
+        for (i, child) in children.iter().enumerate() {
+            path.push(i);
+            push_value(lines, theme, child, (entry, path.clone()));
+            path.pop();
+        }
+    }
+}
+
+/// Formats a value as `name: type = value`, leaving out what the debugger didn't report. Scopes
+/// and ranges of arrays only have a name.
+pub(crate) fn value_spans(theme: &Theme, node: &VariableNode) -> Spans<'static> {
+    let text_style = theme.get("ui.text.focus");
+    let type_style = theme.get("ui.text");
+
+    let mut spans = Vec::with_capacity(5);
+    if !node.name.is_empty() {
+        spans.push(Span::styled(node.name.clone(), text_style));
+        if let Some(ty) = &node.ty {
+            spans.push(Span::raw(": "));
+            spans.push(Span::styled(ty.clone(), type_style));
+        }
+        if node.value.is_empty() {
+            return Spans::from(spans);
+        }
+        spans.push(Span::raw(" = "));
+    }
+    spans.push(Span::styled(node.value.clone(), text_style));
+    Spans::from(spans)
+}
+
+/// Completes the expression with the `completions` request if the debugger supports it.
+fn completions(editor: &Editor, input: &str) -> Vec<Completion> {
+    let debugger = match editor.debuggers.get_active() {
+        Some(debugger)
+            if debugger
+                .caps
+                .as_ref()
+                .and_then(|caps| caps.supports_completions_request)
+                .unwrap_or_default() =>
+        {
+            debugger
+        }
+        _ => return Vec::new(),
+    };
+    let frame_id = debugger.current_stack_frame().map(|frame| frame.id);
+    let column = input.chars().count();
+    let targets = match block_on(debugger.completions(input.to_string(), column, frame_id)) {
+        Ok(targets) => targets,
+        Err(_) => return Vec::new(),
+    };
+
+    // targets without a start replace the word before the cursor
+    let word_start = input
+        .char_indices()
+        .rev()
+        .take_while(|(_, ch)| ch.is_alphanumeric() || *ch == '_')
+        .last()
+        .map_or(input.len(), |(i, _)| i);
+    targets
+        .into_iter()
+        .map(|target| {
+            let start = target.start.map_or(word_start, |start| {
+                // convert from 1-indexed chars to a byte offset
+                input
+                    .char_indices()
+                    .nth(start.saturating_sub(1))
+                    .map_or(input.len(), |(i, _)| i)
+            });
+            (start.., target.text.unwrap_or(target.label).into())
+        })
+        .collect()
+}
+
+impl Component for DebugConsole {
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let output_area = Self::output_area(area);
+        surface.clear_with(output_area, theme.get("ui.background"));
+
+        let title = match self.filter {
+            Some(filter) => format!("Debug console ({})", filter.as_str()),
+            None => "Debug console".to_string(),
+        };
+        let block = Block::default()
+            .title(Span::styled(
+                title,
+                theme.get("ui.text").add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::TOP)
+            .border_style(theme.get("ui.window"));
+        self.height = block.inner(output_area).height as usize;
+
+        // follow the output unless a line is selected
+        if self.state.selected().is_none() {
+            self.state.offset = usize::MAX;
+        }
+        let items: Vec<_> = self
+            .lines(cx.editor)
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect();
+        Tree::new(items)
+            .block(block)
+            .style(theme.get("ui.background").patch(theme.get("ui.text")))
+            .highlight_style(theme.get("ui.menu.selected"))
+            .render_tree(output_area, surface, &mut self.state);
+
+        let prompt_line = area.clip_top(area.height.saturating_sub(1));
+        surface.clear_with(prompt_line, theme.get("ui.background"));
+        self.prompt.render(area, surface, cx);
+    }
+
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key_event = match event {
+            Event::Key(event) => *event,
+            _ => return self.prompt.handle_event(event, cx),
+        };
+
+        match key_event {
+            key!(Esc) | ctrl!('c') => {
+                return EventResult::Consumed(Some(Box::new(
+                    |compositor: &mut Compositor, _cx| {
+                        compositor.remove(Self::ID);
+                    },
+                )));
+            }
+            key!(Enter) if self.prompt.line().is_empty() => self.toggle_selected(cx.editor, None),
+            key!(Enter) => {
+                let expression = self.prompt.line().clone();
+                if cx.editor.registers.last(HISTORY_REGISTER) != Some(&expression) {
+                    cx.editor
+                        .registers
+                        .push(HISTORY_REGISTER, expression.clone());
+                }
+                let editor = &mut *cx.editor;
+                editor
+                    .debug_console
+                    .evaluate(editor.debuggers.get_active(), expression);
+                self.prompt.clear(cx.editor);
+                self.filter = None;
+                self.state.select(None);
+            }
+            alt!('k') => self.move_by(cx.editor, -1),
+            alt!('j') => self.move_by(cx.editor, 1),
+            key!(PageUp) => self.move_by(cx.editor, -(self.height as isize)),
+            key!(PageDown) => self.move_by(cx.editor, self.height as isize),
+            alt!('l') => self.toggle_selected(cx.editor, Some(true)),
+            alt!('h') => self.toggle_selected(cx.editor, Some(false)),
+            alt!('c') => self.cycle_filter(),
+            // back to following the output
+            alt!('g') => self.state.select(None),
+            _ => return self.prompt.handle_event(event, cx),
+        }
+
+        EventResult::Consumed(None)
+    }
+
+    fn cursor(&self, area: Rect, editor: &Editor) -> (Option<Position>, CursorKind) {
+        self.prompt.cursor(area, editor)
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
@@ -4,27 +4,29 @@
     job::{self, Callback},
     key,
     keymap::{KeymapResult, Keymaps},
-    ui::{Completion, ProgressSpinners},
+    ui::{Completion, Explorer, ProgressSpinners},
 };
 
 use helix_core::{
     graphemes::{
         ensure_grapheme_boundary_next_byte, next_grapheme_boundary, prev_grapheme_boundary,
     },
+    line_ending::line_end_char_index,
     movement::Direction,
     syntax::{self, HighlightEvent},
     unicode::width::UnicodeWidthStr,
     visual_coords_at_pos, LineEnding, Position, Range, Selection, Transaction,
 };
 use helix_view::{
-    document::{Mode, SCRATCH_BUFFER_NAME},
+    document::{DiffBase, Mode, SCRATCH_BUFFER_NAME},
     editor::{CompleteAction, CursorShapeConfig},
-    graphics::{Color, CursorKind, Modifier, Rect, Style},
+    graphics::{Color, CursorKind, Modifier, Rect, Style, UnderlineStyle},
     input::{KeyEvent, MouseButton, MouseEvent, MouseEventKind},
     keyboard::{KeyCode, KeyModifiers},
+    terminal::{self, Terminal},
     Document, Editor, Theme, View,
 };
-use std::{borrow::Cow, cmp::min, num::NonZeroUsize, path::PathBuf};
+use std::{borrow::Cow, cmp::min, collections::HashSet, num::NonZeroUsize, path::PathBuf};
 
 use tui::buffer::Buffer as Surface;
 
@@ -37,6 +39,7 @@ pub struct EditorView {
     pseudo_pending: Vec<KeyEvent>,
     last_insert: (commands::MappableCommand, Vec<InsertEvent>),
     pub(crate) completion: Option<Completion>,
+    pub(crate) explorer: Option<Explorer>,
     spinners: ProgressSpinners,
 }
 
@@ -61,6 +64,7 @@ pub fn new(keymaps: Keymaps) -> Self {
             pseudo_pending: Vec::new(),
             last_insert: (commands::MappableCommand::normal_mode, Vec::new()),
             completion: None,
+            explorer: None,
             spinners: ProgressSpinners::default(),
         }
     }
@@ -84,7 +88,7 @@ pub fn render_view(
         let config = editor.config();
 
         // DAP: Highlight current stack frame position
-        let stack_frame = editor.debugger.as_ref().and_then(|debugger| {
+        let stack_frame = editor.debuggers.get_active().and_then(|debugger| {
             if let (Some(frame), Some(thread_id)) = (debugger.active_frame, debugger.thread_id) {
                 debugger
                     .stack_frames
@@ -94,29 +98,30 @@ pub fn render_view(
                 None
             }
         });
-        if let Some(frame) = stack_frame {
-            if doc.path().is_some()
-                && frame
-                    .source
-                    .as_ref()
-                    .and_then(|source| source.path.as_ref())
-                    == doc.path()
-            {
-                let line = frame.line - 1; // convert to 0-indexing
-                if line >= view.offset.row && line < view.offset.row + area.height as usize {
-                    surface.set_style(
-                        Rect::new(
-                            area.x,
-                            area.y + (line - view.offset.row) as u16,
-                            area.width,
-                            1,
-                        ),
-                        theme.get("ui.highlight"),
-                    );
-                }
+        let current_line = match &editor.disassembly {
+            // the current instruction in the disassembly
+            Some(disassembly) if disassembly.doc == doc.id() => disassembly.current_line,
+            _ => stack_frame
+                .filter(|frame| {
+                    doc.path().is_some()
+                        && frame
+                            .source
+                            .as_ref()
+                            .and_then(|source| source.path.as_ref())
+                            == doc.path()
+                })
+                .map(|frame| frame.line - 1), // convert to 0-indexing
+        };
+        if let Some(line) = current_line {
+            if line >= view.offset.row && view.screen_row(line) < area.height as usize {
+                surface.set_style(
+                    Rect::new(area.x, area.y + view.screen_row(line) as u16, area.width, 1),
+                    theme.get("ui.highlight"),
+                );
             }
         }
 
+        Self::highlight_conflicts(doc, view, surface, theme);
         if is_focused && config.cursorline {
             Self::highlight_cursorline(doc, view, surface, theme);
         }
@@ -125,6 +130,10 @@ pub fn render_view(
         }
 
         let mut highlights = Self::doc_syntax_highlights(doc, view.offset, inner.height, theme);
+        let diff_highlights = Self::doc_diff_highlights(editor, doc, view, theme);
+        if !diff_highlights.is_empty() {
+            highlights = Box::new(syntax::merge(highlights, diff_highlights));
+        }
         for diagnostic in Self::doc_diagnostics_highlights(doc, theme) {
             // Most of the `diagnostic` Vecs are empty most of the time. Skipping
             // a merge for any empty Vec saves a significant amount of work.
@@ -148,9 +157,22 @@ pub fn render_view(
             Box::new(highlights)
         };
 
-        Self::render_text_highlights(doc, view.offset, inner, surface, theme, highlights, &config);
+        Self::render_text_highlights(
+            doc,
+            view.offset,
+            &view.filler_rows,
+            inner,
+            surface,
+            theme,
+            highlights,
+            &config,
+        );
+        Self::render_filler_rows(doc, view, inner, surface, theme);
         Self::render_gutter(editor, doc, view, view.area, surface, theme, is_focused);
         Self::render_rulers(editor, doc, view, inner, surface, theme);
+        if editor.show_inline_values {
+            Self::render_inline_values(editor, doc, view, inner, surface, theme);
+        }
 
         if is_focused {
             Self::render_focused_view_elements(view, doc, inner, theme, surface);
@@ -209,6 +231,74 @@ pub fn render_rulers(
             .for_each(|area| surface.set_style(area, ruler_theme))
     }
 
+    /// Shows the values of the variables used on the lines up to the current stack frame after
+    /// the end of those lines.
+    pub fn render_inline_values(
+        editor: &Editor,
+        doc: &Document,
+        view: &View,
+        viewport: Rect,
+        surface: &mut Surface,
+        theme: &Theme,
+    ) {
+        if editor.inline_values.is_empty() {
+            return;
+        }
+        let frame = match editor
+            .debuggers
+            .get_active()
+            .and_then(|debugger| debugger.current_stack_frame())
+        {
+            Some(frame) => frame,
+            None => return,
+        };
+        let frame_path = frame
+            .source
+            .as_ref()
+            .and_then(|source| source.path.as_ref());
+        if frame_path.is_none() || frame_path != doc.path() {
+            return;
+        }
+
+        let style = theme.get("ui.virtual.inline-value");
+        let text = doc.text().slice(..);
+        let last_line = view.last_line(doc).min(frame.line.saturating_sub(1));
+        for line in view.offset.row..=last_line {
+            let content: Cow<str> = text.line(line).into();
+            let mut names: Vec<&str> = Vec::new();
+            for word in content.split(|ch: char| !(ch.is_alphanumeric() || ch == '_')) {
+                if editor.inline_values.contains_key(word) && !names.contains(&word) {
+                    names.push(word);
+                }
+            }
+            if names.is_empty() {
+                continue;
+            }
+
+            let values: Vec<_> = names
+                .iter()
+                .map(|name| {
+                    let value = &editor.inline_values[*name];
+                    format!("{} = {}", name, value.lines().next().unwrap_or_default())
+                })
+                .collect();
+            let line_end = line_end_char_index(&text, line);
+            let width = visual_coords_at_pos(text, line_end, doc.tab_width()).col;
+            // leave some space after the end of the line
+            let x = (width + 2).saturating_sub(view.offset.col);
+            if width + 2 < view.offset.col || x >= viewport.width as usize {
+                continue;
+            }
+            surface.set_stringn(
+                viewport.x + x as u16,
+                viewport.y + view.screen_row(line) as u16,
+                values.join(", "),
+                viewport.width as usize - x,
+                style,
+            );
+        }
+    }
+
     /// Get syntax highlights for a document in a view represented by the first line
     /// and column (`offset`) and the last line. This is done instead of using a view
     /// directly to enable rendering syntax highlighted docs anywhere (eg. picker preview)
@@ -323,6 +413,58 @@ pub fn doc_diagnostics_highlights(
         [default_vec, info_vec, hint_vec, warning_vec, error_vec]
     }
 
+    /// Get highlight spans for the words that changed within modified lines. In side-by-side
+    /// diffs, these are the added words on the side compared to the other one and the removed
+    /// words on the other.
+    pub fn doc_diff_highlights(
+        editor: &Editor,
+        doc: &Document,
+        view: &View,
+        theme: &Theme,
+    ) -> Vec<(usize, std::ops::Range<usize>)> {
+        let (diff_handle, is_new, scope) = match doc.diff_base {
+            DiffBase::Document(base_id, _) if editor.documents.contains_key(&base_id) => {
+                (doc.diff_handle(), true, "diff.plus")
+            }
+            _ => match editor.documents().find(|other| {
+                matches!(other.diff_base, DiffBase::Document(base_id, _) if base_id == doc.id())
+            }) {
+                Some(other) => (other.diff_handle(), false, "diff.minus"),
+                None => (doc.diff_handle(), true, "diff.delta.inline"),
+            },
+        };
+        let (diff_handle, scope) = match (diff_handle, theme.find_scope_index(scope)) {
+            (Some(diff_handle), Some(scope)) => (diff_handle, scope),
+            _ => return Vec::new(),
+        };
+
+        let text = doc.text();
+        let first_line = view.offset.row as u32;
+        let last_line = view.last_line(doc) as u32;
+        let mut spans = Vec::new();
+        for change in diff_handle.inline_changes().iter() {
+            let (lines, ranges) = if is_new {
+                (change.hunk.after, change.added)
+            } else {
+                (change.hunk.before, change.removed)
+            };
+            if lines.end <= first_line {
+                continue;
+            }
+            // the diff may lag behind the latest edits of the document
+            if lines.start > last_line || lines.end as usize > text.len_lines() {
+                break;
+            }
+            let start = text.line_to_char(lines.start as usize);
+            let end = text.line_to_char(lines.end as usize);
+            spans.extend(ranges.into_iter().filter_map(|range| {
+                let range = start + range.start..(start + range.end).min(end);
+                (!range.is_empty()).then(|| (scope, range))
+            }));
+        }
+        spans
+    }
+
     /// Get highlight spans for selections in a document view.
     pub fn doc_selection_highlights(
         mode: Mode,
@@ -354,14 +496,14 @@ pub fn doc_selection_highlights(
         let cursor_scope = match mode {
             Mode::Insert => theme.find_scope_index("ui.cursor.insert"),
             Mode::Select => theme.find_scope_index("ui.cursor.select"),
-            Mode::Normal => theme.find_scope_index("ui.cursor.normal"),
+            Mode::Normal | Mode::Terminal => theme.find_scope_index("ui.cursor.normal"),
         }
         .unwrap_or(base_cursor_scope);
 
         let primary_cursor_scope = match mode {
             Mode::Insert => theme.find_scope_index("ui.cursor.primary.insert"),
             Mode::Select => theme.find_scope_index("ui.cursor.primary.select"),
-            Mode::Normal => theme.find_scope_index("ui.cursor.primary.normal"),
+            Mode::Normal | Mode::Terminal => theme.find_scope_index("ui.cursor.primary.normal"),
         }
         .unwrap_or(base_primary_cursor_scope);
 
@@ -424,9 +566,11 @@ pub fn doc_selection_highlights(
         spans
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
         doc: &Document,
         offset: Position,
+        filler_rows: &[(usize, usize)],
         viewport: Rect,
         surface: &mut Surface,
         theme: &Theme,
@@ -445,6 +589,7 @@ pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
         let mut spans = Vec::new();
         let mut visual_x = 0usize;
         let mut line = 0u16;
+        let mut doc_line = offset.row;
         let tab_width = doc.tab_width();
         let tab = if whitespace.render.tab() == WhitespaceRenderValue::All {
             std::iter::once(characters.tab)
@@ -555,6 +700,13 @@ pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
 
                             visual_x = 0;
                             line += 1;
+                            doc_line += 1;
+                            // leave the filler rows above the next line empty
+                            if let Ok(i) = filler_rows
+                                .binary_search_by_key(&doc_line, |(filler_line, _)| *filler_line)
+                            {
+                                line = line.saturating_add(filler_rows[i].1 as u16);
+                            }
                             is_in_indent_area = true;
 
                             // TODO: with proper iter this shouldn't be necessary
@@ -633,6 +785,30 @@ pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
         }
     }
 
+    /// Fill the rows left empty to align the lines of a side-by-side diff.
+    pub fn render_filler_rows(
+        doc: &Document,
+        view: &View,
+        viewport: Rect,
+        surface: &mut Surface,
+        theme: &Theme,
+    ) {
+        let style = theme
+            .try_get("ui.virtual.filler")
+            .unwrap_or_else(|| theme.get("ui.virtual.whitespace"));
+        let filler = "╱".repeat(viewport.width as usize);
+        let last_line = view.last_line(doc) + 1;
+        for &(line, rows) in &view.filler_rows {
+            if line <= view.offset.row || line > last_line {
+                continue;
+            }
+            let end = view.screen_row(line);
+            for row in end.saturating_sub(rows)..end.min(viewport.height as usize) {
+                surface.set_string(viewport.x, viewport.y + row as u16, &filler, style);
+            }
+        }
+    }
+
     /// Render brace match, etc (meant for the focused view only)
     pub fn render_focused_view_elements(
         view: &View,
@@ -668,6 +844,91 @@ pub fn render_focused_view_elements(
         }
     }
 
+    /// Render the screen of a terminal emulator in place of the document of a view.
+    pub fn render_terminal(
+        &self,
+        editor: &Editor,
+        terminal: &Terminal,
+        view: &View,
+        surface: &mut Surface,
+        is_focused: bool,
+    ) {
+        let theme = &editor.theme;
+        let area = view.area.clip_bottom(1);
+        let base_style = theme.get("ui.background").patch(theme.get("ui.text"));
+        surface.set_style(area, base_style);
+
+        // mark the output as drawn before reading the screen so none of it is missed
+        terminal.take_dirty();
+        let parser = terminal.parser();
+        let screen = parser.screen();
+        let (rows, cols) = screen.size();
+        for row in 0..rows.min(area.height) {
+            for col in 0..cols.min(area.width) {
+                let cell = match screen.cell(row, col) {
+                    Some(cell) if !cell.is_wide_continuation() => cell,
+                    _ => continue,
+                };
+
+                let mut style = base_style;
+                if let Some(fg) = terminal::color(cell.fgcolor()) {
+                    style = style.fg(fg);
+                }
+                if let Some(bg) = terminal::color(cell.bgcolor()) {
+                    style = style.bg(bg);
+                }
+                if cell.bold() {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+                if cell.italic() {
+                    style = style.add_modifier(Modifier::ITALIC);
+                }
+                if cell.underline() {
+                    style = style.underline_style(UnderlineStyle::Line);
+                }
+                if cell.inverse() {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+
+                let contents = cell.contents();
+                let symbol: &str = if contents.is_empty() { " " } else { &contents };
+                surface.set_string(area.x + col, area.y + row, symbol, style);
+            }
+        }
+
+        if is_focused && editor.mode == Mode::Terminal && !screen.hide_cursor() {
+            let (row, col) = screen.cursor_position();
+            if row < area.height && col < area.width {
+                let style = theme
+                    .try_get("ui.cursor.primary")
+                    .unwrap_or_else(|| Style::default().add_modifier(Modifier::REVERSED));
+                surface.set_style(Rect::new(area.x + col, area.y + row, 1, 1), style);
+            }
+        }
+        drop(parser);
+
+        let statusline_area = view.area.clip_top(view.area.height.saturating_sub(1));
+        let title: &str = if terminal.title.is_empty() {
+            "terminal"
+        } else {
+            &terminal.title
+        };
+        let status = if terminal.is_exited() {
+            " [exited]"
+        } else {
+            ""
+        };
+        let doc = editor.document(view.doc).unwrap();
+        let mut context =
+            statusline::RenderContext::new(editor, doc, view, is_focused, &self.spinners);
+        statusline::render_terminal(
+            &mut context,
+            format!("{}{}", title, status),
+            statusline_area,
+            surface,
+        );
+    }
+
     /// Render bufferline at the top
     pub fn render_bufferline(editor: &Editor, viewport: Rect, surface: &mut Surface) {
         let scratch = PathBuf::from(SCRATCH_BUFFER_NAME); // default filename to use for scratch buffer
@@ -754,10 +1015,10 @@ pub fn render_gutter(
             let mut gutter = gutter_type.style(editor, doc, view, theme, is_focused);
             let width = gutter_type.width(view, doc);
             text.reserve(width); // ensure there's enough space for the gutter
-            for (i, line) in (view.offset.row..(last_line + 1)).enumerate() {
+            for line in view.offset.row..(last_line + 1) {
                 let selected = cursors.contains(&line);
                 let x = viewport.x + offset;
-                let y = viewport.y + i as u16;
+                let y = viewport.y + view.screen_row(line) as u16;
 
                 let gutter_style = if selected {
                     gutter_selected_style
@@ -864,7 +1125,7 @@ pub fn highlight_cursorline(doc: &Document, view: &View, surface: &mut Surface,
         for line in view.offset.row..(last_line + 1) {
             let area = Rect::new(
                 view.area.x,
-                view.area.y + (line - view.offset.row) as u16,
+                view.area.y + view.screen_row(line) as u16,
                 view.area.width,
                 1,
             );
@@ -876,6 +1137,47 @@ pub fn highlight_cursorline(doc: &Document, view: &View, surface: &mut Surface,
         }
     }
 
+    /// Apply the highlighting on both sides of the conflicts left by a merge
+    pub fn highlight_conflicts(doc: &Document, view: &View, surface: &mut Surface, theme: &Theme) {
+        if doc.conflicts().is_empty() {
+            return;
+        }
+        let last_line = view.last_line(doc);
+        let marker_style = theme.get("ui.conflict.marker");
+        let ours_style = theme.get("ui.conflict.ours");
+        let theirs_style = theme.get("ui.conflict.theirs");
+        let base_style = theme.get("ui.conflict.base");
+
+        for conflict in doc.conflicts() {
+            if conflict.lines.end <= view.offset.row {
+                continue;
+            }
+            if conflict.lines.start > last_line {
+                break;
+            }
+            for line in
+                conflict.lines.start.max(view.offset.row)..conflict.lines.end.min(last_line + 1)
+            {
+                let style = if conflict.ours.contains(&line) {
+                    ours_style
+                } else if conflict.theirs.contains(&line) {
+                    theirs_style
+                } else if matches!(&conflict.base, Some(base) if base.contains(&line)) {
+                    base_style
+                } else {
+                    marker_style
+                };
+                let area = Rect::new(
+                    view.area.x,
+                    view.area.y + view.screen_row(line) as u16,
+                    view.area.width,
+                    1,
+                );
+                surface.set_style(area, style);
+            }
+        }
+    }
+
     /// Apply the highlighting on the columns where a cursor is active
     pub fn highlight_cursorcolumn(
         doc: &Document,
@@ -1013,6 +1315,29 @@ fn insert_mode(&mut self, cx: &mut commands::Context, event: KeyEvent) {
         }
     }
 
+    fn terminal_mode(&mut self, cx: &mut commands::Context, event: KeyEvent) {
+        if let Some(keyresult) = self.handle_keymap_event(Mode::Terminal, cx, event) {
+            let keys = match keyresult {
+                KeymapResult::NotFound => vec![event],
+                KeymapResult::Cancelled(pending) => pending,
+                _ => unreachable!(),
+            };
+
+            let view_id = view!(cx.editor).id;
+            let terminal = match cx.editor.terminals.get_mut(&view_id) {
+                Some(terminal) => terminal,
+                None => {
+                    cx.editor.enter_normal_mode();
+                    return;
+                }
+            };
+            if let Err(err) = keys.into_iter().try_for_each(|key| terminal.send_key(key)) {
+                cx.editor
+                    .set_error(format!("Failed to write to the terminal: {}", err));
+            }
+        }
+    }
+
     fn command_mode(&mut self, mode: Mode, cxt: &mut commands::Context, event: KeyEvent) {
         match (event, cxt.editor.count) {
             // count handling
@@ -1191,7 +1516,7 @@ fn handle_mouse_event(
                         None => return EventResult::Ignored(None),
                     };
 
-                    let line = coords.row + view.offset.row;
+                    let line = view.line_at_screen_row(coords.row);
                     if line < doc.text().len_lines() {
                         commands::dap_toggle_breakpoint_impl(cxt, path, line);
                         return EventResult::Consumed(None);
@@ -1268,7 +1593,7 @@ fn handle_mouse_event(
                     cxt.editor.focus(view_id);
 
                     let (view, doc) = current!(cxt.editor);
-                    let line = coords.row + view.offset.row;
+                    let line = view.line_at_screen_row(coords.row);
                     if let Ok(pos) = doc.text().try_line_to_char(line) {
                         doc.set_selection(view_id, Selection::point(pos));
                         if modifiers == KeyModifiers::ALT {
@@ -1320,6 +1645,13 @@ fn handle_event(
         event: &Event,
         context: &mut crate::compositor::Context,
     ) -> EventResult {
+        if let Some(explorer) = self.explorer.as_mut() {
+            if explorer.is_focused() && matches!(event, Event::Key(_)) {
+                context.editor.reset_idle_timer();
+                return explorer.handle_event(event, context);
+            }
+        }
+
         let mut cx = commands::Context {
             editor: context.editor,
             count: None,
@@ -1330,6 +1662,16 @@ fn handle_event(
         };
 
         match event {
+            Event::Paste(contents) if cx.editor.mode == Mode::Terminal => {
+                let view_id = view!(cx.editor).id;
+                if let Some(terminal) = cx.editor.terminals.get_mut(&view_id) {
+                    if let Err(err) = terminal.paste(contents) {
+                        cx.editor
+                            .set_error(format!("Failed to write to the terminal: {}", err));
+                    }
+                }
+                EventResult::Consumed(None)
+            }
             Event::Paste(contents) => {
                 cx.count = cx.editor.count;
                 commands::paste_bracketed_value(&mut cx, contents.clone());
@@ -1411,6 +1753,7 @@ fn handle_event(
                                 }
                             }
                         }
+                        Mode::Terminal => self.terminal_mode(&mut cx, key),
                         mode => self.command_mode(mode, &mut cx, key),
                     }
                 }
@@ -1451,7 +1794,15 @@ fn handle_event(
 
             Event::Mouse(event) => self.handle_mouse_event(event, &mut cx),
             Event::IdleTimeout => self.handle_idle_timeout(&mut cx),
-            Event::FocusGained => EventResult::Ignored(None),
+            Event::FocusGained => {
+                // the repository may have changed while the editor was in the background
+                let doc_ids: HashSet<_> =
+                    cx.editor.tree.views().map(|(view, _)| view.doc).collect();
+                for doc_id in doc_ids {
+                    cx.editor.refresh_repo_status(doc_id);
+                }
+                EventResult::Ignored(None)
+            }
             Event::FocusLost => {
                 if context.editor.config().auto_save {
                     if let Err(e) = commands::typed::write_all_impl(context, false, false) {
@@ -1466,6 +1817,7 @@ fn handle_event(
     fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         // clear with background color
         surface.set_style(area, cx.editor.theme.get("ui.background"));
+        cx.editor.sync_diff_buffers();
         let config = cx.editor.config();
 
         // check if bufferline should be rendered
@@ -1482,6 +1834,13 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
             editor_area = editor_area.clip_top(1);
         }
 
+        if let Some(explorer) = self.explorer.as_mut() {
+            explorer.follow(cx.editor);
+            let width = Explorer::WIDTH.min(editor_area.width);
+            explorer.render(editor_area.with_width(width), surface, cx);
+            editor_area = editor_area.clip_left(width);
+        }
+
         // if the terminal size suddenly changed, we need to trigger a resize
         cx.editor.resize(editor_area);
 
@@ -1489,7 +1848,19 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
             Self::render_bufferline(cx.editor, area.with_height(1), surface);
         }
 
+        // keep the size of terminals in sync with their views, minus the statusline
+        for (view, _) in cx.editor.tree.views() {
+            if let Some(terminal) = cx.editor.terminals.get_mut(&view.id) {
+                let area = view.area.clip_bottom(1);
+                terminal.resize(area.height, area.width);
+            }
+        }
+
         for (view, is_focused) in cx.editor.tree.views() {
+            if let Some(terminal) = cx.editor.terminals.get(&view.id) {
+                self.render_terminal(cx.editor, terminal, view, surface, is_focused);
+                continue;
+            }
             let doc = cx.editor.document(view.doc).unwrap();
             self.render_view(cx.editor, doc, view, area, surface, is_focused);
         }
@@ -1566,6 +1937,9 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
     }
 
     fn cursor(&self, _area: Rect, editor: &Editor) -> (Option<Position>, CursorKind) {
+        if matches!(&self.explorer, Some(explorer) if explorer.is_focused()) {
+            return (None, CursorKind::Hidden);
+        }
         match editor.cursor() {
             // All block cursors are drawn manually
             (pos, CursorKind::Block) => (pos, CursorKind::Hidden),
@@ -0,0 +1,649 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use helix_view::{
+    editor::{Action, FilePickerConfig},
+    graphics::{Modifier, Rect},
+    Editor,
+};
+use ignore::WalkBuilder;
+use tui::{
+    buffer::Buffer as Surface,
+    text::Span,
+    widgets::{Block, Borders, Tree, TreeItem, TreeState},
+};
+
+use crate::{
+    compositor::{Compositor, Context, Event, EventResult},
+    job::Callback,
+    key, shift,
+    ui::{self, EditorView, Prompt, PromptEvent},
+};
+
+/// A file or directory listed in the explorer.
+#[derive(Debug)]
+struct Entry {
+    path: PathBuf,
+    /// Number of directories between the entry and the root of the explorer.
+    depth: usize,
+    is_dir: bool,
+    expanded: bool,
+}
+
+impl Entry {
+    fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// A sidebar listing the files of the workspace as a tree. It is rendered and given
+/// input by the [`EditorView`] while focused.
+pub struct Explorer {
+    root: PathBuf,
+    /// The entries that are currently visible, in depth-first order.
+    entries: Vec<Entry>,
+    state: TreeState,
+    focused: bool,
+    /// Path of the document that was last revealed in the tree.
+    followed: Option<PathBuf>,
+    /// Number of entries that fit in the explorer when it was last rendered.
+    height: usize,
+}
+
+impl Explorer {
+    pub const WIDTH: u16 = 30;
+
+    pub fn new(root: PathBuf, config: &FilePickerConfig) -> Self {
+        let root = helix_core::path::get_canonicalized_path(&root).unwrap_or(root);
+        let entries = read_dir(&root, 0, config);
+        let mut state = TreeState::default();
+        state.select((!entries.is_empty()).then(|| 0));
+        Self {
+            root,
+            entries,
+            state,
+            focused: true,
+            followed: None,
+            height: 0,
+        }
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn focus(&mut self) {
+        self.focused = true;
+    }
+
+    pub fn unfocus(&mut self) {
+        self.focused = false;
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.state
+            .selected()
+            .and_then(|index| self.entries.get(index))
+    }
+
+    /// The directory new entries are created in: the selected directory, or the
+    /// parent of the selected file.
+    fn selected_dir(&self) -> PathBuf {
+        match self.selected() {
+            Some(entry) if entry.is_dir => entry.path.clone(),
+            Some(entry) => entry
+                .path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| self.root.clone()),
+            None => self.root.clone(),
+        }
+    }
+
+    fn move_by(&mut self, amount: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let selected = self.state.selected().unwrap_or(0) as isize + amount;
+        let selected = selected.clamp(0, self.entries.len() as isize - 1);
+        self.state.select(Some(selected as usize));
+    }
+
+    fn expand(&mut self, index: usize, config: &FilePickerConfig) {
+        let entry = &mut self.entries[index];
+        if !entry.is_dir || entry.expanded {
+            return;
+        }
+        entry.expanded = true;
+        let children = read_dir(&entry.path, entry.depth + 1, config);
+        self.entries.splice(index + 1..index + 1, children);
+    }
+
+    fn collapse(&mut self, index: usize) {
+        let entry = &mut self.entries[index];
+        if !entry.expanded {
+            return;
+        }
+        entry.expanded = false;
+        let depth = entry.depth;
+        let end = self.entries[index + 1..]
+            .iter()
+            .position(|entry| entry.depth <= depth)
+            .map_or(self.entries.len(), |offset| index + 1 + offset);
+        self.entries.drain(index + 1..end);
+    }
+
+    /// Reads the tree again from disk, keeping the expanded directories and the selection.
+    pub fn refresh(&mut self, config: &FilePickerConfig) {
+        let expanded: HashSet<PathBuf> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.expanded)
+            .map(|entry| entry.path.clone())
+            .collect();
+        let selected = self.selected().map(|entry| entry.path.clone());
+
+        self.entries = read_dir(&self.root, 0, config);
+        // Entries are inserted after the current one when it is expanded, so
+        // this also visits the children of expanded directories.
+        let mut index = 0;
+        while index < self.entries.len() {
+            if expanded.contains(&self.entries[index].path) {
+                self.expand(index, config);
+            }
+            index += 1;
+        }
+
+        let index = selected
+            .and_then(|selected| self.entries.iter().position(|entry| entry.path == selected))
+            .or_else(|| {
+                let last = self.entries.len().checked_sub(1)?;
+                Some(self.state.selected().unwrap_or(0).min(last))
+            });
+        self.state.select(index);
+    }
+
+    /// Expands the ancestors of `path` and selects it.
+    pub fn reveal(&mut self, path: &Path, config: &FilePickerConfig) {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative,
+            Err(_) => return,
+        };
+
+        let mut current = self.root.clone();
+        let mut index = None;
+        for component in relative.components() {
+            current.push(component);
+            let position = match self.entries.iter().position(|entry| entry.path == current) {
+                Some(position) => position,
+                // the file is ignored or not yet on disk
+                None => break,
+            };
+            if current != path {
+                self.expand(position, config);
+            }
+            index = Some(position);
+        }
+
+        if index.is_some() {
+            self.state.select(index);
+        }
+    }
+
+    /// Reveals the document in the focused view if it changed since the last call.
+    pub fn follow(&mut self, editor: &Editor) {
+        let path = doc!(editor).path();
+        if path.is_none() || path == self.followed.as_ref() {
+            return;
+        }
+        self.followed = path.cloned();
+        if let Some(path) = path {
+            self.reveal(path, &editor.config().file_picker);
+        }
+    }
+
+    pub fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let text_style = theme.get("ui.text");
+        let highlight_style = if self.focused {
+            theme.get("ui.menu.selected")
+        } else {
+            theme.get("ui.text.focus")
+        };
+
+        let title = self
+            .root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.root.to_string_lossy().into_owned());
+        let block = Block::default()
+            .title(Span::styled(title, text_style.add_modifier(Modifier::BOLD)))
+            .borders(Borders::RIGHT)
+            .border_style(theme.get("ui.window"));
+        self.height = block.inner(area).height as usize;
+
+        let items: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                if entry.is_dir {
+                    TreeItem::branch(entry.name(), entry.depth, entry.expanded)
+                        .style(text_style.add_modifier(Modifier::BOLD))
+                } else {
+                    TreeItem::leaf(entry.name(), entry.depth)
+                }
+            })
+            .collect();
+
+        Tree::new(items)
+            .block(block)
+            .style(theme.get("ui.background").patch(text_style))
+            .highlight_style(highlight_style)
+            .render_tree(area, surface, &mut self.state);
+    }
+
+    pub fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key_event = match event {
+            Event::Key(event) => *event,
+            _ => return EventResult::Ignored(None),
+        };
+        let config = cx.editor.config();
+        let config = &config.file_picker;
+
+        match key_event {
+            key!('j') | key!(Down) => self.move_by(1),
+            key!('k') | key!(Up) => self.move_by(-1),
+            key!(PageDown) => self.move_by(self.height as isize),
+            key!(PageUp) => self.move_by(-(self.height as isize)),
+            key!(Home) => self.move_by(-(self.entries.len() as isize)),
+            key!(End) => self.move_by(self.entries.len() as isize),
+            key!('l') | key!(Right) => {
+                if let Some(index) = self.state.selected() {
+                    self.expand(index, config);
+                }
+            }
+            key!('h') | key!(Left) => {
+                let index = match self.state.selected() {
+                    Some(index) => index,
+                    None => return EventResult::Consumed(None),
+                };
+                let entry = &self.entries[index];
+                if entry.expanded {
+                    self.collapse(index);
+                } else if let Some(parent) = self.entries[..index]
+                    .iter()
+                    .rposition(|parent| parent.depth < entry.depth)
+                {
+                    self.state.select(Some(parent));
+                }
+            }
+            key!(Enter) => {
+                let index = match self.state.selected() {
+                    Some(index) => index,
+                    None => return EventResult::Consumed(None),
+                };
+                let entry = &self.entries[index];
+                if !entry.is_dir {
+                    if let Err(err) = cx.editor.open(&entry.path, Action::Replace) {
+                        cx.editor.set_error(format!(
+                            "Failed to open file '{}': {}",
+                            entry.path.display(),
+                            err
+                        ));
+                    } else {
+                        self.unfocus();
+                    }
+                } else if entry.expanded {
+                    self.collapse(index);
+                } else {
+                    self.expand(index, config);
+                }
+            }
+            key!('a') => return self.create_prompt(cx.editor),
+            key!('r') => return self.rename_prompt(cx.editor),
+            key!('c') => return self.copy_prompt(cx.editor),
+            key!('d') => return self.delete_prompt(),
+            key!('R') | shift!('R') => self.refresh(config),
+            key!(Esc) => self.unfocus(),
+            key!('q') => {
+                return EventResult::Consumed(Some(Box::new(|compositor: &mut Compositor, _| {
+                    if let Some(editor_view) = compositor.find::<EditorView>() {
+                        editor_view.explorer = None;
+                    }
+                })))
+            }
+            _ => (),
+        }
+
+        EventResult::Consumed(None)
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn create_prompt(&self, editor: &Editor) -> EventResult {
+        let mut input = self.relative(&self.selected_dir());
+        if !input.is_empty() {
+            input.push(std::path::MAIN_SEPARATOR);
+        }
+        let root = self.root.clone();
+        let prompt = Prompt::new(
+            "create (end with / for a directory):".into(),
+            None,
+            ui::completers::none,
+            move |cx: &mut Context, input: &str, event: PromptEvent| {
+                if event != PromptEvent::Validate || input.is_empty() {
+                    return;
+                }
+                let path = root.join(input);
+                let result = if input.ends_with('/') || input.ends_with(std::path::MAIN_SEPARATOR) {
+                    fs::create_dir_all(&path)
+                } else {
+                    create_file(&path)
+                };
+                match result {
+                    Ok(()) => refresh_explorer(cx, Some(path)),
+                    Err(err) => cx.editor.set_error(format!(
+                        "Failed to create '{}': {}",
+                        path.display(),
+                        err
+                    )),
+                }
+            },
+        )
+        .with_line(input, editor);
+        push_prompt(prompt)
+    }
+
+    fn rename_prompt(&self, editor: &Editor) -> EventResult {
+        let source = match self.selected() {
+            Some(entry) => entry.path.clone(),
+            None => return EventResult::Consumed(None),
+        };
+        let input = self.relative(&source);
+        let root = self.root.clone();
+        let prompt = Prompt::new(
+            "rename to:".into(),
+            None,
+            ui::completers::none,
+            move |cx: &mut Context, input: &str, event: PromptEvent| {
+                if event != PromptEvent::Validate || input.is_empty() {
+                    return;
+                }
+                let target = root.join(input);
+                let result = target
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::rename(&source, &target));
+                match result {
+                    Ok(()) => {
+                        // keep documents that were open pointing to the renamed file, or to the
+                        // files of the renamed directory
+                        for doc in cx.editor.documents_mut() {
+                            let path = match doc.path().and_then(|p| p.strip_prefix(&source).ok()) {
+                                Some(relative) => target.join(relative),
+                                None => continue,
+                            };
+                            if let Err(err) = doc.set_path(Some(path.as_path())) {
+                                log::error!("Failed to update the path of a renamed file: {}", err);
+                            }
+                        }
+                        refresh_explorer(cx, Some(target));
+                    }
+                    Err(err) => cx.editor.set_error(format!(
+                        "Failed to rename '{}': {}",
+                        source.display(),
+                        err
+                    )),
+                }
+            },
+        )
+        .with_line(input, editor);
+        push_prompt(prompt)
+    }
+
+    fn copy_prompt(&self, editor: &Editor) -> EventResult {
+        let source = match self.selected() {
+            Some(entry) => entry.path.clone(),
+            None => return EventResult::Consumed(None),
+        };
+        let input = self.relative(&source);
+        let root = self.root.clone();
+        let prompt = Prompt::new(
+            "copy to:".into(),
+            None,
+            ui::completers::none,
+            move |cx: &mut Context, input: &str, event: PromptEvent| {
+                if event != PromptEvent::Validate || input.is_empty() {
+                    return;
+                }
+                let target = root.join(input);
+                copy(cx, &source, target, false);
+            },
+        )
+        .with_line(input, editor);
+        push_prompt(prompt)
+    }
+
+    fn delete_prompt(&self) -> EventResult {
+        let path = match self.selected() {
+            Some(entry) => entry.path.clone(),
+            None => return EventResult::Consumed(None),
+        };
+        let prompt = Prompt::new(
+            format!("delete '{}'? (y/n):", self.relative(&path)).into(),
+            None,
+            ui::completers::none,
+            move |cx: &mut Context, input: &str, event: PromptEvent| {
+                if event != PromptEvent::Validate || input != "y" {
+                    return;
+                }
+                let result = if path.is_dir() {
+                    fs::remove_dir_all(&path)
+                } else {
+                    fs::remove_file(&path)
+                };
+                match result {
+                    Ok(()) => refresh_explorer(cx, None),
+                    Err(err) => cx.editor.set_error(format!(
+                        "Failed to delete '{}': {}",
+                        path.display(),
+                        err
+                    )),
+                }
+            },
+        );
+        push_prompt(prompt)
+    }
+}
+
+/// Lists the children of `dir`, directories first, honouring the ignore settings of the
+/// file picker.
+fn read_dir(dir: &Path, depth: usize, config: &FilePickerConfig) -> Vec<Entry> {
+    let mut entries: Vec<_> = WalkBuilder::new(dir)
+        .hidden(config.hidden)
+        .parents(config.parents)
+        .ignore(config.ignore)
+        .follow_links(config.follow_symlinks)
+        .git_ignore(config.git_ignore)
+        .git_global(config.git_global)
+        .git_exclude(config.git_exclude)
+        .max_depth(Some(1))
+        // We always want to ignore the .git directory, otherwise if
+        // `ignore` is turned off above, we end up with a lot of noise
+        // in our explorer.
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.depth() == 1)
+        .map(|entry| Entry {
+            is_dir: entry.file_type().map_or(false, |ft| ft.is_dir()),
+            path: entry.into_path(),
+            depth,
+            expanded: false,
+        })
+        .collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.path.cmp(&b.path)));
+    entries
+}
+
+fn create_file(path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map(|_| ())
+}
+
+/// Copies `source` to `target`, asking whether to overwrite `target` if it exists.
+fn copy(cx: &mut Context, source: &Path, target: PathBuf, overwrite: bool) {
+    match copy_all(source, &target, overwrite) {
+        Ok(()) => refresh_explorer(cx, Some(target)),
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            let source = source.to_path_buf();
+            let callback = async move {
+                let call: Callback =
+                    Callback::EditorCompositor(Box::new(move |_editor, compositor| {
+                        let prompt = Prompt::new(
+                            format!("'{}' exists, overwrite? (y/n):", target.display()).into(),
+                            None,
+                            ui::completers::none,
+                            move |cx: &mut Context, input: &str, event: PromptEvent| {
+                                if event == PromptEvent::Validate && input == "y" {
+                                    copy(cx, &source, target.clone(), true);
+                                }
+                            },
+                        );
+                        compositor.push(Box::new(prompt))
+                    }));
+                Ok(call)
+            };
+            cx.jobs.callback(callback);
+        }
+        Err(err) => cx
+            .editor
+            .set_error(format!("Failed to copy '{}': {}", source.display(), err)),
+    }
+}
+
+/// Copies a file, or a directory with all of its contents. Fails with
+/// [`io::ErrorKind::AlreadyExists`] if `target` exists, unless `overwrite` is set.
+fn copy_all(source: &Path, target: &Path, overwrite: bool) -> io::Result<()> {
+    let source = helix_core::path::get_normalized_path(source);
+    let target = helix_core::path::get_normalized_path(target);
+    // copying a directory into itself would copy the copy again
+    if target.starts_with(&source) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the target is the source or inside of it",
+        ));
+    }
+    if !overwrite && target.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "the target already exists",
+        ));
+    }
+    copy_recursive(&source, &target)
+}
+
+fn copy_recursive(source: &Path, target: &Path) -> io::Result<()> {
+    if !source.is_dir() {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        return fs::copy(source, target).map(|_| ());
+    }
+
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        copy_recursive(&entry.path(), &target.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+fn push_prompt(prompt: Prompt) -> EventResult {
+    EventResult::Consumed(Some(Box::new(|compositor: &mut Compositor, _| {
+        compositor.push(Box::new(prompt))
+    })))
+}
+
+/// Reads the explorer's tree from disk again after a file operation, selecting `path`.
+fn refresh_explorer(cx: &mut Context, path: Option<PathBuf>) {
+    let callback = async move {
+        let call: Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                let explorer = compositor
+                    .find::<EditorView>()
+                    .and_then(|editor_view| editor_view.explorer.as_mut());
+                if let Some(explorer) = explorer {
+                    let config = editor.config();
+                    explorer.refresh(&config.file_picker);
+                    if let Some(path) = path {
+                        explorer.reveal(&path, &config.file_picker);
+                    }
+                }
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copy_onto_itself_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        fs::write(&file, "foo").unwrap();
+
+        let err = copy_all(&file, &file, true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "foo");
+    }
+
+    #[test]
+    fn copy_into_own_subtree_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("file.txt"), "foo").unwrap();
+
+        let err = copy_all(&src, &src.join("sub"), false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!src.join("sub").exists());
+    }
+
+    #[test]
+    fn copy_onto_existing_file_needs_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.txt");
+        let target = dir.path().join("target.txt");
+        fs::write(&source, "foo").unwrap();
+        fs::write(&target, "bar").unwrap();
+
+        let err = copy_all(&source, &target, false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "bar");
+
+        copy_all(&source, &target, true).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "foo");
+    }
+}
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, path::PathBuf};
+use std::{borrow::Cow, collections::HashMap, path::PathBuf};
 
 use crate::{
     compositor::{Callback, Component, Compositor, Context, Event, EventResult},
@@ -11,6 +11,7 @@
 use fuzzy_matcher::skim::SkimMatcherV2 as Matcher;
 use fuzzy_matcher::FuzzyMatcher;
 
+use helix_vcs::FileStatus;
 use helix_view::{graphics::Rect, Editor};
 use tui::layout::Constraint;
 
@@ -32,14 +33,27 @@ fn filter_text(&self, data: &Self::Data) -> Cow<str> {
 }
 
 impl Item for PathBuf {
-    /// Root prefix to strip.
-    type Data = PathBuf;
+    /// Root prefix to strip, and the version control status of changed files by their path
+    /// relative to the root.
+    type Data = (PathBuf, HashMap<PathBuf, FileStatus>);
+
+    fn format(&self, (root_path, statuses): &Self::Data) -> Row {
+        let path = self.strip_prefix(root_path).unwrap_or(self);
+        if statuses.is_empty() {
+            return path.to_string_lossy().into();
+        }
+        let marker = statuses.get(path).map_or(' ', FileStatus::marker);
+        format!("{} {}", marker, path.display()).into()
+    }
+
+    fn sort_text(&self, data: &Self::Data) -> Cow<str> {
+        self.filter_text(data)
+    }
 
-    fn format(&self, root_path: &Self::Data) -> Row {
+    fn filter_text(&self, (root_path, _): &Self::Data) -> Cow<str> {
         self.strip_prefix(root_path)
             .unwrap_or(self)
             .to_string_lossy()
-            .into()
     }
 }
 
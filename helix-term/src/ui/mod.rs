@@ -1,5 +1,8 @@
+mod breakpoint;
 mod completion;
+mod debug_console;
 pub(crate) mod editor;
+mod explorer;
 mod fuzzy_match;
 mod info;
 pub mod lsp;
@@ -9,27 +12,38 @@
 mod picker;
 pub mod popup;
 mod prompt;
+mod replace;
 mod spinner;
 mod statusline;
 mod text;
+mod variables;
+mod watches;
 
 use crate::compositor::{Component, Compositor};
 use crate::job::{self, Callback};
+pub use breakpoint::{breakpoint_picker, BreakpointItem};
 pub use completion::Completion;
+pub use debug_console::DebugConsole;
 pub use editor::EditorView;
+pub use explorer::Explorer;
 pub use markdown::Markdown;
 pub use menu::Menu;
 pub use picker::{DynamicPicker, FileLocation, FilePicker, Picker};
 pub use popup::Popup;
 pub use prompt::{Prompt, PromptEvent};
+pub use replace::{replace_picker, ReplaceMatch};
 pub use spinner::{ProgressSpinners, Spinner};
 pub use text::Text;
+pub use variables::Variables;
+pub use watches::Watches;
 
 use helix_core::regex::Regex;
 use helix_core::regex::RegexBuilder;
+use helix_vcs::{FileChange, FileStatus};
 use helix_view::Editor;
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 pub fn prompt(
     cx: &mut crate::commands::Context,
@@ -156,11 +170,51 @@ pub fn regex_prompt(
     cx.push_layer(Box::new(prompt));
 }
 
-pub fn file_picker(root: PathBuf, config: &helix_view::editor::Config) -> FilePicker<PathBuf> {
+/// Computes the version control status of the files under `root` in the background, then
+/// pushes a file picker on `root` marking the changed ones.
+pub fn open_file_picker(jobs: &mut job::Jobs, editor: &Editor, root: PathBuf) {
+    let providers = editor.diff_providers.clone();
+    let callback = async move {
+        let statuses = tokio::task::spawn_blocking({
+            let root = root.clone();
+            move || {
+                // The status of changed files, by path relative to the root
+                let canonical_root = match std::fs::canonicalize(&root) {
+                    Ok(canonical_root) => canonical_root,
+                    Err(_) => return HashMap::new(),
+                };
+                providers
+                    .changed_files(&canonical_root)
+                    .into_iter()
+                    .filter_map(|change| {
+                        let path = change.path.strip_prefix(&canonical_root).ok()?;
+                        Some((path.to_owned(), change.status))
+                    })
+                    .collect()
+            }
+        })
+        .await?;
+        let call = Callback::EditorCompositor(Box::new(move |editor, compositor| {
+            let picker = file_picker(root, statuses, editor);
+            compositor.push(Box::new(overlay::overlayed(picker)));
+        }));
+        Ok(call)
+    };
+    jobs.callback(callback);
+}
+
+/// A picker of the files under `root`, marked with their version control `statuses`, by path
+/// relative to the root.
+pub fn file_picker(
+    root: PathBuf,
+    statuses: HashMap<PathBuf, FileStatus>,
+    editor: &Editor,
+) -> FilePicker<PathBuf> {
     use ignore::{types::TypesBuilder, WalkBuilder};
     use std::time::Instant;
 
     let now = Instant::now();
+    let config = editor.config();
 
     let mut walk_builder = WalkBuilder::new(&root);
     walk_builder
@@ -220,7 +274,7 @@ pub fn file_picker(root: PathBuf, config: &helix_view::editor::Config) -> FilePi
 
     FilePicker::new(
         files,
-        root,
+        (root, statuses),
         move |cx, path: &PathBuf, action| {
             if let Err(e) = cx.editor.open(path, action) {
                 let err = if let Some(err) = e.source() {
@@ -235,6 +289,64 @@ pub fn file_picker(root: PathBuf, config: &helix_view::editor::Config) -> FilePi
     )
 }
 
+impl menu::Item for FileChange {
+    /// Root prefix to strip.
+    type Data = PathBuf;
+
+    fn format(&self, root_path: &Self::Data) -> menu::Row {
+        let path = self.path.strip_prefix(root_path).unwrap_or(&self.path);
+        format!("{} {}", self.status.marker(), path.display()).into()
+    }
+
+    fn sort_text(&self, root_path: &Self::Data) -> std::borrow::Cow<str> {
+        self.filter_text(root_path)
+    }
+
+    fn filter_text(&self, root_path: &Self::Data) -> std::borrow::Cow<str> {
+        self.path
+            .strip_prefix(root_path)
+            .unwrap_or(&self.path)
+            .to_string_lossy()
+    }
+}
+
+/// A picker of files that differ from the last commit, previewing their changes. Paths are
+/// shown relative to `root`.
+pub fn changed_file_picker(changes: Vec<FileChange>, root: PathBuf) -> FilePicker<FileChange> {
+    FilePicker::new(
+        changes,
+        root,
+        move |cx, change: &FileChange, action| {
+            if let Err(e) = cx.editor.open(&change.path, action) {
+                cx.editor.set_error(format!(
+                    "unable to open \"{}\": {}",
+                    change.path.display(),
+                    e
+                ));
+            }
+        },
+        |_editor, change| Some((change.path.clone().into(), None)),
+    )
+    .with_diff_preview(|editor, change| file_changes(editor, &change.path))
+}
+
+/// The changes to the file at `path` since the last commit, as a unified diff.
+fn file_changes(editor: &Editor, path: &Path) -> Option<String> {
+    let before = editor
+        .diff_providers
+        .get_diff_base(path)
+        .unwrap_or_default();
+    let after = std::fs::read(path).unwrap_or_default();
+    let is_binary = |bytes: &[u8]| content_inspector::inspect(bytes).is_binary();
+    if is_binary(&before) || is_binary(&after) {
+        return Some("Binary files differ\n".to_string());
+    }
+    Some(helix_vcs::file_patch(
+        &String::from_utf8_lossy(&before),
+        &String::from_utf8_lossy(&after),
+    ))
+}
+
 pub mod completers {
     use crate::ui::prompt::Completion;
     use fuzzy_matcher::skim::SkimMatcherV2 as Matcher;
@@ -19,10 +19,11 @@
 use std::{collections::HashMap, io::Read, path::PathBuf};
 
 use crate::ui::{Prompt, PromptEvent};
-use helix_core::{movement::Direction, unicode::segmentation::UnicodeSegmentation, Position};
+use helix_core::{movement::Direction, unicode::segmentation::UnicodeSegmentation, Position, Rope};
 use helix_view::{
     editor::Action,
     graphics::{CursorKind, Margin, Modifier, Rect},
+    input::KeyEvent,
     theme::Style,
     Document, DocumentId, Editor,
 };
@@ -72,8 +73,17 @@ pub struct FilePicker<T: Item> {
     read_buffer: Vec<u8>,
     /// Given an item in the picker, return the file path and line number to display.
     file_fn: Box<dyn Fn(&Editor, &T) -> Option<FileLocation>>,
+    /// Given an item in the picker, return a diff to display instead of its file.
+    diff_fn: Option<DiffFn<T>>,
+    /// Caches diffs by the index of their item
+    diff_cache: HashMap<usize, CachedPreview>,
+    /// Handles keys before the picker does, for actions on the options.
+    key_fn: Option<KeyFn<T>>,
 }
 
+type DiffFn<T> = Box<dyn Fn(&Editor, &T) -> Option<String>>;
+type KeyFn<T> = Box<dyn Fn(&mut Picker<T>, KeyEvent, &mut Context) -> Option<EventResult>>;
+
 pub enum CachedPreview {
     Document(Box<Document>),
     Binary,
@@ -128,15 +138,45 @@ pub fn new(
             preview_cache: HashMap::new(),
             read_buffer: Vec::with_capacity(1024),
             file_fn: Box::new(preview_fn),
+            diff_fn: None,
+            diff_cache: HashMap::new(),
+            key_fn: None,
         }
     }
 
+    /// Previews the changes of the selected item, as given by `diff_fn`, instead of its file.
+    pub fn with_diff_preview(
+        mut self,
+        diff_fn: impl Fn(&Editor, &T) -> Option<String> + 'static,
+    ) -> Self {
+        self.diff_fn = Some(Box::new(diff_fn));
+        self
+    }
+
+    /// Lets `key_fn` handle keys before the picker, e.g. to toggle the selected option.
+    /// Keys it returns `None` for are handled by the picker as usual.
+    pub fn with_key_handler(
+        mut self,
+        key_fn: impl Fn(&mut Picker<T>, KeyEvent, &mut Context) -> Option<EventResult> + 'static,
+    ) -> Self {
+        self.key_fn = Some(Box::new(key_fn));
+        self
+    }
+
     pub fn truncate_start(mut self, truncate_start: bool) -> Self {
         self.truncate_start = truncate_start;
         self.picker.truncate_start = truncate_start;
         self
     }
 
+    pub fn picker(&self) -> &Picker<T> {
+        &self.picker
+    }
+
+    pub fn picker_mut(&mut self) -> &mut Picker<T> {
+        &mut self.picker
+    }
+
     fn current_file(&self, editor: &Editor) -> Option<FileLocation> {
         self.picker
             .selection()
@@ -144,13 +184,36 @@ fn current_file(&self, editor: &Editor) -> Option<FileLocation> {
             .and_then(|(path_or_id, line)| path_or_id.get_canonicalized().ok().zip(Some(line)))
     }
 
+    /// Get (cached) diff preview for the item at `index`.
+    fn get_diff_preview(&mut self, index: usize, editor: &Editor) -> Preview<'_, 'static> {
+        let item = &self.picker.options[index];
+        let diff_fn = &self.diff_fn;
+        let preview = self.diff_cache.entry(index).or_insert_with(|| {
+            match diff_fn.as_ref().and_then(|diff_fn| diff_fn(editor, item)) {
+                Some(diff) => {
+                    let mut doc = Document::from(Rope::from(diff), None);
+                    let _ = doc.set_language_by_language_id("diff", editor.syn_loader.clone());
+                    CachedPreview::Document(Box::new(doc))
+                }
+                None => CachedPreview::NotFound,
+            }
+        });
+        Preview::Cached(preview)
+    }
+
     /// Get (cached) preview for a given path. If a document corresponding
-    /// to the path is already open in the editor, it is used instead.
+    /// to the path is already open in the editor, it is used instead, unless
+    /// the picker previews diffs.
     fn get_preview<'picker, 'editor>(
         &'picker mut self,
         path_or_id: PathOrId,
         editor: &'editor Editor,
     ) -> Preview<'picker, 'editor> {
+        if self.diff_fn.is_some() {
+            if let Some(index) = self.picker.selection_index() {
+                return self.get_diff_preview(index, editor);
+            }
+        }
         match path_or_id {
             PathOrId::Path(path) => {
                 let path = &path;
@@ -296,6 +359,7 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
             EditorView::render_text_highlights(
                 doc,
                 offset,
+                &[],
                 inner,
                 surface,
                 &cx.editor.theme,
@@ -327,6 +391,11 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
         if let Event::IdleTimeout = event {
             return self.handle_idle_timeout(ctx);
         }
+        if let (Event::Key(key_event), Some(key_fn)) = (event, &self.key_fn) {
+            if let Some(result) = key_fn(&mut self.picker, *key_event, ctx) {
+                return result;
+            }
+        }
         // TODO: keybinds for scrolling preview
         self.picker.handle_event(event, ctx)
     }
@@ -577,6 +646,39 @@ pub fn selection(&self) -> Option<&T> {
             .map(|pmatch| &self.options[pmatch.index])
     }
 
+    /// The index of the selected item in the options.
+    fn selection_index(&self) -> Option<usize> {
+        self.matches.get(self.cursor).map(|pmatch| pmatch.index)
+    }
+
+    pub fn selection_mut(&mut self) -> Option<&mut T> {
+        self.matches
+            .get(self.cursor)
+            .map(|pmatch| &mut self.options[pmatch.index])
+    }
+
+    /// All options of the picker, including the ones filtered out by the current query.
+    pub fn options(&self) -> &[T] {
+        &self.options
+    }
+
+    pub fn options_mut(&mut self) -> &mut [T] {
+        &mut self.options
+    }
+
+    /// Removes the selected option, the cursor moves to the option that followed it.
+    pub fn remove_selection(&mut self) -> Option<T> {
+        let index = self.matches.get(self.cursor)?.index;
+        self.matches.remove(self.cursor);
+        for pmatch in &mut self.matches {
+            if pmatch.index > index {
+                pmatch.index -= 1;
+            }
+        }
+        self.cursor = self.cursor.min(self.matches.len().saturating_sub(1));
+        Some(self.options.remove(index))
+    }
+
     pub fn toggle_preview(&mut self) {
         self.show_preview = !self.show_preview;
     }
@@ -0,0 +1,112 @@
+use std::{borrow::Cow, ops::Range, path::PathBuf};
+
+use helix_core::movement::Direction;
+use helix_view::{graphics::Modifier, theme::Style, Editor};
+use tui::{
+    text::{Span, Spans},
+    widgets::{Cell, Row},
+};
+
+use crate::{
+    alt,
+    compositor::{Compositor, EventResult},
+    ctrl, key,
+    ui::{menu::Item, FilePicker},
+};
+
+/// A single occurrence of the search pattern that can be replaced.
+#[derive(Debug, Clone)]
+pub struct ReplaceMatch {
+    pub path: PathBuf,
+    /// 0 indexed line
+    pub line_num: usize,
+    /// Contents of the line the match was found on at the time of the search.
+    pub line: String,
+    /// Byte range of the match within `line`.
+    pub range: Range<usize>,
+    /// The text to replace the match with, with capture groups already expanded.
+    pub replacement: String,
+    /// Whether the replacement should be applied.
+    pub enabled: bool,
+}
+
+impl ReplaceMatch {
+    fn location(&self) -> String {
+        let relative_path = helix_core::path::get_relative_path(&self.path);
+        format!("{}:{}", relative_path.display(), self.line_num + 1)
+    }
+}
+
+impl Item for ReplaceMatch {
+    type Data = ();
+
+    fn format(&self, _data: &Self::Data) -> Row {
+        let marker = if self.enabled { "[x]" } else { "[ ]" };
+        let before = self.line[..self.range.start].trim_start();
+        let after = self.line[self.range.end..].trim_end();
+        let preview = Spans::from(vec![
+            Span::raw(before),
+            Span::styled(
+                &self.line[self.range.clone()],
+                Style::default().add_modifier(Modifier::CROSSED_OUT),
+            ),
+            Span::styled(
+                self.replacement.as_str(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(after),
+        ]);
+
+        Row::new(vec![
+            Cell::from(marker),
+            Cell::from(self.location()),
+            Cell::from(preview),
+        ])
+    }
+
+    fn filter_text(&self, _data: &Self::Data) -> Cow<str> {
+        format!("{} {}", self.location(), self.line.trim()).into()
+    }
+}
+
+/// Previews the replacements of a project-wide search and replace. Individual
+/// matches can be toggled with `C-space` (`A-a` toggles all of them) and `Enter`
+/// applies the enabled replacements.
+pub fn replace_picker(
+    file_picker: FilePicker<ReplaceMatch>,
+    apply_fn: impl Fn(&mut Editor, Vec<ReplaceMatch>) + 'static,
+) -> FilePicker<ReplaceMatch> {
+    file_picker.with_key_handler(move |picker, key_event, cx| {
+        match key_event {
+            ctrl!(' ') => {
+                if let Some(replace_match) = picker.selection_mut() {
+                    replace_match.enabled = !replace_match.enabled;
+                }
+                picker.move_by(1, Direction::Forward);
+            }
+            alt!('a') => {
+                let enable = picker.options().iter().any(|m| !m.enabled);
+                for replace_match in picker.options_mut() {
+                    replace_match.enabled = enable;
+                }
+            }
+            key!(Enter) => {
+                let matches = picker
+                    .options()
+                    .iter()
+                    .filter(|m| m.enabled)
+                    .cloned()
+                    .collect();
+                apply_fn(cx.editor, matches);
+                return Some(EventResult::Consumed(Some(Box::new(
+                    |compositor: &mut Compositor, _cx| {
+                        compositor.pop();
+                    },
+                ))));
+            }
+            _ => return None,
+        }
+
+        Some(EventResult::Consumed(None))
+    })
+}
@@ -1,5 +1,6 @@
 use helix_core::{coords_at_pos, encoding, Position};
 use helix_lsp::lsp::DiagnosticSeverity;
+use helix_vcs::RepoHead;
 use helix_view::{
     document::{Mode, SCRATCH_BUFFER_NAME},
     graphics::Rect,
@@ -48,12 +49,16 @@ pub struct RenderBuffer<'a> {
     pub right: Spans<'a>,
 }
 
-pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface) {
-    let base_style = if context.focused {
+fn base_style(context: &RenderContext) -> Style {
+    if context.focused {
         context.editor.theme.get("ui.statusline")
     } else {
         context.editor.theme.get("ui.statusline.inactive")
-    };
+    }
+}
+
+pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface) {
+    let base_style = base_style(context);
 
     surface.set_style(viewport.with_height(1), base_style);
 
@@ -125,6 +130,25 @@ pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface
     );
 }
 
+/// Renders the status line of a terminal view, which shows the mode and `title` instead of the
+/// configured elements.
+pub fn render_terminal(
+    context: &mut RenderContext,
+    title: String,
+    viewport: Rect,
+    surface: &mut Surface,
+) {
+    let base_style = base_style(context);
+    surface.set_style(viewport.with_height(1), base_style);
+
+    render_mode(context, |context: &mut RenderContext, text, style| {
+        append(&mut context.parts.left, text, &base_style, style)
+    });
+    append(&mut context.parts.left, title, &base_style, None);
+
+    surface.set_spans(viewport.x, viewport.y, &context.parts.left, viewport.width);
+}
+
 fn append(buffer: &mut Spans, text: String, base_style: &Style, style: Option<Style>) {
     buffer.0.push(Span::styled(
         text,
@@ -155,6 +179,7 @@ fn get_render_function<F>(element_id: StatusLineElementID) -> impl Fn(&mut Rende
         helix_view::editor::StatusLineElement::TotalLineNumbers => render_total_line_numbers,
         helix_view::editor::StatusLineElement::Separator => render_separator,
         helix_view::editor::StatusLineElement::Spacer => render_spacer,
+        helix_view::editor::StatusLineElement::VersionControl => render_version_control,
     }
 }
 
@@ -174,6 +199,7 @@ fn render_mode<F>(context: &mut RenderContext, write: F)
                     Mode::Insert => &modenames.insert,
                     Mode::Select => &modenames.select,
                     Mode::Normal => &modenames.normal,
+                    Mode::Terminal => &modenames.terminal,
                 }
             } else {
                 // If not focused, explicitly leave an empty space instead of returning None.
@@ -185,6 +211,7 @@ fn render_mode<F>(context: &mut RenderContext, write: F)
                 Mode::Insert => Some(context.editor.theme.get("ui.statusline.insert")),
                 Mode::Select => Some(context.editor.theme.get("ui.statusline.select")),
                 Mode::Normal => Some(context.editor.theme.get("ui.statusline.normal")),
+                Mode::Terminal => Some(context.editor.theme.get("ui.statusline.terminal")),
             }
         } else {
             None
@@ -466,3 +493,38 @@ fn render_spacer<F>(context: &mut RenderContext, write: F)
 {
     write(context, String::from(" "), None);
 }
+
+fn render_version_control<F>(context: &mut RenderContext, write: F)
+where
+    F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
+{
+    let status = match context.doc.repo_status() {
+        Some(status) => status,
+        None => return,
+    };
+
+    let mut text = match &status.head {
+        RepoHead::Branch(branch) => format!(" {}", branch),
+        RepoHead::Detached(id) => format!(" ({})", id),
+    };
+    if let Some(operation) = status.operation {
+        text.push('|');
+        text.push_str(operation.label());
+    }
+    if status.ahead > 0 {
+        text.push_str(&format!(" ↑{}", status.ahead));
+    }
+    if status.behind > 0 {
+        text.push_str(&format!(" ↓{}", status.behind));
+    }
+    let hunks = context
+        .doc
+        .diff_handle()
+        .map_or(0, |diff_handle| diff_handle.hunks().len());
+    if hunks > 0 {
+        text.push_str(&format!(" ~{}", hunks));
+    }
+    text.push(' ');
+
+    write(context, text, None);
+}
@@ -0,0 +1,271 @@
+use helix_core::Position;
+use helix_lsp::block_on;
+use helix_view::{
+    debug_console::VariableNode,
+    editor::DataBreakpoint,
+    graphics::{CursorKind, Modifier, Rect},
+    handlers::dap::data_breakpoints_changed,
+    Editor,
+};
+use tui::{
+    buffer::Buffer as Surface,
+    text::Span,
+    widgets::{Block, Borders, Tree, TreeItem, TreeState},
+};
+
+use super::debug_console::{push_value, ValuePath};
+use crate::{
+    compositor::{Component, Compositor, Context, Event, EventResult},
+    key,
+    ui::{self, Prompt, PromptEvent},
+};
+
+/// Shows the scopes of the current stack frame as a tree on the right side of the screen. The
+/// children of a value are fetched once it is expanded, and values can be changed in place or
+/// watched with data breakpoints if the debugger supports it.
+#[derive(Default)]
+pub struct Variables {
+    state: TreeState,
+    /// Number of lines shown, known once rendered.
+    height: usize,
+}
+
+impl Variables {
+    pub const ID: &'static str = "dap-variables";
+    /// Minimum width of the panel, it takes a third of the screen otherwise.
+    const MIN_WIDTH: u16 = 30;
+
+    fn lines(editor: &Editor) -> Vec<(TreeItem<'static>, Option<ValuePath>)> {
+        let mut lines = Vec::new();
+        for (i, scope) in editor.variables.iter().enumerate() {
+            push_value(&mut lines, &editor.theme, scope, (i, Vec::new()));
+        }
+        lines
+    }
+
+    fn selected(&self, editor: &Editor) -> Option<ValuePath> {
+        let selected = self.state.selected()?;
+        Self::lines(editor).into_iter().nth(selected)?.1
+    }
+
+    fn move_by(&mut self, editor: &Editor, amount: isize) {
+        let len = Self::lines(editor).len();
+        if len == 0 {
+            return;
+        }
+        let selected = self.state.selected().unwrap_or(0) as isize + amount;
+        self.state
+            .select(Some(selected.clamp(0, len as isize - 1) as usize));
+    }
+
+    /// Expands or collapses the selected value, or only expands or collapses it if `expand` is
+    /// given.
+    fn toggle_selected(&mut self, editor: &mut Editor, expand: Option<bool>) {
+        let (scope, path) = match self.selected(editor) {
+            Some(value_path) => value_path,
+            None => return,
+        };
+        let node = editor
+            .variables
+            .get_mut(scope)
+            .and_then(|node| node.get_mut(&path));
+        let result = match node {
+            Some(node) if expand.map_or(true, |expand| expand != node.expanded) => {
+                node.toggle(editor.debuggers.get_active())
+            }
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            editor.set_error(format!("Failed to fetch variables: {}", err));
+        }
+    }
+
+    /// Prompts for a new value of the variable at `(scope, path)`.
+    fn prompt(editor: &Editor, (scope, path): ValuePath) -> EventResult {
+        let value = match editor.variables.get(scope).and_then(|node| node.get(&path)) {
+            Some(node) => node.value.clone(),
+            None => return EventResult::Consumed(None),
+        };
+        let prompt = Prompt::new(
+            "set value:".into(),
+            None,
+            ui::completers::none,
+            move |cx: &mut Context, input: &str, event: PromptEvent| {
+                if event != PromptEvent::Validate {
+                    return;
+                }
+                if let Err(err) = set_value(cx.editor, scope, &path, input.to_string()) {
+                    cx.editor
+                        .set_error(format!("Failed to set the value: {}", err));
+                }
+            },
+        )
+        .with_line(value, editor);
+        EventResult::Consumed(Some(Box::new(|compositor: &mut Compositor, _| {
+            compositor.push(Box::new(prompt))
+        })))
+    }
+}
+
+/// Sets the value of the variable at `path` in `scope`, then fetches the values again as others
+/// may depend on it.
+fn set_value(
+    editor: &mut Editor,
+    scope: usize,
+    path: &[usize],
+    value: String,
+) -> anyhow::Result<()> {
+    let debugger = editor
+        .debuggers
+        .get_active()
+        .ok_or_else(|| anyhow::anyhow!("No debug session running"))?;
+    let root = editor
+        .variables
+        .get_mut(scope)
+        .ok_or_else(|| anyhow::anyhow!("The variable no longer exists"))?;
+    let container = container(root, path);
+    let node = root
+        .get_mut(path)
+        .ok_or_else(|| anyhow::anyhow!("The variable no longer exists"))?;
+    block_on(node.set_value(debugger, container, value))?;
+    block_on(editor.refresh_debug_values());
+    Ok(())
+}
+
+/// Adds or removes a data breakpoint on the variable at `path` in `scope`, returning a status
+/// message describing the change.
+fn toggle_data_breakpoint(
+    editor: &mut Editor,
+    scope: usize,
+    path: &[usize],
+) -> anyhow::Result<String> {
+    let debugger = editor
+        .debuggers
+        .get_active_mut()
+        .ok_or_else(|| anyhow::anyhow!("No debug session running"))?;
+    if !debugger
+        .capabilities()
+        .supports_data_breakpoints
+        .unwrap_or_default()
+    {
+        anyhow::bail!("The debugger does not support data breakpoints");
+    }
+    let root = editor
+        .variables
+        .get(scope)
+        .ok_or_else(|| anyhow::anyhow!("The variable no longer exists"))?;
+    let node = root
+        .get(path)
+        .ok_or_else(|| anyhow::anyhow!("The variable no longer exists"))?;
+    if path.is_empty() || node.range.is_some() {
+        anyhow::bail!("Data breakpoints can only be set on variables");
+    }
+
+    let info = block_on(debugger.data_breakpoint_info(container(root, path), node.name.clone()))?;
+    // the description explains why no breakpoint can be set
+    let data_id = info
+        .data_id
+        .ok_or_else(|| anyhow::anyhow!("{}", info.description))?;
+    let breakpoints = editor.data_breakpoints.entry(debugger.id()).or_default();
+    let status = match breakpoints.iter().position(|b| b.data_id == data_id) {
+        Some(i) => {
+            breakpoints.remove(i);
+            format!("Removed the data breakpoint on {}", info.description)
+        }
+        None => {
+            breakpoints.push(DataBreakpoint {
+                data_id,
+                description: info.description.clone(),
+                verified: false,
+            });
+            format!("Added a data breakpoint on {}", info.description)
+        }
+    };
+    data_breakpoints_changed(debugger, breakpoints)?;
+    Ok(status)
+}
+
+/// The variables reference of the parent of the variable at `path`, which variables are set
+/// through.
+fn container(root: &VariableNode, path: &[usize]) -> Option<usize> {
+    let (_, parent) = path.split_last()?;
+    root.get(parent).map(|parent| parent.variables_reference)
+}
+
+impl Component for Variables {
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let width = (area.width / 3).max(Self::MIN_WIDTH).min(area.width);
+        let area = area.clip_bottom(1).clip_left(area.width - width);
+        surface.clear_with(area, theme.get("ui.background"));
+
+        let block = Block::default()
+            .title(Span::styled(
+                "Variables",
+                theme.get("ui.text").add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::LEFT)
+            .border_style(theme.get("ui.window"));
+        self.height = block.inner(area).height as usize;
+        let items: Vec<_> = Self::lines(cx.editor)
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect();
+        if self.state.selected().is_none() && !items.is_empty() {
+            self.state.select(Some(0));
+        }
+        Tree::new(items)
+            .block(block)
+            .style(theme.get("ui.background").patch(theme.get("ui.text")))
+            .highlight_style(theme.get("ui.menu.selected"))
+            .render_tree(area, surface, &mut self.state);
+    }
+
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key_event = match event {
+            Event::Key(event) => *event,
+            _ => return EventResult::Ignored(None),
+        };
+
+        match key_event {
+            key!(Esc) | key!('q') => {
+                return EventResult::Consumed(Some(Box::new(
+                    |compositor: &mut Compositor, _cx| {
+                        compositor.remove(Self::ID);
+                    },
+                )));
+            }
+            key!('j') | key!(Down) => self.move_by(cx.editor, 1),
+            key!('k') | key!(Up) => self.move_by(cx.editor, -1),
+            key!(PageDown) => self.move_by(cx.editor, self.height as isize),
+            key!(PageUp) => self.move_by(cx.editor, -(self.height as isize)),
+            key!('l') | key!(Right) => self.toggle_selected(cx.editor, Some(true)),
+            key!('h') | key!(Left) => self.toggle_selected(cx.editor, Some(false)),
+            key!(Enter) => self.toggle_selected(cx.editor, None),
+            key!('e') => {
+                if let Some(value_path) = self.selected(cx.editor) {
+                    return Self::prompt(cx.editor, value_path);
+                }
+            }
+            key!('b') => {
+                if let Some((scope, path)) = self.selected(cx.editor) {
+                    match toggle_data_breakpoint(cx.editor, scope, &path) {
+                        Ok(status) => cx.editor.set_status(status),
+                        Err(err) => cx.editor.set_error(err.to_string()),
+                    }
+                }
+            }
+            _ => (),
+        }
+
+        EventResult::Consumed(None)
+    }
+
+    fn cursor(&self, _area: Rect, _editor: &Editor) -> (Option<Position>, CursorKind) {
+        (None, CursorKind::Hidden)
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
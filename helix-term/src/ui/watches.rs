@@ -0,0 +1,199 @@
+use helix_core::Position;
+use helix_view::{
+    graphics::{CursorKind, Modifier, Rect},
+    Editor,
+};
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Tree, TreeItem, TreeState},
+};
+
+use super::debug_console::{push_value, ValuePath};
+use crate::{
+    compositor::{Component, Compositor, Context, Event, EventResult},
+    key, shift,
+    ui::{self, Prompt, PromptEvent},
+};
+
+/// Lists the watched expressions with their values in the current stack frame, on the right
+/// side of the screen.
+#[derive(Default)]
+pub struct Watches {
+    state: TreeState,
+}
+
+impl Watches {
+    pub const ID: &'static str = "dap-watches";
+    /// Minimum width of the panel, it takes a third of the screen otherwise.
+    const MIN_WIDTH: u16 = 30;
+
+    fn lines(editor: &Editor) -> Vec<(TreeItem<'static>, Option<ValuePath>)> {
+        let theme = &editor.theme;
+        let mut lines = Vec::new();
+        for (i, watch) in editor.watches.iter().enumerate() {
+            match &watch.value {
+                Some(Ok(node)) => push_value(&mut lines, theme, node, (i, Vec::new())),
+                Some(Err(err)) => {
+                    let content = Spans::from(vec![
+                        Span::styled(watch.expression.clone(), theme.get("ui.text.focus")),
+                        Span::raw(": "),
+                        Span::styled(err.clone(), theme.get("error")),
+                    ]);
+                    lines.push((TreeItem::leaf(content, 0), Some((i, Vec::new()))));
+                }
+                None => {
+                    let content =
+                        Span::styled(watch.expression.clone(), theme.get("ui.text.inactive"));
+                    lines.push((TreeItem::leaf(content, 0), Some((i, Vec::new()))));
+                }
+            }
+        }
+        lines
+    }
+
+    fn selected(&self, editor: &Editor) -> Option<ValuePath> {
+        let selected = self.state.selected()?;
+        Self::lines(editor).into_iter().nth(selected)?.1
+    }
+
+    fn move_by(&mut self, editor: &Editor, amount: isize) {
+        let len = Self::lines(editor).len();
+        if len == 0 {
+            return;
+        }
+        let selected = self.state.selected().unwrap_or(0) as isize + amount;
+        self.state
+            .select(Some(selected.clamp(0, len as isize - 1) as usize));
+    }
+
+    /// Expands or collapses the selected value, or only expands or collapses it if `expand` is
+    /// given.
+    fn toggle_selected(&mut self, editor: &mut Editor, expand: Option<bool>) {
+        let (watch, path) = match self.selected(editor) {
+            Some(value_path) => value_path,
+            None => return,
+        };
+        let node = match editor.watches.get_mut(watch).and_then(|w| w.value.as_mut()) {
+            Some(Ok(node)) => node.get_mut(&path),
+            _ => None,
+        };
+        let result = match node {
+            Some(node) if expand.map_or(true, |expand| expand != node.expanded) => {
+                node.toggle(editor.debuggers.get_active())
+            }
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            editor.set_error(format!("Failed to fetch variables: {}", err));
+        }
+    }
+
+    /// Prompts for an expression to watch, replacing the watch at `index` if given.
+    fn prompt(editor: &Editor, index: Option<usize>) -> EventResult {
+        let line = index
+            .and_then(|index| editor.watches.get(index))
+            .map(|watch| watch.expression.clone())
+            .unwrap_or_default();
+        let prompt = Prompt::new(
+            "watch:".into(),
+            None,
+            ui::completers::none,
+            move |cx: &mut Context, input: &str, event: PromptEvent| {
+                if event != PromptEvent::Validate || input.is_empty() {
+                    return;
+                }
+                cx.editor.add_watch(input.to_string());
+                if let Some(index) = index {
+                    // move the new watch in place of the edited one
+                    let watch = cx.editor.watches.pop().unwrap();
+                    if let Some(old) = cx.editor.watches.get_mut(index) {
+                        *old = watch;
+                    }
+                }
+            },
+        )
+        .with_line(line, editor);
+        EventResult::Consumed(Some(Box::new(|compositor: &mut Compositor, _| {
+            compositor.push(Box::new(prompt))
+        })))
+    }
+}
+
+impl Component for Watches {
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let width = (area.width / 3).max(Self::MIN_WIDTH).min(area.width);
+        let area = area.clip_bottom(1).clip_left(area.width - width);
+        surface.clear_with(area, theme.get("ui.background"));
+
+        let block = Block::default()
+            .title(Span::styled(
+                "Watches",
+                theme.get("ui.text").add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::LEFT)
+            .border_style(theme.get("ui.window"));
+        let items: Vec<_> = Self::lines(cx.editor)
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect();
+        if self.state.selected().is_none() && !items.is_empty() {
+            self.state.select(Some(0));
+        }
+        Tree::new(items)
+            .block(block)
+            .style(theme.get("ui.background").patch(theme.get("ui.text")))
+            .highlight_style(theme.get("ui.menu.selected"))
+            .render_tree(area, surface, &mut self.state);
+    }
+
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key_event = match event {
+            Event::Key(event) => *event,
+            _ => return EventResult::Ignored(None),
+        };
+
+        match key_event {
+            key!(Esc) | key!('q') => {
+                return EventResult::Consumed(Some(Box::new(
+                    |compositor: &mut Compositor, _cx| {
+                        compositor.remove(Self::ID);
+                    },
+                )));
+            }
+            key!('j') | key!(Down) => self.move_by(cx.editor, 1),
+            key!('k') | key!(Up) => self.move_by(cx.editor, -1),
+            key!('l') | key!(Right) => self.toggle_selected(cx.editor, Some(true)),
+            key!('h') | key!(Left) => self.toggle_selected(cx.editor, Some(false)),
+            key!(Enter) => self.toggle_selected(cx.editor, None),
+            key!('a') => return Self::prompt(cx.editor, None),
+            key!('e') => {
+                if let Some((index, _)) = self.selected(cx.editor) {
+                    return Self::prompt(cx.editor, Some(index));
+                }
+            }
+            key!('d') => {
+                if let Some((index, _)) = self.selected(cx.editor) {
+                    cx.editor.watches.remove(index);
+                    self.state.select(None);
+                }
+            }
+            key!('D') | shift!('D') => {
+                cx.editor.watches.clear();
+                self.state.select(None);
+            }
+            _ => (),
+        }
+
+        EventResult::Consumed(None)
+    }
+
+    fn cursor(&self, _area: Rect, _editor: &Editor) -> (Option<Position>, CursorKind) {
+        (None, CursorKind::Hidden)
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
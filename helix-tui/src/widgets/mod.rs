@@ -8,17 +8,20 @@
 // //! - [`List`]
 // //! - [`Table`]
 //! - [`Paragraph`]
+//! - [`Tree`]
 
 mod block;
 // mod list;
 mod paragraph;
 mod reflow;
 mod table;
+mod tree;
 
 pub use self::block::{Block, BorderType};
 // pub use self::list::{List, ListItem, ListState};
 pub use self::paragraph::{Paragraph, Wrap};
 pub use self::table::{Cell, Row, Table, TableState};
+pub use self::tree::{Tree, TreeItem, TreeState};
 
 use crate::buffer::Buffer;
 use bitflags::bitflags;
@@ -0,0 +1,229 @@
+use crate::{
+    buffer::Buffer,
+    text::Spans,
+    widgets::{Block, Widget},
+};
+use helix_view::graphics::{Rect, Style};
+
+/// Width of a single level of indentation.
+const INDENT_WIDTH: usize = 2;
+
+/// An item of a [`Tree`], displayed `depth` levels of indentation below the root.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeItem<'a> {
+    content: Spans<'a>,
+    depth: usize,
+    /// `None` for leaves, whether the children are shown for branches.
+    expanded: Option<bool>,
+    style: Style,
+}
+
+impl<'a> TreeItem<'a> {
+    pub fn leaf<T>(content: T, depth: usize) -> TreeItem<'a>
+    where
+        T: Into<Spans<'a>>,
+    {
+        TreeItem {
+            content: content.into(),
+            depth,
+            expanded: None,
+            style: Style::default(),
+        }
+    }
+
+    pub fn branch<T>(content: T, depth: usize, expanded: bool) -> TreeItem<'a>
+    where
+        T: Into<Spans<'a>>,
+    {
+        TreeItem {
+            content: content.into(),
+            depth,
+            expanded: Some(expanded),
+            style: Style::default(),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> TreeItem<'a> {
+        self.style = style;
+        self
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct TreeState {
+    pub offset: usize,
+    pub selected: Option<usize>,
+}
+
+impl TreeState {
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected = index;
+        if index.is_none() {
+            self.offset = 0;
+        }
+    }
+}
+
+/// A widget to display a hierarchy of items, one per line, among which one can be selected.
+///
+/// The tree is given as the flattened list of its visible items in depth-first order, so
+/// collapsed branches simply leave out their children.
+///
+/// # Examples
+///
+/// ```
+/// # use helix_tui::widgets::{Block, Borders, Tree, TreeItem};
+/// # use helix_view::graphics::{Style, Modifier};
+/// let items = vec![
+///     TreeItem::branch("src", 0, true),
+///     TreeItem::leaf("main.rs", 1),
+///     TreeItem::branch("tests", 0, false),
+/// ];
+/// Tree::new(items)
+///     .block(Block::default().title("Files").borders(Borders::ALL))
+///     .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tree<'a> {
+    block: Option<Block<'a>>,
+    items: Vec<TreeItem<'a>>,
+    /// Style used as a base style for the widget
+    style: Style,
+    /// Style used to render selected item
+    highlight_style: Style,
+}
+
+impl<'a> Tree<'a> {
+    pub fn new<T>(items: T) -> Tree<'a>
+    where
+        T: Into<Vec<TreeItem<'a>>>,
+    {
+        Tree {
+            block: None,
+            items: items.into(),
+            style: Style::default(),
+            highlight_style: Style::default(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Tree<'a> {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Tree<'a> {
+        self.style = style;
+        self
+    }
+
+    pub fn highlight_style(mut self, style: Style) -> Tree<'a> {
+        self.highlight_style = style;
+        self
+    }
+
+    pub fn render_tree(mut self, area: Rect, buf: &mut Buffer, state: &mut TreeState) {
+        if area.area() == 0 {
+            return;
+        }
+        buf.set_style(area, self.style);
+        let tree_area = match self.block.take() {
+            Some(b) => {
+                let inner_area = b.inner(area);
+                b.render(area, buf);
+                inner_area
+            }
+            None => area,
+        };
+
+        if tree_area.area() == 0 || self.items.is_empty() {
+            return;
+        }
+
+        // scroll the selected item into view
+        let height = tree_area.height as usize;
+        state.offset = state.offset.min(self.items.len().saturating_sub(height));
+        if let Some(selected) = state.selected {
+            let selected = selected.min(self.items.len() - 1);
+            if selected < state.offset {
+                state.offset = selected;
+            } else if selected >= state.offset + height {
+                state.offset = selected + 1 - height;
+            }
+        }
+
+        for (i, item) in self
+            .items
+            .iter()
+            .enumerate()
+            .skip(state.offset)
+            .take(height)
+        {
+            let y = tree_area.top() + (i - state.offset) as u16;
+            let area = Rect::new(tree_area.left(), y, tree_area.width, 1);
+            let item_style = self.style.patch(item.style);
+            buf.set_style(area, item_style);
+
+            let symbol = match item.expanded {
+                Some(true) => "▾ ",
+                Some(false) => "▸ ",
+                None => "  ",
+            };
+            let prefix = format!("{}{}", " ".repeat(item.depth * INDENT_WIDTH), symbol);
+            let (x, _) =
+                buf.set_stringn(area.left(), y, prefix, tree_area.width as usize, item_style);
+            buf.set_spans(x, y, &item.content, area.right().saturating_sub(x));
+
+            if state.selected == Some(i) {
+                buf.set_style(area, self.highlight_style);
+            }
+        }
+    }
+}
+
+impl<'a> Widget for Tree<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut state = TreeState::default();
+        Tree::render_tree(self, area, buf, &mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_renders_indentation_and_markers() {
+        let items = vec![
+            TreeItem::branch("src", 0, true),
+            TreeItem::leaf("lib.rs", 1),
+            TreeItem::branch("docs", 0, false),
+        ];
+        let mut buf = Buffer::empty(Rect::new(0, 0, 12, 3));
+        Tree::new(items).render(buf.area, &mut buf);
+        assert_eq!(
+            buf,
+            Buffer::with_lines(vec!["▾ src       ", "    lib.rs  ", "▸ docs      "])
+        );
+    }
+
+    #[test]
+    fn tree_scrolls_selected_item_into_view() {
+        let items: Vec<_> = (0..5).map(|i| TreeItem::leaf(i.to_string(), 0)).collect();
+        let mut state = TreeState::default();
+        state.select(Some(4));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 2));
+        Tree::new(items).render_tree(buf.area, &mut buf, &mut state);
+        assert_eq!(state.offset, 3);
+        assert_eq!(buf, Buffer::with_lines(vec!["  3", "  4"]));
+
+        state.select(Some(0));
+        let items: Vec<_> = (0..5).map(|i| TreeItem::leaf(i.to_string(), 0)).collect();
+        Tree::new(items).render_tree(buf.area, &mut buf, &mut state);
+        assert_eq!(state.offset, 0);
+        assert_eq!(buf, Buffer::with_lines(vec!["  0", "  1"]));
+    }
+}
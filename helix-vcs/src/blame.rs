@@ -0,0 +1,138 @@
+use std::ops::Range;
+
+use imara_diff::intern::InternedInput;
+
+use crate::diff::FileHunks;
+
+/// A commit that changed a file, like the one that last changed a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameCommit {
+    /// The full id of the commit, in hexadecimal.
+    pub id: String,
+    pub author: String,
+    /// Seconds since the unix epoch at which the commit was authored.
+    pub time: i64,
+    /// Offset in seconds of the timezone of the author from UTC.
+    pub offset: i32,
+    /// The first line of the commit message.
+    pub summary: String,
+}
+
+impl BlameCommit {
+    /// The date the commit was authored, as `YYYY-MM-DD` in the timezone of the author.
+    pub fn date(&self) -> String {
+        let days = (self.time + self.offset as i64).div_euclid(24 * 60 * 60);
+        let (year, month, day) = civil_from_days(days);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    /// The abbreviated id of the commit.
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(8)]
+    }
+}
+
+/// The commits that last changed each line of a file, as of its diff base.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Blame {
+    pub commits: Vec<BlameCommit>,
+    /// The index into `commits` of the commit for each line of the diff base.
+    pub lines: Vec<usize>,
+}
+
+impl Blame {
+    /// Returns the commit that last changed the `line` of the diff base.
+    pub fn base_line_commit(&self, line: u32) -> Option<&BlameCommit> {
+        let commit = *self.lines.get(line as usize)?;
+        self.commits.get(commit)
+    }
+
+    /// Returns the commit that last changed the `line` of the document, whose changes from the
+    /// diff base are `hunks`. Lines with uncommitted changes have no commit.
+    pub fn line_commit(&self, hunks: Option<&FileHunks>, line: u32) -> Option<&BlameCommit> {
+        let line = match hunks {
+            Some(hunks) => hunks.base_line(line)?,
+            None => line,
+        };
+        self.base_line_commit(line)
+    }
+}
+
+/// The message of a commit along with the changes it made to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitDiff {
+    pub message: String,
+    /// The changes to the file as a unified diff.
+    pub diff: String,
+}
+
+/// Maps each line of `after` to the line of `before` it is unchanged from, or `None` if the
+/// line was added or changed.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+pub(crate) fn unchanged_lines(before: &[u8], after: &[u8]) -> Vec<Option<u32>> {
+    let input = InternedInput::new(before, after);
+    let mut lines = Vec::with_capacity(input.after.len());
+    let mut before_line = 0;
+    imara_diff::diff(
+        crate::diff::ALGORITHM,
+        &input,
+        |before: Range<u32>, after: Range<u32>| {
+            let unchanged = after.start - lines.len() as u32;
+            lines.extend((before_line..before_line + unchanged).map(Some));
+            lines.extend(after.map(|_| None));
+            before_line = before.end;
+        },
+    );
+    let unchanged = input.after.len() as u32 - lines.len() as u32;
+    lines.extend((before_line..before_line + unchanged).map(Some));
+    lines
+}
+
+/// Converts days since the unix epoch to a (year, month, day) date of the gregorian calendar.
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = if month < 10 { month + 3 } else { month - 9 } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unchanged_lines_are_mapped() {
+        assert_eq!(
+            unchanged_lines(b"a\nb\nc\nd\n", b"a\nx\nc\nd\ne\n"),
+            [Some(0), None, Some(2), Some(3), None]
+        );
+        assert_eq!(unchanged_lines(b"", b"a\nb\n"), [None, None]);
+        assert_eq!(unchanged_lines(b"a\nb\nc\n", b"c\n"), [Some(2)]);
+    }
+
+    #[test]
+    fn dates() {
+        let mut commit = BlameCommit {
+            id: "0123456789abcdef".to_string(),
+            author: "author".to_string(),
+            time: 946684800,
+            offset: 0,
+            summary: String::new(),
+        };
+        assert_eq!(commit.date(), "2000-01-01");
+        commit.offset = -60 * 60;
+        assert_eq!(commit.date(), "1999-12-31");
+        commit.time = 1709208000;
+        commit.offset = 0;
+        assert_eq!(commit.date(), "2024-02-29");
+        assert_eq!(commit.short_id(), "01234567");
+    }
+}
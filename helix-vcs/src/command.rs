@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Finds the root of the repository containing `path`, the closest of its ancestors containing
+/// `marker`, which is the directory a version control system keeps its data in.
+pub(crate) fn find_repo_root(path: &Path, marker: &str) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(marker).exists())
+        .map(Path::to_owned)
+}
+
+/// A command of `program` running in `dir`, without input and with its errors discarded.
+pub(crate) fn command(program: &str, dir: &Path) -> Command {
+    let mut command = Command::new(program);
+    command
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null());
+    command
+}
+
+/// Runs `command`, returning its output if it succeeds.
+pub(crate) fn output(command: &mut Command) -> Option<Vec<u8>> {
+    let output = command.output().ok()?;
+    output.status.success().then(|| output.stdout)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn innermost_root() {
+        let temp = tempfile::tempdir().unwrap();
+        let inner = temp.path().join("a/b");
+        std::fs::create_dir_all(inner.join(".hg")).unwrap();
+        std::fs::create_dir(temp.path().join(".hg")).unwrap();
+
+        let file = inner.join("c/file.txt");
+        assert_eq!(find_repo_root(&file, ".hg"), Some(inner));
+        assert_eq!(
+            find_repo_root(&temp.path().join("a/file.txt"), ".hg").as_deref(),
+            Some(temp.path())
+        );
+        assert_eq!(find_repo_root(&file, ".jj"), None);
+    }
+}
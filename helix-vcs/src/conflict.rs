@@ -0,0 +1,124 @@
+use std::ops::Range;
+
+use helix_core::RopeSlice;
+
+/// The length of the markers around a conflict.
+const MARKER_LEN: usize = 7;
+
+/// A block of conflicting changes left in a file by a merge, in between `<<<<<<<`, `=======`
+/// and `>>>>>>>` markers. The ranges are lines of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// All the lines of the conflict, including the markers.
+    pub lines: Range<usize>,
+    /// Our side of the conflict, after the `<<<<<<<` marker.
+    pub ours: Range<usize>,
+    /// The common ancestor of both sides, after the `|||||||` marker, only present in conflicts
+    /// of the `diff3` style.
+    pub base: Option<Range<usize>>,
+    /// Their side of the conflict, after the `=======` marker.
+    pub theirs: Range<usize>,
+}
+
+/// Finds the conflicts of `text`.
+pub fn find_conflicts(text: RopeSlice) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    // the lines of the markers of the conflict the line is in
+    let mut start = None;
+    let mut base_marker = None;
+    let mut separator = None;
+    for (i, line) in text.lines().enumerate() {
+        if is_marker(line, '<') {
+            start = Some(i);
+            base_marker = None;
+            separator = None;
+            continue;
+        }
+        match (start, separator) {
+            (Some(_), None) if base_marker.is_none() && is_marker(line, '|') => {
+                base_marker = Some(i)
+            }
+            (Some(_), None) if is_marker(line, '=') => separator = Some(i),
+            (Some(first), Some(separator)) if is_marker(line, '>') => {
+                conflicts.push(Conflict {
+                    lines: first..i + 1,
+                    ours: first + 1..base_marker.unwrap_or(separator),
+                    base: base_marker.map(|base_marker| base_marker + 1..separator),
+                    theirs: separator + 1..i,
+                });
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    conflicts
+}
+
+/// Whether `line` is a conflict marker made of `marker`. The `=======` separator stands
+/// alone, the other markers may be followed by a description of their side.
+fn is_marker(line: RopeSlice, marker: char) -> bool {
+    let mut chars = line.chars();
+    if !chars
+        .by_ref()
+        .take(MARKER_LEN)
+        .eq(std::iter::repeat(marker).take(MARKER_LEN))
+    {
+        return false;
+    }
+    match chars.next() {
+        None | Some('\r' | '\n') => true,
+        Some(' ') => marker != '=',
+        Some(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use helix_core::Rope;
+
+    use super::*;
+
+    #[test]
+    fn merge_conflicts() {
+        let text = Rope::from(
+            "a\n<<<<<<< HEAD\nours\n=======\ntheirs\nmore\n>>>>>>> branch\nb\n<<<<<<< HEAD\n=======\nc\n>>>>>>> branch",
+        );
+        assert_eq!(
+            find_conflicts(text.slice(..)),
+            [
+                Conflict {
+                    lines: 1..7,
+                    ours: 2..3,
+                    base: None,
+                    theirs: 4..6,
+                },
+                Conflict {
+                    lines: 8..12,
+                    ours: 9..9,
+                    base: None,
+                    theirs: 10..11,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn diff3_conflicts() {
+        let text = Rope::from("<<<<<<< ours\nx\n||||||| base\ny\n=======\nz\n>>>>>>> theirs\n");
+        assert_eq!(
+            find_conflicts(text.slice(..)),
+            [Conflict {
+                lines: 0..7,
+                ours: 1..2,
+                base: Some(3..4),
+                theirs: 5..6,
+            }]
+        );
+    }
+
+    #[test]
+    fn not_conflicts() {
+        let text = Rope::from("<<<<<<<< a\n=======\n>>>>>>> b\n<<<<<<< a\n========\n");
+        assert!(find_conflicts(text.slice(..)).is_empty());
+    }
+}
@@ -12,8 +12,13 @@
 use crate::diff::worker::DiffWorker;
 
 mod line_cache;
+mod patch;
+mod words;
 mod worker;
 
+pub use patch::{file_patch, hunk_patch};
+pub use words::word_diff;
+
 type RedrawHandle = (Arc<Notify>, Arc<RwLock<()>>);
 
 /// A rendering lock passed to the differ the prevents redraws from occurring
@@ -33,6 +38,9 @@ pub struct DiffHandle {
     channel: UnboundedSender<Event>,
     render_lock: Arc<RwLock<()>>,
     hunks: Arc<Mutex<Vec<Hunk>>>,
+    inline_changes: Arc<Mutex<Vec<InlineChange>>>,
+    /// The text the document is compared to, which the `before` ranges of the hunks refer to.
+    diff_base: Arc<Mutex<Rope>>,
     inverted: bool,
 }
 
@@ -48,17 +56,22 @@ fn new_with_handle(
     ) -> (DiffHandle, JoinHandle<()>) {
         let (sender, receiver) = unbounded_channel();
         let hunks: Arc<Mutex<Vec<Hunk>>> = Arc::default();
+        let inline_changes: Arc<Mutex<Vec<InlineChange>>> = Arc::default();
         let worker = DiffWorker {
             channel: receiver,
             hunks: hunks.clone(),
             new_hunks: Vec::default(),
+            inline_changes: inline_changes.clone(),
+            new_inline_changes: Vec::default(),
             redraw_notify: redraw_handle.0,
             diff_finished_notify: Arc::default(),
         };
-        let handle = tokio::spawn(worker.run(diff_base, doc));
+        let handle = tokio::spawn(worker.run(diff_base.clone(), doc));
         let differ = DiffHandle {
             channel: sender,
             hunks,
+            inline_changes,
+            diff_base: Arc::new(Mutex::new(diff_base)),
             inverted: false,
             render_lock: redraw_handle.1,
         };
@@ -76,6 +89,14 @@ pub fn hunks(&self) -> FileHunks {
         }
     }
 
+    /// The words changed within the lines of the hunks that modify lines.
+    pub fn inline_changes(&self) -> InlineChanges {
+        InlineChanges {
+            changes: self.inline_changes.lock(),
+            inverted: self.inverted,
+        }
+    }
+
     /// Updates the document associated with this redraw handle
     /// This function is only intended to be called from within the rendering loop
     /// if called from elsewhere it may fail to acquire the render lock and panic
@@ -93,7 +114,13 @@ pub fn update_document(&self, doc: Rope, block: bool) -> bool {
         self.update_document_impl(doc, self.inverted, Some(RenderLock { lock, timeout }))
     }
 
+    /// Returns the text the document is compared to.
+    pub fn diff_base(&self) -> Rope {
+        self.diff_base.lock().clone()
+    }
+
     pub fn update_diff_base(&self, diff_base: Rope) -> bool {
+        *self.diff_base.lock() = diff_base.clone();
         self.update_document_impl(diff_base, !self.inverted, None)
     }
 
@@ -118,10 +145,12 @@ fn update_document_impl(
 /// maximum time that rendering should be blocked until the diff finishes
 const SYNC_DIFF_TIMEOUT: u64 = 12;
 const DIFF_DEBOUNCE_TIME_ASYNC: u64 = 96;
-const ALGORITHM: Algorithm = Algorithm::Histogram;
+pub(crate) const ALGORITHM: Algorithm = Algorithm::Histogram;
 const MAX_DIFF_LINES: usize = 64 * u16::MAX as usize;
 // cap average line length to 128 for files with MAX_DIFF_LINES
 const MAX_DIFF_BYTES: usize = MAX_DIFF_LINES * 128;
+/// changes of many lines are hardly comparable word by word
+const MAX_INLINE_DIFF_LINES: usize = 100;
 
 /// A single change in a file potentially spanning multiple lines
 /// Hunks produced by the differs are always ordered by their position
@@ -165,6 +194,46 @@ pub fn is_pure_removal(&self) -> bool {
     }
 }
 
+/// The words that changed within the lines modified by a hunk, found by comparing the words of
+/// its `before` and `after` lines.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct InlineChange {
+    pub hunk: Hunk,
+    /// The char ranges removed from the `before` lines, relative to the start of the first one.
+    pub removed: Vec<Range<usize>>,
+    /// The char ranges added to the `after` lines, relative to the start of the first one.
+    pub added: Vec<Range<usize>>,
+}
+
+impl InlineChange {
+    pub fn invert(&self) -> InlineChange {
+        InlineChange {
+            hunk: self.hunk.invert(),
+            removed: self.added.clone(),
+            added: self.removed.clone(),
+        }
+    }
+}
+
+/// The words changed within the modified lines of a file, in the order of their hunks
+#[derive(Debug)]
+pub struct InlineChanges<'a> {
+    changes: MutexGuard<'a, Vec<InlineChange>>,
+    inverted: bool,
+}
+
+impl InlineChanges<'_> {
+    pub fn iter(&self) -> impl Iterator<Item = InlineChange> + '_ {
+        self.changes.iter().map(|change| {
+            if self.inverted {
+                change.invert()
+            } else {
+                change.clone()
+            }
+        })
+    }
+}
+
 /// A list of changes in a file sorted in ascending
 /// non-overlapping order
 #[derive(Debug)]
@@ -247,6 +316,62 @@ pub fn prev_hunk(&self, line: u32) -> Option<u32> {
         }
     }
 
+    /// Maps a `line` of the document to the line of the diff base it is unchanged from,
+    /// or `None` if the line was added or changed.
+    pub fn base_line(&self, line: u32) -> Option<u32> {
+        let mut base_line = line;
+        for i in 0..self.len() {
+            let hunk = self.nth_hunk(i);
+            if hunk.after.start > line {
+                break;
+            }
+            if hunk.after.contains(&line) {
+                return None;
+            }
+            base_line = line - hunk.after.end + hunk.before.end;
+        }
+        Some(base_line)
+    }
+
+    /// Maps a `line` of the document to the corresponding line of the diff base, or the other
+    /// way around with `from_base`. Changed lines map to the start of their change.
+    pub fn corresponding_line(&self, line: u32, from_base: bool) -> u32 {
+        let mut corresponding = line;
+        for i in 0..self.len() {
+            let hunk = self.nth_hunk(i);
+            let (from, to) = if from_base {
+                (hunk.before, hunk.after)
+            } else {
+                (hunk.after, hunk.before)
+            };
+            if from.start > line {
+                break;
+            }
+            if from.contains(&line) {
+                return to.start;
+            }
+            corresponding = line - from.end + to.end;
+        }
+        corresponding
+    }
+
+    /// The empty rows to show above lines of the document and above lines of the diff base, as
+    /// `(line, rows)`, so that their unchanged lines can be shown side by side.
+    pub fn filler_rows(&self) -> (Vec<(usize, usize)>, Vec<(usize, usize)>) {
+        let mut doc_rows = Vec::new();
+        let mut base_rows = Vec::new();
+        for i in 0..self.len() {
+            let hunk = self.nth_hunk(i);
+            let (before, after) = (hunk.before.len(), hunk.after.len());
+            if before > after {
+                doc_rows.push((hunk.after.end as usize, before - after));
+            } else if after > before {
+                base_rows.push((hunk.before.end as usize, after - before));
+            }
+        }
+        (doc_rows, base_rows)
+    }
+
     pub fn hunk_at(&self, line: u32, include_removal: bool) -> Option<u32> {
         let hunk_range = if self.inverted {
             |hunk: &Hunk| hunk.before.clone()
@@ -0,0 +1,98 @@
+use std::ops::Range;
+
+use helix_core::{Rope, RopeSlice};
+use imara_diff::intern::InternedInput;
+use imara_diff::UnifiedDiffBuilder;
+
+use super::{Hunk, ALGORITHM};
+
+/// Formats all the changes from `before` to `after` as the hunks of a unified diff, with
+/// their `@@` headers but without the `---` and `+++` file headers.
+pub fn file_patch(before: &str, after: &str) -> String {
+    let input = InternedInput::new(before, after);
+    imara_diff::diff(ALGORITHM, &input, UnifiedDiffBuilder::new(&input))
+}
+
+/// Formats `hunk` as the hunk of a unified diff without context lines, starting with its
+/// `@@` header. The `before` lines are read from `diff_base` and the `after` lines from `doc`.
+pub fn hunk_patch(diff_base: &Rope, doc: &Rope, hunk: &Hunk) -> String {
+    let before = lines(diff_base.slice(..), &hunk.before);
+    let after = lines(doc.slice(..), &hunk.after);
+    let mut patch = format!(
+        "@@ -{} +{} @@\n",
+        header_range(hunk.before.start, before.len()),
+        header_range(hunk.after.start, after.len())
+    );
+    for (prefix, lines) in [('-', before), ('+', after)] {
+        for line in lines {
+            patch.push(prefix);
+            for chunk in line.chunks() {
+                patch.push_str(chunk);
+            }
+            if !patch.ends_with('\n') {
+                patch.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    patch
+}
+
+/// The lines of `text` in the range, without the empty line following a final line ending.
+fn lines<'a>(text: RopeSlice<'a>, lines: &Range<u32>) -> Vec<RopeSlice<'a>> {
+    lines
+        .clone()
+        .map(|line| text.line(line as usize))
+        .filter(|line| line.len_chars() > 0)
+        .collect()
+}
+
+/// Unified diffs count lines from 1, and an empty range is given by the line before it.
+fn header_range(start: u32, len: usize) -> String {
+    if len == 0 {
+        format!("{},0", start)
+    } else {
+        format!("{},{}", start + 1, len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn whole_file() {
+        assert_eq!(
+            file_patch("a\nb\nc\n", "a\nx\nc\n"),
+            "@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n"
+        );
+        assert!(file_patch("a\n", "a\n").is_empty());
+    }
+
+    #[test]
+    fn changed_lines() {
+        let diff_base = Rope::from("a\nb\nc\n");
+        let doc = Rope::from("a\nx\ny\nc\n");
+        let hunk = Hunk {
+            before: 1..2,
+            after: 1..3,
+        };
+        assert_eq!(
+            hunk_patch(&diff_base, &doc, &hunk),
+            "@@ -2,1 +2,2 @@\n-b\n+x\n+y\n"
+        );
+    }
+
+    #[test]
+    fn insertion_without_final_newline() {
+        let diff_base = Rope::from("a\n");
+        let doc = Rope::from("a\nb");
+        let hunk = Hunk {
+            before: 1..2,
+            after: 1..2,
+        };
+        assert_eq!(
+            hunk_patch(&diff_base, &doc, &hunk),
+            "@@ -1,0 +2,1 @@\n+b\n\\ No newline at end of file\n"
+        );
+    }
+}
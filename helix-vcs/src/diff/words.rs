@@ -0,0 +1,95 @@
+use std::ops::Range;
+
+use helix_core::chars::{categorize_char, CharCategory};
+use imara_diff::intern::{InternedInput, Interner};
+
+use super::ALGORITHM;
+
+/// Compares the words of the `before` and `after` text of a change, to find the parts of its
+/// lines that changed. Returns the char ranges of `before` that were removed and the char ranges
+/// of `after` that were added.
+pub fn word_diff(before: &str, after: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let before_tokens = tokens(before);
+    let after_tokens = tokens(after);
+    let mut input = InternedInput {
+        before: Vec::with_capacity(before_tokens.len()),
+        after: Vec::with_capacity(after_tokens.len()),
+        interner: Interner::new(before_tokens.len() + after_tokens.len()),
+    };
+    input.update_before(before_tokens.iter().map(|(token, _)| *token));
+    input.update_after(after_tokens.iter().map(|(token, _)| *token));
+
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    imara_diff::diff(
+        ALGORITHM,
+        &input,
+        |before: Range<u32>, after: Range<u32>| {
+            removed.extend(char_range(&before_tokens, before));
+            added.extend(char_range(&after_tokens, after));
+        },
+    );
+    (removed, added)
+}
+
+/// Splits `text` into words, runs of whitespace and single other characters, along with the
+/// char range of each.
+fn tokens(text: &str) -> Vec<(&str, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut start = (0, 0);
+    let mut prev_category = None;
+    let mut len_chars = 0;
+    for (char_idx, (byte_idx, ch)) in text.char_indices().enumerate() {
+        let category = categorize_char(ch);
+        let joins_prev = prev_category.as_ref() == Some(&category)
+            && matches!(category, CharCategory::Word | CharCategory::Whitespace);
+        if char_idx > 0 && !joins_prev {
+            tokens.push((&text[start.0..byte_idx], start.1..char_idx));
+            start = (byte_idx, char_idx);
+        }
+        prev_category = Some(category);
+        len_chars = char_idx + 1;
+    }
+    if !text.is_empty() {
+        tokens.push((&text[start.0..], start.1..len_chars));
+    }
+    tokens
+}
+
+/// The chars covered by a range of `tokens`, or `None` if the range is empty.
+fn char_range(tokens: &[(&str, Range<usize>)], range: Range<u32>) -> Option<Range<usize>> {
+    if range.is_empty() {
+        return None;
+    }
+    let start = tokens[range.start as usize].1.start;
+    let end = tokens[range.end as usize - 1].1.end;
+    Some(start..end)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tokenize() {
+        let tokens: Vec<_> = tokens("let foo  = (bar);\n")
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect();
+        assert_eq!(
+            tokens,
+            ["let", " ", "foo", "  ", "=", " ", "(", "bar", ")", ";", "\n"]
+        );
+    }
+
+    #[test]
+    fn changed_words() {
+        let (removed, added) = word_diff("let foo = 1;\n", "let bar = 1;\n");
+        assert_eq!(removed, [4..7]);
+        assert_eq!(added, [4..7]);
+
+        let (removed, added) = word_diff("fn a(x)\n", "fn a(x, yé)\n");
+        assert!(removed.is_empty());
+        assert_eq!(added, [6..10]);
+    }
+}
@@ -3,7 +3,7 @@
 use std::sync::Arc;
 
 use helix_core::{Rope, RopeSlice};
-use imara_diff::intern::InternedInput;
+use imara_diff::intern::{InternedInput, Token};
 use parking_lot::Mutex;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::sync::Notify;
@@ -11,10 +11,12 @@
 
 use crate::diff::{
     Event, RenderLock, ALGORITHM, DIFF_DEBOUNCE_TIME_ASYNC, DIFF_DEBOUNCE_TIME_SYNC,
+    MAX_INLINE_DIFF_LINES,
 };
 
 use super::line_cache::InternedRopeLines;
-use super::Hunk;
+use super::words::word_diff;
+use super::{Hunk, InlineChange};
 
 #[cfg(test)]
 mod test;
@@ -23,6 +25,8 @@ pub(super) struct DiffWorker {
     pub channel: UnboundedReceiver<Event>,
     pub hunks: Arc<Mutex<Vec<Hunk>>>,
     pub new_hunks: Vec<Hunk>,
+    pub inline_changes: Arc<Mutex<Vec<InlineChange>>>,
+    pub new_inline_changes: Vec<InlineChange>,
     pub redraw_notify: Arc<Notify>,
     pub diff_finished_notify: Arc<Notify>,
 }
@@ -74,22 +78,60 @@ pub async fn run(mut self, diff_base: Rope, doc: Rope) {
         }
     }
 
-    /// update the hunks (used by the gutter) by replacing it with `self.new_hunks`.
+    /// update the hunks (used by the gutter) by replacing it with `self.new_hunks`,
+    /// and the inline changes with `self.new_inline_changes`.
     /// `self.new_hunks` is always empty after this function runs.
     /// To improve performance this function tries to reuse the allocation of the old diff previously stored in `self.line_diffs`
     fn apply_hunks(&mut self) {
         swap(&mut *self.hunks.lock(), &mut self.new_hunks);
+        swap(
+            &mut *self.inline_changes.lock(),
+            &mut self.new_inline_changes,
+        );
         self.diff_finished_notify.notify_waiters();
         self.new_hunks.clear();
+        self.new_inline_changes.clear();
     }
 
     fn perform_diff(&mut self, input: &InternedInput<RopeSlice>) {
         imara_diff::diff(ALGORITHM, input, |before: Range<u32>, after: Range<u32>| {
             self.new_hunks.push(Hunk { before, after })
-        })
+        });
+        let inline_changes = self
+            .new_hunks
+            .iter()
+            .filter_map(|hunk| inline_change(input, hunk));
+        self.new_inline_changes.extend(inline_changes);
     }
 }
 
+/// Compares the words of the lines modified by `hunk`, or returns `None` if it only adds or
+/// removes lines.
+fn inline_change(input: &InternedInput<RopeSlice>, hunk: &Hunk) -> Option<InlineChange> {
+    if hunk.is_pure_insertion()
+        || hunk.is_pure_removal()
+        || hunk.before.len().max(hunk.after.len()) > MAX_INLINE_DIFF_LINES
+    {
+        return None;
+    }
+    let text = |lines: &[Token], range: &Range<u32>| {
+        let mut text = String::new();
+        for &line in &lines[range.start as usize..range.end as usize] {
+            text.extend(input.interner[line].chunks());
+        }
+        text
+    };
+    let (removed, added) = word_diff(
+        &text(&input.before, &hunk.before),
+        &text(&input.after, &hunk.after),
+    );
+    Some(InlineChange {
+        hunk: hunk.clone(),
+        removed,
+        added,
+    })
+}
+
 struct EventAccumulator {
     diff_base: Option<Rope>,
     doc: Option<Rope>,
@@ -1,7 +1,7 @@
 use helix_core::Rope;
 use tokio::task::JoinHandle;
 
-use crate::diff::{DiffHandle, Hunk};
+use crate::diff::{DiffHandle, Hunk, InlineChange};
 
 impl DiffHandle {
     fn new_test(diff_base: &str, doc: &str) -> (DiffHandle, JoinHandle<()>) {
@@ -147,3 +147,23 @@ async fn update_base() {
         ]
     )
 }
+
+#[tokio::test]
+async fn inline_changes() {
+    let (differ, handle) =
+        DiffHandle::new_test("foo\nlet a = 1;\nbar\n", "foo\nlet b = 2;\nbar\nbaz\n");
+    let inline_changes = differ.inline_changes.clone();
+    differ.into_diff(handle).await;
+    let changes = inline_changes.lock().clone();
+    assert_eq!(
+        changes,
+        [InlineChange {
+            hunk: Hunk {
+                before: 1..2,
+                after: 1..2
+            },
+            removed: vec![4..5, 8..9],
+            added: vec![4..5, 8..9],
+        }]
+    );
+}
@@ -1,11 +1,25 @@
-use std::path::Path;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 use git::objs::tree::EntryMode;
 use git::sec::trust::DefaultForLevel;
 use git::{Commit, ObjectId, Repository, ThreadSafeRepository};
 use git_repository as git;
 
-use crate::DiffProvider;
+use crate::blame::{unchanged_lines, Blame, BlameCommit, CommitDiff};
+use crate::command::{command, find_repo_root, output};
+use crate::status::{
+    parse_branch_headers, parse_porcelain, repo_operation, FileChange, RepoStatus,
+};
+use crate::{file_patch, DiffProvider, INDEX_REVISION};
+
+/// Number of commits looked at by blame before the remaining lines are attributed to the
+/// oldest one, to bound the time spent on files with long histories.
+const MAX_BLAME_COMMITS: usize = 1000;
+
+/// Number of commits looked at for the history of a file.
+const MAX_HISTORY_COMMITS: usize = 10000;
 
 #[cfg(test)]
 mod test;
@@ -54,6 +68,11 @@ fn open_repo(path: &Path, ceiling_dir: Option<&Path>) -> Option<ThreadSafeReposi
 }
 
 impl DiffProvider for Git {
+    fn repo_root(&self, path: &Path) -> Option<PathBuf> {
+        // worktrees and submodules have a `.git` file pointing to their repository
+        find_repo_root(path, ".git")
+    }
+
     fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
         debug_assert!(!file.exists() || file.is_file());
         debug_assert!(file.is_absolute());
@@ -64,30 +83,260 @@ fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
         let file_oid = find_file_in_commit(&repo, &head, file)?;
 
         let file_object = repo.find_object(file_oid).ok()?;
-        let mut data = file_object.detach().data;
-        // convert LF to CRLF if configured to avoid showing every line as changed
-        if repo
-            .config_snapshot()
-            .boolean("core.autocrlf")
-            .unwrap_or(false)
-        {
-            let mut normalized_file = Vec::with_capacity(data.len());
-            let mut at_cr = false;
-            for &byte in &data {
-                if byte == b'\n' {
-                    // if this is a LF instead of a CRLF (last byte was not a CR)
-                    // insert a new CR to generate a CRLF
-                    if !at_cr {
-                        normalized_file.push(b'\r');
-                    }
+        Some(normalize_line_endings(&repo, file_object.detach().data))
+    }
+
+    fn get_file_at(&self, file: &Path, revision: &str) -> Option<Vec<u8>> {
+        let repo = Git::open_repo(file.parent()?, None)?.to_thread_local();
+        let file_oid = if revision == INDEX_REVISION {
+            let rel_path = file.strip_prefix(repo.work_dir()?).ok()?;
+            let index = repo.index().ok()?;
+            let entry = index.entry_by_path_and_stage(&git::path::into_bstr(rel_path), 0)?;
+            entry.id
+        } else {
+            let commit = repo
+                .rev_parse_single(revision)
+                .ok()?
+                .object()
+                .ok()?
+                .peel_to_kind(git::object::Kind::Commit)
+                .ok()?
+                .try_into_commit()
+                .ok()?;
+            find_file_in_commit(&repo, &commit, file)?
+        };
+
+        let file_object = repo.find_object(file_oid).ok()?;
+        Some(normalize_line_endings(&repo, file_object.detach().data))
+    }
+
+    fn blame(&self, file: &Path) -> Option<Blame> {
+        let repo = Git::open_repo(file.parent()?, None)?.to_thread_local();
+        let mut commit = repo.head_commit().ok()?;
+        let mut blob = find_file_in_commit(&repo, &commit, file)?;
+        let mut data = repo.find_object(blob).ok()?.detach().data;
+
+        let line_count = data.split_inclusive(|&byte| byte == b'\n').count();
+        let mut blame = Blame {
+            commits: Vec::new(),
+            lines: vec![0; line_count],
+        };
+        // the lines still to attribute, as their line in the version of `commit` and in HEAD
+        let mut pending: Vec<(u32, usize)> =
+            (0..line_count).map(|line| (line as u32, line)).collect();
+        for _ in 0..MAX_BLAME_COMMITS {
+            let parent = first_parent(&commit);
+            let parent_blob = parent
+                .as_ref()
+                .and_then(|parent| find_file_in_commit(&repo, parent, file));
+            if parent_blob == Some(blob) {
+                if let Some(parent) = parent {
+                    // the file is unchanged by this commit
+                    commit = parent;
+                    continue;
                 }
-                at_cr = byte == b'\r';
-                normalized_file.push(byte)
             }
-            data = normalized_file
+
+            let parent_data = parent_blob
+                .and_then(|blob| repo.find_object(blob).ok())
+                .map(|object| object.detach().data)
+                .unwrap_or_default();
+            let unchanged = unchanged_lines(&parent_data, &data);
+            let index = blame.commits.len();
+            let mut attributed = false;
+            pending.retain_mut(|(line, head_line)| match unchanged[*line as usize] {
+                Some(parent_line) => {
+                    *line = parent_line;
+                    true
+                }
+                None => {
+                    blame.lines[*head_line] = index;
+                    attributed = true;
+                    false
+                }
+            });
+            if attributed {
+                blame.commits.push(blame_commit(&commit)?);
+            }
+
+            match (parent, parent_blob) {
+                (Some(parent), Some(parent_blob)) if !pending.is_empty() => {
+                    commit = parent;
+                    blob = parent_blob;
+                    data = parent_data;
+                }
+                _ => return Some(blame),
+            }
         }
-        Some(data)
+
+        // the history is too long, blame the oldest commit looked at for what is left
+        let index = blame.commits.len();
+        blame.commits.push(blame_commit(&commit)?);
+        for (_, head_line) in pending {
+            blame.lines[head_line] = index;
+        }
+        Some(blame)
     }
+
+    fn file_history(&self, file: &Path) -> Option<Vec<BlameCommit>> {
+        let repo = Git::open_repo(file.parent()?, None)?.to_thread_local();
+        let mut commit = repo.head_commit().ok()?;
+        let mut blob = find_file_in_commit(&repo, &commit, file);
+        let mut history = Vec::new();
+        // like `git log --first-parent`, the changes merged from other branches are attributed
+        // to the merge commit
+        for _ in 0..MAX_HISTORY_COMMITS {
+            let parent = first_parent(&commit);
+            let parent_blob = parent
+                .as_ref()
+                .and_then(|parent| find_file_in_commit(&repo, parent, file));
+            if blob.is_some() && parent_blob != blob {
+                history.push(blame_commit(&commit)?);
+            }
+            match parent {
+                Some(parent) => {
+                    commit = parent;
+                    blob = parent_blob;
+                }
+                None => break,
+            }
+        }
+        Some(history)
+    }
+
+    fn commit_diff(&self, file: &Path, commit: &str) -> Option<CommitDiff> {
+        let repo = Git::open_repo(file.parent()?, None)?.to_thread_local();
+        let id = ObjectId::from_hex(commit.as_bytes()).ok()?;
+        let commit = repo.find_object(id).ok()?.try_into_commit().ok()?;
+        let message = commit.decode().ok()?.message.to_string();
+
+        let read_file = |commit: &Commit| -> Option<Vec<u8>> {
+            let blob = find_file_in_commit(&repo, commit, file)?;
+            Some(repo.find_object(blob).ok()?.detach().data)
+        };
+        let after = read_file(&commit).unwrap_or_default();
+        let before = first_parent(&commit)
+            .and_then(|parent| read_file(&parent))
+            .unwrap_or_default();
+        let before = String::from_utf8_lossy(&before);
+        let after = String::from_utf8_lossy(&after);
+        let hunks = file_patch(&before, &after);
+
+        let path = file.strip_prefix(repo.work_dir()?).ok()?.display();
+        let diff = format!("--- a/{}\n+++ b/{}\n{}", path, path, hunks);
+        Some(CommitDiff { message, diff })
+    }
+
+    fn changed_files(&self, dir: &Path) -> Option<Vec<FileChange>> {
+        let repo = Git::open_repo(dir, None)?.to_thread_local();
+        let work_dir = repo.work_dir()?;
+        let output = git_status(work_dir, &["--porcelain=v1", "-z", "--untracked-files=all"])?;
+        Some(parse_porcelain(work_dir, &output))
+    }
+
+    fn repo_status(&self, dir: &Path) -> Option<RepoStatus> {
+        let repo = Git::open_repo(dir, None)?.to_thread_local();
+        let output = git_status(
+            repo.work_dir()?,
+            &["--porcelain=v2", "--branch", "--untracked-files=no"],
+        )?;
+        parse_branch_headers(
+            &String::from_utf8_lossy(&output),
+            repo_operation(repo.git_dir()),
+        )
+    }
+
+    fn apply_to_index(&self, file: &Path, patch: &str, reverse: bool) -> io::Result<()> {
+        let not_found = || io::Error::new(io::ErrorKind::NotFound, "File is not in a repository");
+        let repo = Git::open_repo(file.parent().ok_or_else(not_found)?, None)
+            .ok_or_else(not_found)?
+            .to_thread_local();
+        let work_dir = repo.work_dir().ok_or_else(not_found)?;
+        let path = file.strip_prefix(work_dir).map_err(|_| not_found())?;
+        // git paths always use forward slashes
+        let path = path.to_string_lossy().replace('\\', "/");
+        let patch = format!("--- a/{}\n+++ b/{}\n{}", path, path, patch);
+
+        // writing the index isn't supported by gitoxide yet
+        let mut command = Command::new("git");
+        command
+            .arg("apply")
+            .arg("--cached")
+            .arg("--unidiff-zero")
+            .arg("--whitespace=nowarn");
+        if reverse {
+            command.arg("--reverse");
+        }
+        let mut process = command
+            .arg("-")
+            .current_dir(work_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        process
+            .stdin
+            .take()
+            .expect("Failed to open stdin")
+            .write_all(patch.as_bytes())?;
+        let output = process.wait_with_output()?;
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stderr);
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                message.trim().to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Runs `git status` with `args` in `work_dir`, returning its output.
+fn git_status(work_dir: &Path, args: &[&str]) -> Option<Vec<u8>> {
+    // the status of the working directory isn't supported by gitoxide yet
+    output(command("git", work_dir).arg("status").args(args))
+}
+
+/// Converts LF to CRLF if configured to avoid showing every line as changed.
+fn normalize_line_endings(repo: &Repository, data: Vec<u8>) -> Vec<u8> {
+    if !repo
+        .config_snapshot()
+        .boolean("core.autocrlf")
+        .unwrap_or(false)
+    {
+        return data;
+    }
+    let mut normalized_file = Vec::with_capacity(data.len());
+    let mut at_cr = false;
+    for &byte in &data {
+        if byte == b'\n' {
+            // if this is a LF instead of a CRLF (last byte was not a CR)
+            // insert a new CR to generate a CRLF
+            if !at_cr {
+                normalized_file.push(b'\r');
+            }
+        }
+        at_cr = byte == b'\r';
+        normalized_file.push(byte)
+    }
+    normalized_file
+}
+
+/// Returns the first parent of `commit`, following the history of the branch it was made on.
+fn first_parent<'repo>(commit: &Commit<'repo>) -> Option<Commit<'repo>> {
+    let parent = commit.parent_ids().next()?;
+    parent.object().ok()?.try_into_commit().ok()
+}
+
+fn blame_commit(commit: &Commit) -> Option<BlameCommit> {
+    let decoded = commit.decode().ok()?;
+    Some(BlameCommit {
+        id: commit.id.to_hex().to_string(),
+        author: decoded.author.name.to_string(),
+        time: decoded.author.time.seconds_since_unix_epoch as i64,
+        offset: decoded.author.time.offset_in_seconds,
+        summary: decoded.message().summary().to_string(),
+    })
 }
 
 /// Finds the object that contains the contents of a file at a specific commit.
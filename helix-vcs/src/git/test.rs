@@ -2,7 +2,7 @@
 
 use tempfile::TempDir;
 
-use crate::{DiffProvider, Git};
+use crate::{DiffProvider, FileStatus, Git, RepoHead, RepoOperation};
 
 fn exec_git_cmd(args: &str, git_dir: &Path) {
     let res = Command::new("git")
@@ -119,3 +119,172 @@ fn symlink() {
     assert_eq!(Git.get_diff_base(&file_link), None);
     assert_eq!(Git.get_diff_base(&file), Some(Vec::from(contents)));
 }
+
+#[test]
+fn blame() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file)
+        .unwrap()
+        .write_all(b"foo\nbar\n")
+        .unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file)
+        .unwrap()
+        .write_all(b"foo\nbaz\nbar\n")
+        .unwrap();
+    create_commit(temp_git.path(), true);
+    // uncommitted changes are not blamed
+    File::create(&file).unwrap().write_all(b"qux\n").unwrap();
+
+    let blame = Git.blame(&file).unwrap();
+    assert_eq!(blame.commits.len(), 2);
+    assert_eq!(blame.lines, [1, 0, 1]);
+    assert_eq!(blame.commits[0].author, "author");
+    assert_eq!(blame.commits[0].summary, "message");
+    assert_eq!(blame.commits[0].date(), "2000-01-01");
+
+    let commit_diff = Git.commit_diff(&file, &blame.commits[0].id).unwrap();
+    assert_eq!(commit_diff.message.trim_end(), "message");
+    assert!(commit_diff.diff.contains("+baz"));
+}
+
+#[test]
+fn commit_summary() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"foo\n").unwrap();
+    exec_git_cmd("add -A", temp_git.path());
+    exec_git_cmd("commit -m summary -m body", temp_git.path());
+
+    // only the first paragraph of the message is shown
+    let history = Git.file_history(&file).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].summary, "summary");
+    let blame = Git.blame(&file).unwrap();
+    assert_eq!(blame.commits[0].summary, "summary");
+
+    let commit_diff = Git.commit_diff(&file, &history[0].id).unwrap();
+    assert_eq!(commit_diff.message.trim_end(), "summary\n\nbody");
+}
+
+#[test]
+fn stage_hunk() {
+    use crate::{hunk_patch, Hunk};
+    use helix_core::Rope;
+
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"a\nb\n").unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file).unwrap().write_all(b"a\nc\n").unwrap();
+
+    let index_contents = || {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(temp_git.path())
+            .args(["show", ":file.txt"])
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let hunk = Hunk {
+        before: 1..2,
+        after: 1..2,
+    };
+    let patch = hunk_patch(&Rope::from("a\nb\n"), &Rope::from("a\nc\n"), &hunk);
+    Git.apply_to_index(&file, &patch, false).unwrap();
+    assert_eq!(index_contents(), "a\nc\n");
+    Git.apply_to_index(&file, &patch, true).unwrap();
+    assert_eq!(index_contents(), "a\nb\n");
+}
+
+#[test]
+fn file_at_revision() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"foo").unwrap();
+    create_commit(temp_git.path(), true);
+    exec_git_cmd("tag first", temp_git.path());
+    File::create(&file).unwrap().write_all(b"bar").unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file).unwrap().write_all(b"baz").unwrap();
+    exec_git_cmd("add file.txt", temp_git.path());
+    File::create(&file).unwrap().write_all(b"qux").unwrap();
+
+    assert_eq!(Git.get_file_at(&file, "first"), Some(b"foo".to_vec()));
+    assert_eq!(Git.get_file_at(&file, "HEAD~1"), Some(b"foo".to_vec()));
+    assert_eq!(Git.get_file_at(&file, "main"), Some(b"bar".to_vec()));
+    assert_eq!(Git.get_file_at(&file, "index"), Some(b"baz".to_vec()));
+    assert_eq!(Git.get_file_at(&file, "missing"), None);
+}
+
+#[test]
+fn changed_files() {
+    let temp_git = empty_git_repo();
+    let modified = temp_git.path().join("modified.txt");
+    let untracked = temp_git.path().join("untracked.txt");
+    File::create(&modified).unwrap().write_all(b"foo").unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&modified).unwrap().write_all(b"bar").unwrap();
+    File::create(&untracked).unwrap().write_all(b"baz").unwrap();
+
+    let mut changes: Vec<_> = Git
+        .changed_files(temp_git.path())
+        .unwrap()
+        .into_iter()
+        .map(|change| (change.path, change.status))
+        .collect();
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        changes,
+        [
+            (modified, FileStatus::Modified),
+            (untracked, FileStatus::Untracked)
+        ]
+    );
+}
+
+#[test]
+fn repo_status() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"foo").unwrap();
+    create_commit(temp_git.path(), true);
+
+    let status = Git.repo_status(temp_git.path()).unwrap();
+    assert_eq!(status.head, RepoHead::Branch("main".to_owned()));
+    assert_eq!(
+        (status.ahead, status.behind, status.operation),
+        (0, 0, None)
+    );
+
+    File::create(temp_git.path().join(".git/BISECT_LOG")).unwrap();
+    exec_git_cmd("checkout --detach", temp_git.path());
+    let status = Git.repo_status(temp_git.path()).unwrap();
+    assert!(matches!(status.head, RepoHead::Detached(id) if id.len() == 7));
+    assert_eq!(status.operation, Some(RepoOperation::Bisect));
+}
+
+#[test]
+fn file_history() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    let other = temp_git.path().join("other.txt");
+    File::create(&file).unwrap().write_all(b"foo").unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&other).unwrap().write_all(b"bar").unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file).unwrap().write_all(b"baz").unwrap();
+    exec_git_cmd("add -A", temp_git.path());
+    exec_git_cmd("commit -m second", temp_git.path());
+
+    let summaries: Vec<_> = Git
+        .file_history(&file)
+        .unwrap()
+        .into_iter()
+        .map(|commit| commit.summary)
+        .collect();
+    assert_eq!(summaries, ["second", "message"]);
+    assert_eq!(Git.file_history(&other).unwrap().len(), 1);
+}
@@ -0,0 +1,143 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::command::{command, find_repo_root, output};
+use crate::{DiffProvider, FileChange, FileStatus, INDEX_REVISION};
+
+/// Mercurial repositories, through the `hg` command.
+pub struct Hg;
+
+impl Hg {
+    /// An `hg` command running in `root`, with the output unaffected by the user configuration.
+    fn hg(root: &Path) -> Command {
+        let mut command = command("hg", root);
+        command.env("HGPLAIN", "1");
+        command
+    }
+}
+
+impl DiffProvider for Hg {
+    fn repo_root(&self, path: &Path) -> Option<PathBuf> {
+        find_repo_root(path, ".hg")
+    }
+
+    fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
+        // the parent of the working directory
+        self.get_file_at(file, ".")
+    }
+
+    fn get_file_at(&self, file: &Path, revision: &str) -> Option<Vec<u8>> {
+        // mercurial commits the working directory as a whole, without an index
+        if revision == INDEX_REVISION {
+            return None;
+        }
+        let root = self.repo_root(file)?;
+        let path = file.strip_prefix(&root).ok()?;
+        output(
+            Self::hg(&root)
+                .args(["cat", "--rev", revision, "--"])
+                .arg(format!("path:{}", path.display())),
+        )
+    }
+
+    fn changed_files(&self, dir: &Path) -> Option<Vec<FileChange>> {
+        let root = self.repo_root(dir)?;
+        let output = output(Self::hg(&root).args(["status", "--print0"]))?;
+        Some(parse_status(&root, &output))
+    }
+}
+
+/// Parses the output of `hg status --print0`, whose paths are relative to `root`.
+fn parse_status(root: &Path, output: &[u8]) -> Vec<FileChange> {
+    output
+        .split(|&byte| byte == 0)
+        .filter_map(|entry| {
+            let (code, path) = (entry.first()?, entry.get(2..)?);
+            let status = match code {
+                b'M' => FileStatus::Modified,
+                b'A' => FileStatus::Added,
+                // removed with `hg remove` and missing from the working directory
+                b'R' | b'!' => FileStatus::Deleted,
+                b'?' => FileStatus::Untracked,
+                _ => return None,
+            };
+            Some(FileChange {
+                path: root.join(String::from_utf8_lossy(path).as_ref()),
+                status,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn status() {
+        let output = b"M src/a.rs\0A b\0R c\0! d\0? e\0";
+        let changes: Vec<_> = parse_status(Path::new("/repo"), output)
+            .into_iter()
+            .map(|change| (change.path, change.status))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (PathBuf::from("/repo/src/a.rs"), FileStatus::Modified),
+                (PathBuf::from("/repo/b"), FileStatus::Added),
+                (PathBuf::from("/repo/c"), FileStatus::Deleted),
+                (PathBuf::from("/repo/d"), FileStatus::Deleted),
+                (PathBuf::from("/repo/e"), FileStatus::Untracked),
+            ]
+        );
+    }
+
+    /// Runs `hg` in `dir`, or returns `false` if mercurial isn't installed.
+    fn exec_hg_cmd(args: &[&str], dir: &Path) -> bool {
+        let status = match Command::new("hg")
+            .args(args)
+            .current_dir(dir)
+            .env("HGPLAIN", "1")
+            .env("HGUSER", "helix-test <test@helix.org>")
+            .status()
+        {
+            Ok(status) => status,
+            Err(_) => return false,
+        };
+        assert!(status.success(), "`hg {}` failed", args.join(" "));
+        true
+    }
+
+    #[test]
+    fn diff_base_and_status() {
+        let temp = tempfile::tempdir().unwrap();
+        if !exec_hg_cmd(&["init"], temp.path()) {
+            return;
+        }
+        let file = temp.path().join("file.txt");
+        fs::write(&file, "foo").unwrap();
+        assert!(exec_hg_cmd(&["commit", "-A", "-m", "message"], temp.path()));
+        fs::write(&file, "bar").unwrap();
+        fs::write(temp.path().join("new.txt"), "baz").unwrap();
+
+        assert_eq!(Hg.get_diff_base(&file), Some(b"foo".to_vec()));
+        assert_eq!(Hg.get_diff_base(&temp.path().join("new.txt")), None);
+        let mut changes = Hg.changed_files(temp.path()).unwrap();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            changes,
+            [
+                FileChange {
+                    path: file,
+                    status: FileStatus::Modified
+                },
+                FileChange {
+                    path: temp.path().join("new.txt"),
+                    status: FileStatus::Untracked
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::command::{command, find_repo_root, output};
+use crate::{DiffProvider, FileChange, FileStatus, INDEX_REVISION};
+
+/// Jujutsu repositories, through the `jj` command.
+pub struct Jujutsu;
+
+impl Jujutsu {
+    /// A `jj` command running in `root`, with the output unaffected by the user configuration.
+    fn jj(root: &Path) -> Command {
+        let mut command = command("jj", root);
+        command.args(["--no-pager", "--color=never"]);
+        command
+    }
+}
+
+impl DiffProvider for Jujutsu {
+    fn repo_root(&self, path: &Path) -> Option<PathBuf> {
+        find_repo_root(path, ".jj")
+    }
+
+    fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
+        // the working copy is a commit of its own, which is compared to its parent
+        self.get_file_at(file, "@-")
+    }
+
+    fn get_file_at(&self, file: &Path, revision: &str) -> Option<Vec<u8>> {
+        // jujutsu commits the working copy as a whole, without an index
+        if revision == INDEX_REVISION {
+            return None;
+        }
+        let root = self.repo_root(file)?;
+        let path = file.strip_prefix(&root).ok()?;
+        let mut command = Self::jj(&root);
+        command.args(["file", "show"]);
+        // committed files don't depend on the working copy, which isn't worth snapshotting
+        if !includes_working_copy(revision) {
+            command.arg("--ignore-working-copy");
+        }
+        output(command.args(["-r", revision, "--"]).arg(fileset(path)))
+    }
+
+    fn changed_files(&self, dir: &Path) -> Option<Vec<FileChange>> {
+        let root = self.repo_root(dir)?;
+        let output = output(Self::jj(&root).args(["diff", "--summary", "-r", "@"]))?;
+        Some(parse_summary(&root, &String::from_utf8_lossy(&output)))
+    }
+}
+
+/// Whether `revision` may refer to the working-copy commit `@`, which is only up to date once
+/// the working copy is snapshotted. Its ancestors like `@-` aren't affected by snapshots.
+fn includes_working_copy(revision: &str) -> bool {
+    match revision.trim().strip_prefix('@') {
+        Some(parents) => parents.is_empty() || parents.chars().any(|c| c != '-'),
+        None => revision.contains('@'),
+    }
+}
+
+/// The fileset matching exactly the file at `path`, relative to the root of the repository.
+fn fileset(path: &Path) -> String {
+    let path = path
+        .to_string_lossy()
+        .replace('\\', "/")
+        .replace('"', "\\\"");
+    format!("root-file:\"{}\"", path)
+}
+
+/// Parses the output of `jj diff --summary`, whose paths are relative to `root`.
+fn parse_summary(root: &Path, output: &str) -> Vec<FileChange> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (code, path) = line.split_once(' ')?;
+            let status = match code {
+                "M" => FileStatus::Modified,
+                "A" => FileStatus::Added,
+                "D" => FileStatus::Deleted,
+                "R" | "C" => FileStatus::Renamed,
+                _ => return None,
+            };
+            Some(FileChange {
+                path: root.join(rename_target(path)),
+                status,
+            })
+        })
+        .collect()
+}
+
+/// Renamed and copied files are shown as `dir/{old => new}`, from which the new path is taken.
+fn rename_target(path: &str) -> String {
+    let rename = path.find('{').and_then(|start| {
+        let end = start + path[start..].find('}')?;
+        let (_, new) = path[start + 1..end].split_once(" => ")?;
+        Some(format!("{}{}{}", &path[..start], new, &path[end + 1..]))
+    });
+    match rename {
+        // a path moved to a parent directory leaves an empty component
+        Some(rename) => rename.replace("//", "/"),
+        None => path.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn summary() {
+        let output = "M src/a.rs\nA b\nD c\nR src/{d.rs => e/d.rs}\nR {f => }g\n";
+        let changes: Vec<_> = parse_summary(Path::new("/repo"), output)
+            .into_iter()
+            .map(|change| (change.path, change.status))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (PathBuf::from("/repo/src/a.rs"), FileStatus::Modified),
+                (PathBuf::from("/repo/b"), FileStatus::Added),
+                (PathBuf::from("/repo/c"), FileStatus::Deleted),
+                (PathBuf::from("/repo/src/e/d.rs"), FileStatus::Renamed),
+                (PathBuf::from("/repo/g"), FileStatus::Renamed),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_fileset() {
+        assert_eq!(
+            fileset(Path::new("a/\"b\".txt")),
+            "root-file:\"a/\\\"b\\\".txt\""
+        );
+    }
+
+    #[test]
+    fn working_copy_revisions() {
+        assert!(includes_working_copy("@"));
+        assert!(includes_working_copy("@+"));
+        assert!(includes_working_copy("::@"));
+        assert!(!includes_working_copy("@-"));
+        assert!(!includes_working_copy("@--"));
+        assert!(!includes_working_copy("abc123"));
+    }
+
+    /// Runs `jj` in `dir`, or returns `false` if jujutsu isn't installed.
+    fn exec_jj_cmd(args: &[&str], dir: &Path) -> bool {
+        let status = match Command::new("jj")
+            .args(args)
+            .current_dir(dir)
+            .env("JJ_USER", "helix-test")
+            .env("JJ_EMAIL", "test@helix.org")
+            .status()
+        {
+            Ok(status) => status,
+            Err(_) => return false,
+        };
+        assert!(status.success(), "`jj {}` failed", args.join(" "));
+        true
+    }
+
+    #[test]
+    fn diff_base_and_status() {
+        let temp = tempfile::tempdir().unwrap();
+        if !exec_jj_cmd(&["git", "init"], temp.path()) {
+            return;
+        }
+        let file = temp.path().join("file.txt");
+        fs::write(&file, "foo").unwrap();
+        assert!(exec_jj_cmd(&["commit", "-m", "message"], temp.path()));
+        fs::write(&file, "bar").unwrap();
+        fs::write(temp.path().join("new.txt"), "baz").unwrap();
+
+        // checked before anything snapshots the working copy, which reading `@` has to do
+        assert_eq!(Jujutsu.get_file_at(&file, "@"), Some(b"bar".to_vec()));
+        assert_eq!(Jujutsu.get_diff_base(&file), Some(b"foo".to_vec()));
+
+        let mut changes = Jujutsu.changed_files(temp.path()).unwrap();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            changes,
+            [
+                FileChange {
+                    path: file.clone(),
+                    status: FileStatus::Modified
+                },
+                FileChange {
+                    path: temp.path().join("new.txt"),
+                    status: FileStatus::Added
+                },
+            ]
+        );
+    }
+}
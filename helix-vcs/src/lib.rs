@@ -1,4 +1,7 @@
-use std::path::Path;
+use std::cmp::Reverse;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[cfg(feature = "git")]
 pub use git::Git;
@@ -8,16 +11,79 @@
 #[cfg(feature = "git")]
 mod git;
 
+mod blame;
+mod command;
+mod conflict;
 mod diff;
+mod hg;
+mod jj;
+mod status;
 
-pub use diff::{DiffHandle, Hunk};
+pub use blame::{Blame, BlameCommit, CommitDiff};
+pub use conflict::{find_conflicts, Conflict};
+pub use diff::{
+    file_patch, hunk_patch, word_diff, DiffHandle, FileHunks, Hunk, InlineChange, InlineChanges,
+};
+pub use hg::Hg;
+pub use jj::Jujutsu;
+pub use status::{FileChange, FileStatus, RepoHead, RepoOperation, RepoStatus};
+
+/// The revision referring to the index of changes to commit.
+pub const INDEX_REVISION: &str = "index";
+
+pub trait DiffProvider: Send + Sync {
+    /// Returns the root of the repository of this provider containing `path`, if any.
+    fn repo_root(&self, _path: &Path) -> Option<PathBuf> {
+        None
+    }
 
-pub trait DiffProvider {
     /// Returns the data that a diff should be computed against
     /// if this provider is used.
     /// The data is returned as raw byte without any decoding or encoding performed
     /// to ensure all file encodings are handled correctly.
     fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>>;
+
+    /// Returns the contents of the file as of a revision, like a branch, a tag or a commit.
+    /// The revision [`INDEX_REVISION`] refers to the changes staged for the next commit.
+    fn get_file_at(&self, _file: &Path, _revision: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Returns the commit that last changed each line of the diff base.
+    fn blame(&self, _file: &Path) -> Option<Blame> {
+        None
+    }
+
+    /// Returns the commits that changed `file`, from the most recent one.
+    fn file_history(&self, _file: &Path) -> Option<Vec<BlameCommit>> {
+        None
+    }
+
+    /// Returns the message of `commit` and the changes it made to `file`.
+    fn commit_diff(&self, _file: &Path, _commit: &str) -> Option<CommitDiff> {
+        None
+    }
+
+    /// Returns the files that differ from the last commit in the repository containing `dir`,
+    /// or `None` if `dir` isn't in a repository of this provider.
+    fn changed_files(&self, _dir: &Path) -> Option<Vec<FileChange>> {
+        None
+    }
+
+    /// Returns the branch, upstream divergence and operation in progress of the repository
+    /// containing `dir`, or `None` if `dir` isn't in a repository of this provider.
+    fn repo_status(&self, _dir: &Path) -> Option<RepoStatus> {
+        None
+    }
+
+    /// Applies `patch`, hunks of `file` created with [`hunk_patch`], to the index of changes to
+    /// commit. With `reverse` the hunks are removed from the index instead.
+    fn apply_to_index(&self, _file: &Path, _patch: &str, _reverse: bool) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Staging is not supported",
+        ))
+    }
 }
 
 #[doc(hidden)]
@@ -28,24 +94,107 @@ fn get_diff_base(&self, _file: &Path) -> Option<Vec<u8>> {
     }
 }
 
+#[derive(Clone)]
 pub struct DiffProviderRegistry {
-    providers: Vec<Box<dyn DiffProvider>>,
+    providers: Vec<Arc<dyn DiffProvider>>,
 }
 
 impl DiffProviderRegistry {
-    pub fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
+    /// The provider of the innermost repository containing `path`. The first provider wins for
+    /// repositories sharing the same root, like git repositories colocated with jujutsu.
+    fn provider(&self, path: &Path) -> Option<&dyn DiffProvider> {
         self.providers
             .iter()
-            .find_map(|provider| provider.get_diff_base(file))
+            .filter_map(|provider| Some((provider.repo_root(path)?, provider)))
+            .min_by_key(|(root, _)| Reverse(root.components().count()))
+            .map(|(_, provider)| provider.as_ref())
+    }
+
+    pub fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
+        self.provider(file)?.get_diff_base(file)
+    }
+
+    pub fn get_file_at(&self, file: &Path, revision: &str) -> Option<Vec<u8>> {
+        self.provider(file)?.get_file_at(file, revision)
+    }
+
+    pub fn blame(&self, file: &Path) -> Option<Blame> {
+        self.provider(file)?.blame(file)
+    }
+
+    pub fn file_history(&self, file: &Path) -> Option<Vec<BlameCommit>> {
+        self.provider(file)?.file_history(file)
+    }
+
+    pub fn commit_diff(&self, file: &Path, commit: &str) -> Option<CommitDiff> {
+        self.provider(file)?.commit_diff(file, commit)
+    }
+
+    /// Returns the files that differ from the last commit in the repository containing `dir`.
+    pub fn changed_files(&self, dir: &Path) -> Vec<FileChange> {
+        self.provider(dir)
+            .and_then(|provider| provider.changed_files(dir))
+            .unwrap_or_default()
+    }
+
+    pub fn repo_status(&self, dir: &Path) -> Option<RepoStatus> {
+        self.provider(dir)?.repo_status(dir)
+    }
+
+    /// Applies `patch` to the index of the repository containing `file`.
+    pub fn apply_to_index(&self, file: &Path, patch: &str, reverse: bool) -> io::Result<()> {
+        let provider = self.provider(file).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "File is not in a repository")
+        })?;
+        provider.apply_to_index(file, patch, reverse)
     }
 }
 
 impl Default for DiffProviderRegistry {
     fn default() -> Self {
-        // currently only git is supported
-        // TODO make this configurable when more providers are added
-        let git: Box<dyn DiffProvider> = Box::new(Git);
-        let providers = vec![git];
+        // git comes first as it supports the most features in colocated repositories
+        // TODO make this configurable
+        let providers: Vec<Arc<dyn DiffProvider>> =
+            vec![Arc::new(Git), Arc::new(Jujutsu), Arc::new(Hg)];
         DiffProviderRegistry { providers }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A provider of the repositories marked by a directory named `marker`.
+    struct Marked(&'static str);
+
+    impl DiffProvider for Marked {
+        fn repo_root(&self, path: &Path) -> Option<PathBuf> {
+            command::find_repo_root(path, self.0)
+        }
+
+        fn get_diff_base(&self, _file: &Path) -> Option<Vec<u8>> {
+            Some(self.0.as_bytes().to_vec())
+        }
+    }
+
+    #[test]
+    fn innermost_repository() {
+        let temp = tempfile::tempdir().unwrap();
+        let inner = temp.path().join("inner");
+        std::fs::create_dir_all(inner.join(".a")).unwrap();
+        std::fs::create_dir_all(inner.join(".c")).unwrap();
+        std::fs::create_dir(temp.path().join(".b")).unwrap();
+        let registry = DiffProviderRegistry {
+            providers: vec![
+                Arc::new(Marked(".a")),
+                Arc::new(Marked(".b")),
+                Arc::new(Marked(".c")),
+            ],
+        };
+
+        let base = |path: &Path| registry.get_diff_base(path).map(String::from_utf8);
+        assert_eq!(base(&inner.join("file")), Some(Ok(".a".to_owned())));
+        assert_eq!(base(&temp.path().join("file")), Some(Ok(".b".to_owned())));
+        assert_eq!(base(Path::new("/")), None);
+    }
+}
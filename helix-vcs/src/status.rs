@@ -0,0 +1,239 @@
+use std::path::{Path, PathBuf};
+
+/// How a file differs from the last commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileStatus {
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    /// Not tracked by version control yet.
+    Untracked,
+    /// Conflicts are left in the file by a merge.
+    Conflicted,
+}
+
+impl FileStatus {
+    /// The letter marking the status, like in `git status --short`.
+    pub fn marker(&self) -> char {
+        match self {
+            Self::Modified => 'M',
+            Self::Added => 'A',
+            Self::Deleted => 'D',
+            Self::Renamed => 'R',
+            Self::Untracked => '?',
+            Self::Conflicted => 'U',
+        }
+    }
+}
+
+/// A file of the working directory that differs from the last commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// The absolute path of the file.
+    pub path: PathBuf,
+    pub status: FileStatus,
+}
+
+/// Parses the output of `git status --porcelain=v1 -z`, whose paths are relative to
+/// `work_dir`.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+pub(crate) fn parse_porcelain(work_dir: &Path, output: &[u8]) -> Vec<FileChange> {
+    let mut changes = Vec::new();
+    let mut entries = output
+        .split(|&byte| byte == 0)
+        .filter(|entry| !entry.is_empty());
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (x, y) = (entry[0], entry[1]);
+        let path = String::from_utf8_lossy(&entry[3..]);
+        let status = match (x, y) {
+            (b'?', b'?') => FileStatus::Untracked,
+            (b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D') => FileStatus::Conflicted,
+            (b'R' | b'C', _) => {
+                // the path the file was renamed or copied from follows
+                entries.next();
+                FileStatus::Renamed
+            }
+            (b'A', _) => FileStatus::Added,
+            (b'D', _) | (_, b'D') => FileStatus::Deleted,
+            _ => FileStatus::Modified,
+        };
+        changes.push(FileChange {
+            path: work_dir.join(path.as_ref()),
+            status,
+        });
+    }
+    changes
+}
+
+/// The commit checked out in a repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoHead {
+    /// The name of the branch checked out.
+    Branch(String),
+    /// The abbreviated id of the commit checked out without a branch.
+    Detached(String),
+}
+
+/// An operation left in progress in a repository, waiting for the user to continue it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoOperation {
+    Rebase,
+    Merge,
+    Bisect,
+    CherryPick,
+    Revert,
+}
+
+impl RepoOperation {
+    /// How the operation is shown in the prompt of git.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Rebase => "REBASE",
+            Self::Merge => "MERGING",
+            Self::Bisect => "BISECTING",
+            Self::CherryPick => "CHERRY-PICKING",
+            Self::Revert => "REVERTING",
+        }
+    }
+}
+
+/// The state of a repository as a whole.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoStatus {
+    pub head: RepoHead,
+    /// The number of commits of the branch that its upstream branch doesn't have.
+    pub ahead: usize,
+    /// The number of commits of the upstream branch that the branch doesn't have.
+    pub behind: usize,
+    pub operation: Option<RepoOperation>,
+}
+
+/// The length of abbreviated commit ids.
+const SHORT_ID_LEN: usize = 7;
+
+/// Parses the headers of the output of `git status --porcelain=v2 --branch`.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+pub(crate) fn parse_branch_headers(
+    output: &str,
+    operation: Option<RepoOperation>,
+) -> Option<RepoStatus> {
+    let mut oid = None;
+    let mut branch = None;
+    let (mut ahead, mut behind) = (0, 0);
+    for line in output.lines() {
+        let header = match line.strip_prefix("# branch.") {
+            Some(header) => header,
+            None => continue,
+        };
+        let (key, value) = header.split_once(' ')?;
+        match key {
+            "oid" => oid = Some(value),
+            "head" => branch = Some(value),
+            "ab" => {
+                for count in value.split(' ') {
+                    if let Some(count) = count.strip_prefix('+') {
+                        ahead = count.parse().ok()?;
+                    } else if let Some(count) = count.strip_prefix('-') {
+                        behind = count.parse().ok()?;
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    let head = match branch? {
+        "(detached)" => {
+            let oid = oid?;
+            RepoHead::Detached(oid[..oid.len().min(SHORT_ID_LEN)].to_owned())
+        }
+        branch => RepoHead::Branch(branch.to_owned()),
+    };
+    Some(RepoStatus {
+        head,
+        ahead,
+        behind,
+        operation,
+    })
+}
+
+/// Finds the operation in progress from the state files git leaves in `git_dir`.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+pub(crate) fn repo_operation(git_dir: &Path) -> Option<RepoOperation> {
+    let operations = [
+        ("rebase-merge", RepoOperation::Rebase),
+        ("rebase-apply", RepoOperation::Rebase),
+        ("MERGE_HEAD", RepoOperation::Merge),
+        ("CHERRY_PICK_HEAD", RepoOperation::CherryPick),
+        ("REVERT_HEAD", RepoOperation::Revert),
+        ("BISECT_LOG", RepoOperation::Bisect),
+    ];
+    operations
+        .into_iter()
+        .find(|(file, _)| git_dir.join(file).exists())
+        .map(|(_, operation)| operation)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn porcelain() {
+        let output = b"R  new\0old\0 M src/a.rs\0?? notes\0UU b\0A  c\0 D d\0";
+        let changes = parse_porcelain(Path::new("/repo"), output);
+        let statuses: Vec<_> = changes
+            .iter()
+            .map(|change| (change.path.to_str().unwrap(), change.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("/repo/new", FileStatus::Renamed),
+                ("/repo/src/a.rs", FileStatus::Modified),
+                ("/repo/notes", FileStatus::Untracked),
+                ("/repo/b", FileStatus::Conflicted),
+                ("/repo/c", FileStatus::Added),
+                ("/repo/d", FileStatus::Deleted),
+            ]
+        );
+    }
+
+    #[test]
+    fn branch_headers() {
+        let output = "# branch.oid 0123456789abcdef\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +2 -1\n1 .M N... 100644 100644 100644 a b file\n";
+        assert_eq!(
+            parse_branch_headers(output, None),
+            Some(RepoStatus {
+                head: RepoHead::Branch("main".to_owned()),
+                ahead: 2,
+                behind: 1,
+                operation: None,
+            })
+        );
+
+        let output = "# branch.oid 0123456789abcdef\n# branch.head (detached)\n";
+        assert_eq!(
+            parse_branch_headers(output, Some(RepoOperation::Rebase)),
+            Some(RepoStatus {
+                head: RepoHead::Detached("0123456".to_owned()),
+                ahead: 0,
+                behind: 0,
+                operation: Some(RepoOperation::Rebase),
+            })
+        );
+    }
+
+    #[test]
+    fn operation_in_progress() {
+        let git_dir = tempfile::tempdir().unwrap();
+        assert_eq!(repo_operation(git_dir.path()), None);
+        std::fs::write(git_dir.path().join("MERGE_HEAD"), "").unwrap();
+        assert_eq!(repo_operation(git_dir.path()), Some(RepoOperation::Merge));
+        std::fs::create_dir(git_dir.path().join("rebase-merge")).unwrap();
+        assert_eq!(repo_operation(git_dir.path()), Some(RepoOperation::Rebase));
+    }
+}
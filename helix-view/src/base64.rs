@@ -86,10 +86,39 @@ pub fn encode(input: &[u8]) -> String {
     String::from_utf8(output).expect("Invalid UTF8")
 }
 
+/// Decodes padded or unpadded base64, returning `None` if `input` isn't valid base64.
+pub fn decode(input: &str) -> Option<Vec<u8>> {
+    let input = input.trim_end_matches(PAD_BYTE as char).as_bytes();
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut chunk_int: u32 = 0;
+    for (i, byte) in input.iter().enumerate() {
+        let value = ENCODE_TABLE.iter().position(|b| b == byte)? as u32;
+        chunk_int = chunk_int.shl(6_u8).bitor(value);
+        // every 4 characters make 3 bytes
+        if i % 4 == 3 {
+            output.extend_from_slice(&[
+                chunk_int.shr(16_u8) as u8,
+                chunk_int.shr(8_u8) as u8,
+                chunk_int as u8,
+            ]);
+            chunk_int = 0;
+        }
+    }
+    // the leftover characters hold the high bits of 1 or 2 bytes
+    match input.len() % 4 {
+        0 => (),
+        2 => output.push(chunk_int.shr(4_u8) as u8),
+        3 => output.extend_from_slice(&[chunk_int.shr(10_u8) as u8, chunk_int.shr(2_u8) as u8]),
+        _ => return None,
+    }
+    Some(output)
+}
+
 #[cfg(test)]
 mod tests {
     fn compare_encode(expected: &str, target: &[u8]) {
         assert_eq!(expected, super::encode(target));
+        assert_eq!(Some(target.to_vec()), super::decode(expected));
     }
 
     #[test]
@@ -127,6 +156,12 @@ fn encode_rfc4648_6() {
         compare_encode("Zm9vYmFy", b"foobar");
     }
 
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(super::decode("Zm9v!"), None);
+        assert_eq!(super::decode("Zm9vY"), None);
+    }
+
     #[test]
     fn encode_all_ascii() {
         let mut ascii = Vec::<u8>::with_capacity(128);
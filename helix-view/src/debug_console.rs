@@ -0,0 +1,454 @@
+//! Output of the debugged program and the debug adapter, the results of the expressions
+//! evaluated in the debug console, the watched expressions and the variables of the current
+//! stack frame.
+
+use futures_util::{future::BoxFuture, FutureExt};
+use helix_dap as dap;
+use helix_lsp::block_on;
+use std::ops::Range;
+
+/// Number of entries kept before the oldest ones are dropped.
+const MAX_ENTRIES: usize = 10_000;
+/// Number of indexed children fetched at once, larger arrays are split into ranges.
+const PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputCategory {
+    Console,
+    Important,
+    Stdout,
+    Stderr,
+    Other,
+}
+
+impl OutputCategory {
+    /// Categories output can be filtered by, in the order they are cycled through.
+    pub const ALL: [Self; 5] = [
+        Self::Console,
+        Self::Important,
+        Self::Stdout,
+        Self::Stderr,
+        Self::Other,
+    ];
+
+    /// Parses the category of an `output` event, which defaults to `console`.
+    pub fn from_dap(category: Option<&str>) -> Self {
+        match category {
+            None | Some("console") => Self::Console,
+            Some("important") => Self::Important,
+            Some("stdout") => Self::Stdout,
+            Some("stderr") => Self::Stderr,
+            Some(_) => Self::Other,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Console => "console",
+            Self::Important => "important",
+            Self::Stdout => "stdout",
+            Self::Stderr => "stderr",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ConsoleEntry {
+    Output {
+        category: OutputCategory,
+        text: String,
+    },
+    /// An expression entered in the console.
+    Input(String),
+    /// The value an expression evaluated to, or structured output of the adapter.
+    Value(VariableNode),
+    Error(String),
+}
+
+/// A value reported by the debugger. Its children are fetched once it is expanded for the
+/// first time.
+#[derive(Debug, Clone)]
+pub struct VariableNode {
+    pub name: String,
+    pub value: String,
+    pub ty: Option<String>,
+    /// Reference to fetch the children with, `0` if the value has none.
+    pub variables_reference: usize,
+    /// Expression the value can be evaluated or changed with.
+    pub evaluate_name: Option<String>,
+    /// Number of indexed children, like the elements of an array.
+    pub indexed_variables: Option<usize>,
+    /// The indexed children of the parent this node groups, if it is a range of a large array
+    /// rather than a value.
+    pub range: Option<Range<usize>>,
+    pub children: Option<Vec<VariableNode>>,
+    pub expanded: bool,
+}
+
+impl VariableNode {
+    pub fn new(
+        name: String,
+        value: String,
+        ty: Option<String>,
+        variables_reference: usize,
+    ) -> Self {
+        Self {
+            name,
+            value,
+            ty,
+            variables_reference,
+            evaluate_name: None,
+            indexed_variables: None,
+            range: None,
+            children: None,
+            expanded: false,
+        }
+    }
+
+    pub fn is_expandable(&self) -> bool {
+        self.variables_reference > 0
+    }
+
+    /// Expands or collapses the node, fetching its children if they weren't yet.
+    pub fn toggle(&mut self, debugger: Option<&dap::Client>) -> anyhow::Result<()> {
+        if !self.is_expandable() {
+            return Ok(());
+        }
+        if !self.expanded && self.children.is_none() {
+            let debugger = debugger.ok_or_else(|| anyhow::anyhow!("No debug session running"))?;
+            self.children = Some(block_on(self.fetch_children(debugger))?);
+        }
+        self.expanded = !self.expanded;
+        Ok(())
+    }
+
+    /// Fetches the children of the node. Arrays with more than [`PAGE_SIZE`] elements are split
+    /// into ranges whose elements are only fetched once the range is expanded.
+    pub async fn fetch_children(&self, debugger: &dap::Client) -> anyhow::Result<Vec<Self>> {
+        let reference = self.variables_reference;
+        let range = match (&self.range, self.indexed_variables) {
+            (Some(range), _) => range.clone(),
+            (None, Some(len)) if len > PAGE_SIZE => {
+                let named = debugger.named_variables(reference).await?;
+                let mut children: Vec<_> = named.into_iter().map(Self::from).collect();
+                children.extend(self.ranges(0..len));
+                return Ok(children);
+            }
+            _ => {
+                let variables = debugger.variables(reference).await?;
+                return Ok(variables.into_iter().map(Self::from).collect());
+            }
+        };
+        if range.len() > PAGE_SIZE {
+            return Ok(self.ranges(range));
+        }
+        let variables = debugger
+            .indexed_variables(reference, range.start, range.len())
+            .await?;
+        Ok(variables.into_iter().map(Self::from).collect())
+    }
+
+    /// Splits the indexed children in `range` into at most [`PAGE_SIZE`] ranges, each again
+    /// containing a power of [`PAGE_SIZE`] children.
+    fn ranges(&self, range: Range<usize>) -> Vec<Self> {
+        let mut size = PAGE_SIZE;
+        while range.len() > size * PAGE_SIZE {
+            size *= PAGE_SIZE;
+        }
+        range
+            .clone()
+            .step_by(size)
+            .map(|start| {
+                let end = (start + size).min(range.end);
+                Self {
+                    range: Some(start..end),
+                    ..Self::new(
+                        format!("[{}..{}]", start, end),
+                        String::new(),
+                        None,
+                        self.variables_reference,
+                    )
+                }
+            })
+            .collect()
+    }
+
+    /// Expands the node and the same descendants that were expanded in `old`, a previous value
+    /// of the node, like the same variable before the debugger stopped again.
+    pub fn restore_expanded<'a>(
+        &'a mut self,
+        old: &'a VariableNode,
+        debugger: &'a dap::Client,
+    ) -> BoxFuture<'a, ()> {
+        async move {
+            if !old.expanded || !self.is_expandable() {
+                return;
+            }
+            let mut children = match self.fetch_children(debugger).await {
+                Ok(children) => children,
+                Err(_) => return,
+            };
+            for child in &mut children {
+                let old_child = old
+                    .children
+                    .iter()
+                    .flatten()
+                    .find(|old_child| old_child.name == child.name);
+                if let Some(old_child) = old_child {
+                    child.restore_expanded(old_child, debugger).await;
+                }
+            }
+            self.children = Some(children);
+            self.expanded = true;
+        }
+        .boxed()
+    }
+
+    /// Changes the value in the debugged program. Variables are set in their `container`, the
+    /// variables reference of their parent, and other values through their expression.
+    pub async fn set_value(
+        &mut self,
+        debugger: &dap::Client,
+        container: Option<usize>,
+        value: String,
+    ) -> anyhow::Result<()> {
+        let caps = debugger.caps.as_ref();
+        let supports_set_variable = caps
+            .and_then(|caps| caps.supports_set_variable)
+            .unwrap_or_default();
+        let supports_set_expression = caps
+            .and_then(|caps| caps.supports_set_expression)
+            .unwrap_or_default();
+        if self.range.is_some() {
+            anyhow::bail!("A range of values can't be changed");
+        }
+
+        let (value, ty, variables_reference, indexed_variables) = match container {
+            Some(container) if supports_set_variable => {
+                let response = debugger
+                    .set_variable(container, self.name.clone(), value)
+                    .await?;
+                (
+                    response.value,
+                    response.ty,
+                    response.variables_reference,
+                    response.indexed_variables,
+                )
+            }
+            _ => match &self.evaluate_name {
+                Some(expression) if supports_set_expression => {
+                    let frame_id = debugger.current_stack_frame().map(|frame| frame.id);
+                    let response = debugger
+                        .set_expression(expression.clone(), value, frame_id)
+                        .await?;
+                    (
+                        response.value,
+                        response.ty,
+                        response.variables_reference,
+                        response.indexed_variables,
+                    )
+                }
+                _ => anyhow::bail!("The debugger doesn't support changing this value"),
+            },
+        };
+
+        self.value = value;
+        if ty.is_some() {
+            self.ty = ty;
+        }
+        self.variables_reference = variables_reference.unwrap_or(0);
+        self.indexed_variables = indexed_variables;
+        self.children = None;
+        self.expanded = false;
+        Ok(())
+    }
+
+    /// Returns the descendant at `path`, a list of child indices.
+    pub fn get(&self, path: &[usize]) -> Option<&Self> {
+        match path.split_first() {
+            Some((&index, rest)) => self.children.as_ref()?.get(index)?.get(rest),
+            None => Some(self),
+        }
+    }
+
+    /// Returns the descendant at `path`, a list of child indices.
+    pub fn get_mut(&mut self, path: &[usize]) -> Option<&mut Self> {
+        match path.split_first() {
+            Some((&index, rest)) => self.children.as_mut()?.get_mut(index)?.get_mut(rest),
+            None => Some(self),
+        }
+    }
+}
+
+impl From<dap::Variable> for VariableNode {
+    fn from(variable: dap::Variable) -> Self {
+        Self {
+            evaluate_name: variable.evaluate_name,
+            indexed_variables: variable.indexed_variables,
+            ..Self::new(
+                variable.name,
+                variable.value,
+                variable.ty,
+                variable.variables_reference,
+            )
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DebugConsole {
+    pub entries: Vec<ConsoleEntry>,
+}
+
+impl DebugConsole {
+    pub fn push(&mut self, entry: ConsoleEntry) {
+        if self.entries.len() >= MAX_ENTRIES {
+            self.entries.drain(..MAX_ENTRIES / 10);
+        }
+        self.entries.push(entry);
+    }
+
+    /// Appends output, continuing the last entry if it is an unfinished line of the same category.
+    pub fn push_output(&mut self, category: OutputCategory, text: &str) {
+        if let Some(ConsoleEntry::Output {
+            category: last_category,
+            text: last_text,
+        }) = self.entries.last_mut()
+        {
+            if *last_category == category && !last_text.ends_with('\n') {
+                last_text.push_str(text);
+                return;
+            }
+        }
+        self.push(ConsoleEntry::Output {
+            category,
+            text: text.to_string(),
+        });
+    }
+
+    /// Evaluates `expression` in the context of the current stack frame and records it together
+    /// with its result.
+    pub fn evaluate(&mut self, debugger: Option<&dap::Client>, expression: String) {
+        self.push(ConsoleEntry::Input(expression.clone()));
+        let debugger = match debugger {
+            Some(debugger) => debugger,
+            None => {
+                self.push(ConsoleEntry::Error("No debug session running".to_string()));
+                return;
+            }
+        };
+        match block_on(evaluate(debugger, expression, "repl")) {
+            Ok(node) => self.push(ConsoleEntry::Value(node)),
+            Err(err) => self.push(ConsoleEntry::Error(err.to_string())),
+        }
+    }
+}
+
+/// Evaluates `expression` in the current stack frame. `context` tells the debugger where the
+/// expression comes from, like `repl` or `watch`.
+pub async fn evaluate(
+    debugger: &dap::Client,
+    expression: String,
+    context: &str,
+) -> anyhow::Result<VariableNode> {
+    let frame_id = debugger.current_stack_frame().map(|frame| frame.id);
+    let response = debugger
+        .eval(expression.clone(), frame_id, Some(context.to_string()))
+        .await?;
+    Ok(VariableNode {
+        evaluate_name: Some(expression),
+        indexed_variables: response.indexed_variables,
+        ..VariableNode::new(
+            String::new(),
+            response.result,
+            response.ty,
+            response.variables_reference,
+        )
+    })
+}
+
+/// An expression that is evaluated again whenever the debugger stops.
+#[derive(Debug, Clone)]
+pub struct Watch {
+    pub expression: String,
+    /// The value in the last stack frame it was evaluated in, `None` until it was.
+    pub value: Option<Result<VariableNode, String>>,
+}
+
+impl Watch {
+    pub fn new(expression: String) -> Self {
+        Self {
+            expression,
+            value: None,
+        }
+    }
+
+    /// Evaluates the expression in the current stack frame, expanding the new value like the
+    /// previous one was.
+    pub async fn evaluate(&mut self, debugger: &dap::Client) {
+        let mut value = match evaluate(debugger, self.expression.clone(), "watch").await {
+            // shown as `expression = value`
+            Ok(node) => Ok(VariableNode {
+                name: self.expression.clone(),
+                ..node
+            }),
+            Err(err) => Err(err.to_string()),
+        };
+        if let (Ok(node), Some(Ok(old))) = (&mut value, &self.value) {
+            node.restore_expanded(old, debugger).await;
+        }
+        self.value = Some(value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn output_continues_unfinished_lines() {
+        let mut console = DebugConsole::default();
+        console.push_output(OutputCategory::Stdout, "foo");
+        console.push_output(OutputCategory::Stdout, "bar\n");
+        console.push_output(OutputCategory::Stdout, "baz\n");
+        console.push_output(OutputCategory::Stderr, "qux");
+
+        let texts: Vec<_> = console
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                ConsoleEntry::Output { category, text } => (*category, text.as_str()),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                (OutputCategory::Stdout, "foobar\n"),
+                (OutputCategory::Stdout, "baz\n"),
+                (OutputCategory::Stderr, "qux"),
+            ]
+        );
+    }
+
+    #[test]
+    fn large_arrays_are_split_into_ranges() {
+        let names = |node: &VariableNode, range| -> Vec<String> {
+            node.ranges(range)
+                .into_iter()
+                .map(|node| node.name)
+                .collect()
+        };
+        let array = VariableNode::new("array".into(), String::new(), None, 1);
+        assert_eq!(
+            names(&array, 0..250),
+            ["[0..100]", "[100..200]", "[200..250]"]
+        );
+
+        let ranges = array.ranges(0..25_000);
+        assert_eq!(ranges.len(), 25);
+        assert_eq!(ranges[1].range, Some(1000..2000));
+        assert_eq!(names(&ranges[1], 1000..2000).len(), 10);
+    }
+}
@@ -0,0 +1,392 @@
+//! Read-only buffers showing the machine code and the memory of the program being debugged.
+
+use std::fmt::Write;
+
+use helix_core::{Selection, Transaction};
+use helix_dap::DisassembledInstruction;
+
+use crate::{editor::Action, Document, DocumentId, Editor};
+
+/// Number of instructions disassembled before the current one.
+const INSTRUCTIONS_BEFORE: usize = 32;
+/// Number of instructions disassembled in total around the current one.
+const INSTRUCTIONS: usize = 96;
+/// Number of bytes shown on each line of a memory view.
+const BYTES_PER_LINE: usize = 16;
+
+/// The buffer showing the instructions around the one the debugger is stopped at.
+#[derive(Debug)]
+pub struct Disassembly {
+    pub doc: DocumentId,
+    /// The address of the instruction on each line of the buffer, `None` for symbol labels.
+    pub addresses: Vec<Option<u64>>,
+    /// The line of the instruction the debugger is stopped at.
+    pub current_line: Option<usize>,
+}
+
+/// A buffer showing `count` bytes of memory, read again every time the debugger stops.
+#[derive(Debug, Clone)]
+pub struct MemoryView {
+    pub memory_reference: String,
+    pub count: usize,
+}
+
+/// Parses an address in hexadecimal, with or without its `0x` prefix.
+pub fn parse_address(address: &str) -> Option<u64> {
+    let address = address.trim();
+    let digits = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .unwrap_or(address);
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// Lists the instructions as `address  bytes  instruction`, with a label line before the
+/// instructions starting a symbol. Returns the text along with the address of each line.
+pub fn format_disassembly(instructions: &[DisassembledInstruction]) -> (String, Vec<Option<u64>>) {
+    let bytes_width = instructions
+        .iter()
+        .filter_map(|instruction| instruction.instruction_bytes.as_ref())
+        .map(|bytes| bytes.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut text = String::new();
+    let mut addresses = Vec::with_capacity(instructions.len());
+    let mut symbol = None;
+    for instruction in instructions {
+        if instruction.symbol.is_some() && instruction.symbol != symbol {
+            symbol = instruction.symbol.clone();
+            let _ = writeln!(text, "{}:", symbol.as_deref().unwrap_or_default());
+            addresses.push(None);
+        }
+        let _ = writeln!(
+            text,
+            "  {}  {:bytes_width$}  {}",
+            instruction.address,
+            instruction.instruction_bytes.as_deref().unwrap_or_default(),
+            instruction.instruction,
+        );
+        addresses.push(parse_address(&instruction.address));
+    }
+    (text, addresses)
+}
+
+/// Formats the bytes read at `address` as a hex dump of 16 bytes per line, followed by the
+/// `unreadable` bytes that couldn't be read shown as `??`.
+pub fn hex_dump(address: u64, bytes: &[u8], unreadable: usize) -> String {
+    let bytes: Vec<_> = bytes
+        .iter()
+        .copied()
+        .map(Some)
+        .chain(std::iter::repeat(None).take(unreadable))
+        .collect();
+
+    let mut text = String::new();
+    for (i, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+        let _ = write!(text, "{:016x} ", address + (i * BYTES_PER_LINE) as u64);
+        for byte in line {
+            match byte {
+                Some(byte) => {
+                    let _ = write!(text, " {:02x}", byte);
+                }
+                None => text.push_str(" ??"),
+            }
+        }
+        // align the characters of a shorter last line
+        for _ in line.len()..BYTES_PER_LINE {
+            text.push_str("   ");
+        }
+        text.push_str("  ");
+        text.extend(line.iter().map(|byte| match byte {
+            Some(byte) if byte.is_ascii_graphic() || *byte == b' ' => *byte as char,
+            _ => '.',
+        }));
+        text.push('\n');
+    }
+    text
+}
+
+impl Editor {
+    /// Opens the disassembly around the instruction the debugger is stopped at in a split, or
+    /// focuses it if it is already open.
+    pub async fn show_disassembly(&mut self) -> anyhow::Result<()> {
+        let debugger = match self.debuggers.get_active() {
+            Some(debugger) => debugger,
+            None => anyhow::bail!("Debugger is not running"),
+        };
+        if !debugger
+            .capabilities()
+            .supports_disassemble_request
+            .unwrap_or(false)
+        {
+            anyhow::bail!("Debugger does not support disassembling");
+        }
+        if debugger.current_stack_frame().is_none() {
+            anyhow::bail!("Debugger is not stopped");
+        }
+
+        let open = self
+            .disassembly
+            .as_ref()
+            .map(|disassembly| disassembly.doc)
+            .filter(|doc| self.documents.contains_key(doc));
+        match open {
+            Some(doc) => {
+                let view = self
+                    .tree
+                    .views()
+                    .find(|(view, _)| view.doc == doc)
+                    .map(|(view, _)| view.id);
+                match view {
+                    Some(view) => self.focus(view),
+                    None => self.switch(doc, Action::VerticalSplit),
+                }
+            }
+            None => {
+                let mut doc = Document::default();
+                doc.readonly = true;
+                let doc = self.new_file_from_document(Action::VerticalSplit, doc);
+                self.disassembly = Some(Disassembly {
+                    doc,
+                    addresses: Vec::new(),
+                    current_line: None,
+                });
+            }
+        }
+        self.refresh_disassembly().await;
+        Ok(())
+    }
+
+    /// Moves the disassembly to the instruction of the current stack frame, disassembling
+    /// around it again if it isn't listed already.
+    pub(crate) async fn refresh_disassembly(&mut self) {
+        let doc = match &self.disassembly {
+            Some(disassembly) => disassembly.doc,
+            None => return,
+        };
+        if !self.documents.contains_key(&doc) {
+            self.disassembly = None;
+            return;
+        }
+        let debugger = match self.debuggers.get_active() {
+            Some(debugger) => debugger,
+            None => return,
+        };
+        let reference = match debugger
+            .current_stack_frame()
+            .and_then(|frame| frame.instruction_pointer_reference.clone())
+        {
+            Some(reference) => reference,
+            None => {
+                if let Some(disassembly) = &mut self.disassembly {
+                    disassembly.current_line = None;
+                }
+                return;
+            }
+        };
+        let address = parse_address(&reference);
+
+        let listed = self.disassembly.as_ref().and_then(|disassembly| {
+            address.and_then(|address| {
+                disassembly
+                    .addresses
+                    .iter()
+                    .position(|line| *line == Some(address))
+            })
+        });
+        let current_line = match listed {
+            Some(line) => line,
+            None => {
+                let instructions = match debugger
+                    .disassemble(reference, -(INSTRUCTIONS_BEFORE as isize), INSTRUCTIONS)
+                    .await
+                {
+                    Ok(instructions) => instructions,
+                    Err(err) => {
+                        self.set_error(format!("Failed to disassemble: {}", err));
+                        return;
+                    }
+                };
+                let (text, addresses) = format_disassembly(&instructions);
+                let current_line = address
+                    .and_then(|address| addresses.iter().position(|line| *line == Some(address)));
+                self.set_readonly_text(doc, text);
+                let disassembly = self.disassembly.as_mut().unwrap();
+                disassembly.addresses = addresses;
+                match current_line {
+                    Some(line) => line,
+                    None => {
+                        disassembly.current_line = None;
+                        return;
+                    }
+                }
+            }
+        };
+        self.disassembly.as_mut().unwrap().current_line = Some(current_line);
+
+        let doc = self.documents.get_mut(&doc).unwrap();
+        let pos = doc.text().line_to_char(current_line);
+        for (view, _) in self.tree.views_mut() {
+            if view.doc == doc.id() {
+                doc.set_selection(view.id, Selection::point(pos));
+                view.ensure_cursor_in_view(doc, 0);
+            }
+        }
+    }
+
+    /// Opens a buffer showing `count` bytes of memory from `memory_reference` in a split.
+    pub async fn show_memory(
+        &mut self,
+        memory_reference: String,
+        count: usize,
+    ) -> anyhow::Result<()> {
+        let debugger = match self.debuggers.get_active() {
+            Some(debugger) => debugger,
+            None => anyhow::bail!("Debugger is not running"),
+        };
+        if !debugger
+            .capabilities()
+            .supports_read_memory_request
+            .unwrap_or(false)
+        {
+            anyhow::bail!("Debugger does not support reading memory");
+        }
+        let text = read_memory(debugger, &memory_reference, count).await?;
+
+        let mut doc = Document::default();
+        doc.readonly = true;
+        let doc = self.new_file_from_document(Action::VerticalSplit, doc);
+        self.set_readonly_text(doc, text);
+        self.memory_views.insert(
+            doc,
+            MemoryView {
+                memory_reference,
+                count,
+            },
+        );
+        Ok(())
+    }
+
+    /// Reads the memory shown by the memory views again.
+    pub(crate) async fn refresh_memory_views(&mut self) {
+        self.memory_views
+            .retain(|doc, _| self.documents.contains_key(doc));
+        let debugger = match self.debuggers.get_active() {
+            Some(debugger) => debugger,
+            None => return,
+        };
+        let mut texts = Vec::new();
+        for (doc, view) in &self.memory_views {
+            let text = read_memory(debugger, &view.memory_reference, view.count)
+                .await
+                .unwrap_or_else(|err| format!("Failed to read memory: {}\n", err));
+            texts.push((*doc, text));
+        }
+        for (doc, text) in texts {
+            self.set_readonly_text(doc, text);
+        }
+    }
+
+    /// Replaces the text of a read-only buffer, which isn't meant to be saved.
+    fn set_readonly_text(&mut self, doc_id: DocumentId, text: String) {
+        let view_id = self.tree.focus;
+        let doc = match self.documents.get_mut(&doc_id) {
+            Some(doc) => doc,
+            None => return,
+        };
+        doc.ensure_view_init(view_id);
+
+        let transaction = Transaction::change(
+            doc.text(),
+            std::iter::once((0, doc.text().len_chars(), Some(text.into()))),
+        );
+        doc.readonly = false;
+        doc.apply(&transaction, view_id);
+        doc.readonly = true;
+        doc.append_changes_to_history(self.tree.get_mut(view_id));
+        doc.reset_modified();
+    }
+}
+
+async fn read_memory(
+    debugger: &helix_dap::Client,
+    memory_reference: &str,
+    count: usize,
+) -> anyhow::Result<String> {
+    let response = debugger
+        .read_memory(memory_reference.to_string(), count)
+        .await?;
+    let address = parse_address(&response.address)
+        .ok_or_else(|| anyhow::anyhow!("Invalid address {}", response.address))?;
+    let bytes = match &response.data {
+        Some(data) => {
+            crate::base64::decode(data).ok_or_else(|| anyhow::anyhow!("Invalid memory data"))?
+        }
+        None => Vec::new(),
+    };
+    let text = hex_dump(address, &bytes, response.unreadable_bytes.unwrap_or(0));
+    Ok(if text.is_empty() {
+        format!("{:016x}  unreadable\n", address)
+    } else {
+        text
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn instruction(address: &str, symbol: Option<&str>, text: &str) -> DisassembledInstruction {
+        DisassembledInstruction {
+            address: address.to_string(),
+            instruction_bytes: Some("55".to_string()),
+            instruction: text.to_string(),
+            symbol: symbol.map(String::from),
+            location: None,
+            line: None,
+            column: None,
+            end_line: None,
+            end_column: None,
+        }
+    }
+
+    #[test]
+    fn addresses_are_parsed() {
+        assert_eq!(parse_address("0x1000"), Some(0x1000));
+        assert_eq!(parse_address("7FFF0010"), Some(0x7fff_0010));
+        assert_eq!(parse_address("main+4"), None);
+    }
+
+    #[test]
+    fn disassembly_lists_symbols() {
+        let (text, addresses) = format_disassembly(&[
+            instruction("0x1000", Some("main"), "push rbp"),
+            instruction("0x1001", Some("main"), "ret"),
+            instruction("0x1002", None, "nop"),
+        ]);
+        assert_eq!(
+            text,
+            "main:\n  0x1000  55  push rbp\n  0x1001  55  ret\n  0x1002  55  nop\n"
+        );
+        assert_eq!(addresses, [None, Some(0x1000), Some(0x1001), Some(0x1002)]);
+    }
+
+    #[test]
+    fn memory_is_dumped() {
+        let bytes: Vec<u8> = (b'a'..=b'r').collect();
+        let text = hex_dump(0x10, &bytes, 1);
+        let mut lines = text.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "0000000000000010  61 62 63 64 65 66 67 68 69 6a 6b 6c 6d 6e 6f 70  abcdefghijklmnop"
+            )
+        );
+        assert_eq!(
+            lines.next(),
+            Some(format!("0000000000000020  71 72 ??{}  qr.", "   ".repeat(13)).as_str())
+        );
+        assert_eq!(lines.next(), None);
+    }
+}
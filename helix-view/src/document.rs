@@ -3,7 +3,7 @@
 use futures_util::FutureExt;
 use helix_core::auto_pairs::AutoPairs;
 use helix_core::Range;
-use helix_vcs::{DiffHandle, DiffProviderRegistry};
+use helix_vcs::{Blame, BlameCommit, Conflict, DiffHandle, DiffProviderRegistry, RepoStatus};
 
 use serde::de::{self, Deserialize, Deserializer};
 use serde::Serialize;
@@ -22,8 +22,8 @@
     indent::{auto_detect_indent_style, IndentStyle},
     line_ending::auto_detect_line_ending,
     syntax::{self, LanguageConfiguration},
-    ChangeSet, Diagnostic, LineEnding, Rope, RopeBuilder, Selection, Syntax, Transaction,
-    DEFAULT_LINE_ENDING,
+    ChangeSet, Diagnostic, LineEnding, Operation, Rope, RopeBuilder, Selection, Syntax,
+    Transaction, DEFAULT_LINE_ENDING,
 };
 
 use crate::editor::RedrawHandle;
@@ -41,6 +41,7 @@ pub enum Mode {
     Normal = 0,
     Select = 1,
     Insert = 2,
+    Terminal = 3,
 }
 
 impl Display for Mode {
@@ -49,6 +50,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             Mode::Normal => f.write_str("normal"),
             Mode::Select => f.write_str("select"),
             Mode::Insert => f.write_str("insert"),
+            Mode::Terminal => f.write_str("terminal"),
         }
     }
 }
@@ -61,6 +63,7 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
             "normal" => Ok(Mode::Normal),
             "select" => Ok(Mode::Select),
             "insert" => Ok(Mode::Insert),
+            "terminal" => Ok(Mode::Terminal),
             _ => bail!("Invalid mode '{}'", s),
         }
     }
@@ -86,6 +89,34 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     }
 }
 
+/// What a document is compared to by the diff gutter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffBase {
+    /// The file as of the last commit.
+    Head,
+    /// The file as of a revision of the version control system, like a branch or a commit.
+    Revision(String),
+    /// Another document, along with its version when it was last compared.
+    Document(DocumentId, i32),
+}
+
+impl Default for DiffBase {
+    fn default() -> Self {
+        Self::Head
+    }
+}
+
+impl DiffBase {
+    /// Reads the diff base of the file at `path`. Documents are not read from the providers.
+    pub fn load(&self, path: &Path, provider_registry: &DiffProviderRegistry) -> Option<Vec<u8>> {
+        match self {
+            Self::Head => provider_registry.get_diff_base(path),
+            Self::Revision(revision) => provider_registry.get_file_at(path, revision),
+            Self::Document(..) => None,
+        }
+    }
+}
+
 /// A snapshot of the text of a document that we want to write out to disk
 #[derive(Debug, Clone)]
 pub struct DocumentSavedEvent {
@@ -107,6 +138,9 @@ pub struct Document {
     encoding: &'static encoding::Encoding,
 
     pub restore_cursor: bool,
+    /// Edits through transactions are refused, for buffers whose text is generated rather than
+    /// written by the user. Programs can still change it with [`Document::apply_output`].
+    pub readonly: bool,
 
     /// Current indent style.
     pub indent_style: IndentStyle,
@@ -138,6 +172,18 @@ pub struct Document {
     language_server: Option<Arc<helix_lsp::Client>>,
 
     diff_handle: Option<DiffHandle>,
+    /// What the document is compared to by `diff_handle`.
+    pub diff_base: DiffBase,
+    /// The conflicts left in the document by a merge.
+    conflicts: Vec<Conflict>,
+    /// The commits that last changed the lines of the diff base, if the blame is shown.
+    blame: Option<Blame>,
+    /// The state of the repository containing the document, if it's shown in the statusline.
+    repo_status: Option<RepoStatus>,
+
+    /// The text before the first edit that wasn't taken by [`Document::take_edits`] yet, and the
+    /// changes applied since. Only recorded while enabled with [`Document::set_track_edits`].
+    tracked_edits: Option<(Rope, ChangeSet)>,
 }
 
 use std::{fmt, mem};
@@ -355,6 +401,7 @@ pub fn from(text: Rope, encoding: Option<&'static encoding::Encoding>) -> Self {
         let encoding = encoding.unwrap_or(encoding::UTF_8);
         let changes = ChangeSet::new(&text);
         let old_state = None;
+        let conflicts = helix_vcs::find_conflicts(text.slice(..));
 
         Self {
             id: DocumentId::default(),
@@ -365,6 +412,7 @@ pub fn from(text: Rope, encoding: Option<&'static encoding::Encoding>) -> Self {
             indent_style: DEFAULT_INDENT,
             line_ending: DEFAULT_LINE_ENDING,
             restore_cursor: false,
+            readonly: false,
             syntax: None,
             language: None,
             changes,
@@ -377,6 +425,11 @@ pub fn from(text: Rope, encoding: Option<&'static encoding::Encoding>) -> Self {
             modified_since_accessed: false,
             language_server: None,
             diff_handle: None,
+            diff_base: DiffBase::Head,
+            conflicts,
+            blame: None,
+            repo_status: None,
+            tracked_edits: None,
         }
     }
 
@@ -656,9 +709,12 @@ pub fn reload(
 
         self.detect_indent_and_line_ending();
 
-        match provider_registry.get_diff_base(&path) {
-            Some(diff_base) => self.set_diff_base(diff_base, redraw_handle),
-            None => self.diff_handle = None,
+        // a document compared to another one keeps comparing to it
+        if !matches!(self.diff_base, DiffBase::Document(..)) {
+            match self.diff_base.load(&path, provider_registry) {
+                Some(diff_base) => self.set_diff_base(diff_base, redraw_handle),
+                None => self.diff_handle = None,
+            }
         }
 
         Ok(())
@@ -802,6 +858,14 @@ fn apply_impl(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
 
         if !transaction.changes().is_empty() {
             self.version += 1;
+            // only look for conflicts again while there are some, or when a change like a
+            // reload inserts a conflict marker
+            let inserts_conflict = transaction.changes().changes().iter().any(
+                |change| matches!(change, Operation::Insert(text) if text.contains("<<<<<<<")),
+            );
+            if inserts_conflict || !self.conflicts.is_empty() {
+                self.conflicts = helix_vcs::find_conflicts(self.text.slice(..));
+            }
             // start computing the diff in parallel
             if let Some(diff_handle) = &self.diff_handle {
                 diff_handle.update_document(self.text.clone(), false);
@@ -815,6 +879,13 @@ fn apply_impl(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
                 });
             }
 
+            // remember the edits for positions tracked outside of the document
+            if let Some((_, changes)) = &mut self.tracked_edits {
+                take_with(changes, |changes| {
+                    changes.compose(transaction.changes().clone())
+                });
+            }
+
             // update tree-sitter syntax tree
             if let Some(syntax) = &mut self.syntax {
                 // TODO: no unwrap
@@ -853,6 +924,10 @@ fn apply_impl(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
 
     /// Apply a [`Transaction`] to the [`Document`] to change its text.
     pub fn apply(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
+        if self.readonly && !transaction.changes().is_empty() {
+            return false;
+        }
+
         // store the state just before any changes are made. This allows us to undo to the
         // state just before a transaction was applied.
         if self.changes.is_empty() && !transaction.changes().is_empty() {
@@ -873,6 +948,14 @@ pub fn apply(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
         success
     }
 
+    /// Applies changes that come from a program rather than the user, like the output of a
+    /// process. They are applied to read-only documents too, and aren't recorded in the
+    /// history so they can't be undone.
+    pub fn apply_output(&mut self, changes: ChangeSet) -> bool {
+        // without a selection in the transaction, no view is affected in particular
+        self.apply_impl(&Transaction::from(changes), ViewId::default())
+    }
+
     fn undo_redo_impl(&mut self, view: &mut View, undo: bool) -> bool {
         let mut history = self.history.take();
         let txn = if undo { history.undo() } else { history.redo() };
@@ -1066,19 +1149,79 @@ pub fn diff_handle(&self) -> Option<&DiffHandle> {
         self.diff_handle.as_ref()
     }
 
+    /// The conflicts left in the document by a merge, in order.
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    /// The blame, which is only shown while the document is compared to the last commit that
+    /// it was computed for.
+    pub fn blame(&self) -> Option<&Blame> {
+        match self.diff_base {
+            DiffBase::Head => self.blame.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn set_blame(&mut self, blame: Option<Blame>) {
+        self.blame = blame;
+    }
+
+    pub fn repo_status(&self) -> Option<&RepoStatus> {
+        self.repo_status.as_ref()
+    }
+
+    pub fn set_repo_status(&mut self, repo_status: Option<RepoStatus>) {
+        self.repo_status = repo_status;
+    }
+
+    /// Returns the commit that last changed `line`, mapping the line through the uncommitted
+    /// changes. Lines with uncommitted changes have no commit.
+    pub fn line_blame(&self, line: usize) -> Option<&BlameCommit> {
+        let blame = self.blame()?;
+        let hunks = self.diff_handle.as_ref().map(|differ| differ.hunks());
+        blame.line_commit(hunks.as_ref(), line as u32)
+    }
+
     /// Intialize/updates the differ for this document with a new base.
     pub fn set_diff_base(&mut self, diff_base: Vec<u8>, redraw_handle: RedrawHandle) {
         if let Ok((diff_base, _)) = from_reader(&mut diff_base.as_slice(), Some(self.encoding)) {
-            if let Some(differ) = &self.diff_handle {
-                differ.update_diff_base(diff_base);
-                return;
-            }
-            self.diff_handle = Some(DiffHandle::new(diff_base, self.text.clone(), redraw_handle))
+            self.set_diff_base_text(diff_base, redraw_handle);
         } else {
             self.diff_handle = None;
         }
     }
 
+    /// Compares the document to `diff_base` from now on, reading it from the providers. Returns
+    /// `false` and keeps comparing to the current diff base if the new one can't be read.
+    pub fn load_diff_base(
+        &mut self,
+        diff_base: DiffBase,
+        provider_registry: &DiffProviderRegistry,
+        redraw_handle: RedrawHandle,
+    ) -> bool {
+        let text = self
+            .path()
+            .and_then(|path| diff_base.load(path, provider_registry));
+        match text {
+            Some(text) => self.set_diff_base(text, redraw_handle),
+            // files that aren't tracked have no diff against the last commit
+            None if diff_base == DiffBase::Head => self.diff_handle = None,
+            None => return false,
+        }
+        self.diff_base = diff_base;
+        true
+    }
+
+    /// Intialize/updates the differ for this document with a new base that is already decoded.
+    pub fn set_diff_base_text(&mut self, diff_base: Rope, redraw_handle: RedrawHandle) {
+        if let Some(differ) = &self.diff_handle {
+            differ.update_diff_base(diff_base);
+            return;
+        }
+        self.diff_handle = Some(DiffHandle::new(diff_base, self.text.clone(), redraw_handle))
+    }
+
     #[inline]
     /// Tree-sitter AST tree
     pub fn syntax(&self) -> Option<&Syntax> {
@@ -1160,6 +1303,28 @@ pub fn position(
         )
     }
 
+    /// Starts or stops recording the edits applied to the document, for positions kept outside
+    /// of it to be mapped through them.
+    pub fn set_track_edits(&mut self, track: bool) {
+        if !track {
+            self.tracked_edits = None;
+        } else if self.tracked_edits.is_none() {
+            self.tracked_edits = Some((self.text.clone(), ChangeSet::new(&self.text)));
+        }
+    }
+
+    /// Returns the text before the edits applied since the last call together with those edits,
+    /// if they are tracked and there are any.
+    pub fn take_edits(&mut self) -> Option<(Rope, ChangeSet)> {
+        let (old_text, changes) = self.tracked_edits.as_mut()?;
+        if changes.is_empty() {
+            return None;
+        }
+        let old_text = mem::replace(old_text, self.text.clone());
+        let changes = mem::replace(changes, ChangeSet::new(&self.text));
+        Some((old_text, changes))
+    }
+
     #[inline]
     pub fn diagnostics(&self) -> &[Diagnostic] {
         &self.diagnostics
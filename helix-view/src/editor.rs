@@ -1,15 +1,19 @@
 use crate::{
     align_view,
     clipboard::{get_clipboard_provider, ClipboardProvider},
-    document::{DocumentSavedEventFuture, DocumentSavedEventResult, Mode},
+    debug_console::{DebugConsole, VariableNode, Watch},
+    debug_memory::{Disassembly, MemoryView},
+    document::{DiffBase, DocumentSavedEventFuture, DocumentSavedEventResult, Mode},
     graphics::{CursorKind, Rect},
     info::Info,
     input::KeyEvent,
+    repl::{Repl, ReplEvent},
+    terminal::{Terminal, TerminalEvent},
     theme::{self, Theme},
     tree::{self, Tree},
     Align, Document, DocumentId, View, ViewId,
 };
-use helix_vcs::DiffProviderRegistry;
+use helix_vcs::{Blame, DiffProviderRegistry, RepoStatus};
 
 use futures_util::stream::select_all::SelectAll;
 use futures_util::{future, StreamExt};
@@ -18,7 +22,7 @@
 
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     io::stdin,
     num::NonZeroUsize,
     path::{Path, PathBuf},
@@ -383,6 +387,7 @@ pub struct ModeConfig {
     pub normal: String,
     pub insert: String,
     pub select: String,
+    pub terminal: String,
 }
 
 impl Default for ModeConfig {
@@ -391,6 +396,7 @@ fn default() -> Self {
             normal: String::from("NOR"),
             insert: String::from("INS"),
             select: String::from("SEL"),
+            terminal: String::from("TER"),
         }
     }
 }
@@ -443,6 +449,10 @@ pub enum StatusLineElement {
     /// The total line numbers of the current file
     TotalLineNumbers,
 
+    /// The branch, upstream divergence and operation in progress of the repository, and the
+    /// number of changes to the file
+    VersionControl,
+
     /// A single space
     Spacer,
 }
@@ -553,6 +563,8 @@ pub enum GutterType {
     Spacer,
     /// Highlight local changes
     Diff,
+    /// Show the author and date of the commit that last changed each line
+    Blame,
 }
 
 impl std::str::FromStr for GutterType {
@@ -564,6 +576,7 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
             "spacer" => Ok(Self::Spacer),
             "line-numbers" => Ok(Self::LineNumbers),
             "diff" => Ok(Self::Diff),
+            "blame" => Ok(Self::Blame),
             _ => anyhow::bail!("Gutter type can only be `diagnostics` or `line-numbers`."),
         }
     }
@@ -742,17 +755,88 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct Breakpoint {
-    pub id: Option<usize>,
-    pub verified: bool,
-    pub message: Option<String>,
+    /// The state reported by each debug session, by session id. The sessions don't outlive the
+    /// editor.
+    #[serde(skip)]
+    pub states: HashMap<usize, BreakpointState>,
 
     pub line: usize,
-    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub hit_condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub log_message: Option<String>,
+    /// Disabled breakpoints are kept around but not sent to the debugger.
+    #[serde(default = "default_breakpoint_enabled")]
+    pub enabled: bool,
+    /// Set to run to a line, and removed once the debugger stops. They are not persisted.
+    #[serde(skip)]
+    pub temporary: bool,
+}
+
+fn default_breakpoint_enabled() -> bool {
+    true
+}
+
+impl Default for Breakpoint {
+    fn default() -> Self {
+        Self {
+            states: HashMap::new(),
+            line: 0,
+            condition: None,
+            hit_condition: None,
+            log_message: None,
+            enabled: true,
+            temporary: false,
+        }
+    }
+}
+
+impl Breakpoint {
+    /// Whether the debug session `id` could set the breakpoint.
+    pub fn verified(&self, id: Option<usize>) -> bool {
+        id.and_then(|id| self.states.get(&id))
+            .map_or(false, |state| state.verified)
+    }
+}
+
+/// The state of a breakpoint in a debug session.
+#[derive(Debug, Clone, Default)]
+pub struct BreakpointState {
+    pub id: Option<usize>,
+    pub verified: bool,
+    pub message: Option<String>,
+    pub column: Option<usize>,
+}
+
+/// A breakpoint on the function with the given name.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionBreakpoint {
+    pub name: String,
+    pub condition: Option<String>,
+    pub verified: bool,
+}
+
+/// A breakpoint on a value of the debugged program, which is only valid for the debug session
+/// it was set in.
+#[derive(Debug, Clone)]
+pub struct DataBreakpoint {
+    pub data_id: String,
+    /// Describes the value to the user, as reported by the debugger.
+    pub description: String,
+    pub verified: bool,
+}
+
+/// An exception filter advertised by the debugger that is enabled.
+#[derive(Debug, Clone)]
+pub struct ExceptionBreakpoint {
+    pub filter: String,
+    /// Only break on the exceptions matching this condition, if the filter supports it.
+    pub condition: Option<String>,
 }
 
 use futures_util::stream::{Flatten, Once};
@@ -778,10 +862,54 @@ pub struct Editor {
     pub language_servers: helix_lsp::Registry,
     pub diagnostics: BTreeMap<lsp::Url, Vec<lsp::Diagnostic>>,
     pub diff_providers: DiffProviderRegistry,
-
-    pub debugger: Option<dap::Client>,
-    pub debugger_events: SelectAll<UnboundedReceiverStream<dap::Payload>>,
+    /// Blames computed in the background, for the documents they were computed for.
+    pub blame_events: (
+        UnboundedSender<(DocumentId, Option<Blame>)>,
+        UnboundedReceiver<(DocumentId, Option<Blame>)>,
+    ),
+    /// Repository states computed in the background, for the documents they were computed for.
+    pub repo_status_events: (
+        UnboundedSender<(DocumentId, Option<RepoStatus>)>,
+        UnboundedReceiver<(DocumentId, Option<RepoStatus>)>,
+    ),
+
+    pub debuggers: dap::Registry,
     pub breakpoints: HashMap<PathBuf, Vec<Breakpoint>>,
+    /// Files whose breakpoints moved with edits and weren't sent to the debuggers yet.
+    pub moved_breakpoints: HashSet<PathBuf>,
+    pub function_breakpoints: Vec<FunctionBreakpoint>,
+    /// Data breakpoints by the id of the debug session they were set in.
+    pub data_breakpoints: HashMap<usize, Vec<DataBreakpoint>>,
+    pub exception_breakpoints: Vec<ExceptionBreakpoint>,
+    pub debug_console: DebugConsole,
+    pub watches: Vec<Watch>,
+    /// Scopes of the current stack frame, with the variables in them once expanded.
+    pub variables: Vec<VariableNode>,
+    /// Values of the variables in scope of the current stack frame, by name.
+    pub inline_values: HashMap<String, String>,
+    /// Whether `inline_values` are shown next to the lines using the variables.
+    pub show_inline_values: bool,
+    /// The buffer showing the disassembly around the current instruction.
+    pub disassembly: Option<Disassembly>,
+    /// Buffers showing memory of the debuggee, read again whenever it stops.
+    pub memory_views: HashMap<DocumentId, MemoryView>,
+
+    /// Documents listing global search results whose edits are written back to the
+    /// original files, with the lines they list keyed by their `path:line` prefix.
+    pub search_buffers: HashMap<DocumentId, HashMap<String, SearchBufferLine>>,
+
+    /// Terminal emulators, keyed by the view they are displayed in.
+    pub terminals: HashMap<ViewId, Terminal>,
+    pub terminal_events: (
+        UnboundedSender<TerminalEvent>,
+        UnboundedReceiver<TerminalEvent>,
+    ),
+    /// The terminal that was focused last, which selections are sent to.
+    pub last_terminal: Option<ViewId>,
+
+    /// Running interpreters, keyed by the language they were started for.
+    pub repls: HashMap<String, Repl>,
+    pub repl_events: (UnboundedSender<ReplEvent>, UnboundedReceiver<ReplEvent>),
 
     pub clipboard_provider: Box<dyn ClipboardProvider>,
 
@@ -817,12 +945,24 @@ pub struct Editor {
 
 pub type RedrawHandle = (Arc<Notify>, Arc<RwLock<()>>);
 
+/// A line of a file listed in a search results buffer.
+#[derive(Debug, Clone)]
+pub struct SearchBufferLine {
+    pub path: PathBuf,
+    /// 0 indexed line
+    pub line_num: usize,
+    /// Contents of the line, including its line ending, as they were last written.
+    pub line: String,
+}
+
 #[derive(Debug)]
 pub enum EditorEvent {
     DocumentSaved(DocumentSavedEventResult),
     ConfigEvent(ConfigEvent),
     LanguageServerMessage((usize, Call)),
-    DebuggerEvent(dap::Payload),
+    DebuggerEvent((usize, dap::Payload)),
+    TerminalEvent(TerminalEvent),
+    ReplEvent(ReplEvent),
     IdleTimer,
 }
 
@@ -890,9 +1030,27 @@ pub fn new(
             language_servers: helix_lsp::Registry::new(),
             diagnostics: BTreeMap::new(),
             diff_providers: DiffProviderRegistry::default(),
-            debugger: None,
-            debugger_events: SelectAll::new(),
+            blame_events: unbounded_channel(),
+            repo_status_events: unbounded_channel(),
+            debuggers: dap::Registry::new(),
             breakpoints: HashMap::new(),
+            moved_breakpoints: HashSet::new(),
+            function_breakpoints: Vec::new(),
+            data_breakpoints: HashMap::new(),
+            exception_breakpoints: Vec::new(),
+            debug_console: DebugConsole::default(),
+            watches: Vec::new(),
+            variables: Vec::new(),
+            inline_values: HashMap::new(),
+            show_inline_values: true,
+            disassembly: None,
+            memory_views: HashMap::new(),
+            search_buffers: HashMap::new(),
+            terminals: HashMap::new(),
+            terminal_events: unbounded_channel(),
+            last_terminal: None,
+            repls: HashMap::new(),
+            repl_events: unbounded_channel(),
             syn_loader,
             theme_loader,
             last_theme: None,
@@ -1144,6 +1302,11 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
                     }
                 }
 
+                // the terminal of the view goes away along with its buffer
+                if self.tree.get(view_id).doc != id {
+                    self.terminals.remove(&view_id);
+                }
+
                 self.replace_document_in_view(view_id, id);
 
                 return;
@@ -1180,7 +1343,7 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
     }
 
     /// Generate an id for a new document and register it.
-    fn new_document(&mut self, mut doc: Document) -> DocumentId {
+    pub(crate) fn new_document(&mut self, mut doc: Document) -> DocumentId {
         let id = self.next_document_id;
         // Safety: adding 1 from 1 is fine, probably impossible to reach usize max
         self.next_document_id =
@@ -1197,12 +1360,36 @@ fn new_document(&mut self, mut doc: Document) -> DocumentId {
         id
     }
 
-    fn new_file_from_document(&mut self, action: Action, doc: Document) -> DocumentId {
+    pub fn new_file_from_document(&mut self, action: Action, doc: Document) -> DocumentId {
         let id = self.new_document(doc);
         self.switch(id, action);
         id
     }
 
+    /// Opens a terminal running `command`, or the default shell if it is empty, in a new view
+    /// and enters terminal mode.
+    pub fn open_terminal(&mut self, action: Action, command: &[String]) -> Result<ViewId, Error> {
+        let doc_id = self.new_file(action);
+        let view_id = self.tree.focus;
+        // leave a line for the statusline
+        let area = self.tree.get(view_id).area;
+        let size = (area.height.saturating_sub(1).max(1), area.width.max(1));
+        let cwd = std::env::current_dir()?;
+
+        let events = self.terminal_events.0.clone();
+        let terminal = match Terminal::spawn(view_id, command, &cwd, size, events) {
+            Ok(terminal) => terminal,
+            Err(err) => {
+                let _ = self.close_document(doc_id, true);
+                return Err(err);
+            }
+        };
+        self.terminals.insert(view_id, terminal);
+        self.last_terminal = Some(view_id);
+        self.mode = Mode::Terminal;
+        Ok(view_id)
+    }
+
     pub fn new_file(&mut self, action: Action) -> DocumentId {
         self.new_file_from_document(action, Document::default())
     }
@@ -1223,10 +1410,18 @@ pub fn open(&mut self, path: &Path, action: Action) -> Result<DocumentId, Error>
             let mut doc = Document::open(&path, None, Some(self.syn_loader.clone()))?;
 
             let _ = Self::launch_language_server(&mut self.language_servers, &mut doc);
+            doc.set_track_edits(
+                self.breakpoints
+                    .get(&path)
+                    .map_or(false, |breakpoints| !breakpoints.is_empty()),
+            );
             if let Some(diff_base) = self.diff_providers.get_diff_base(&path) {
                 doc.set_diff_base(diff_base, self.redraw_handle.clone());
             }
-            self.new_document(doc)
+            let id = self.new_document(doc);
+            self.refresh_blame(id);
+            self.refresh_repo_status(id);
+            id
         };
 
         self.switch(id, action);
@@ -1234,6 +1429,12 @@ pub fn open(&mut self, path: &Path, action: Action) -> Result<DocumentId, Error>
     }
 
     pub fn close(&mut self, id: ViewId) {
+        // Dropping the terminal kills its process.
+        self.terminals.remove(&id);
+        if self.last_terminal == Some(id) {
+            self.last_terminal = None;
+        }
+
         // Remove selections for the closed view on all documents.
         for doc in self.documents_mut() {
             doc.remove_view(id);
@@ -1242,6 +1443,138 @@ pub fn close(&mut self, id: ViewId) {
         self._refresh();
     }
 
+    /// Computes the blame of a document in the background if the blame gutter is shown, which
+    /// is set on the document once done.
+    pub fn refresh_blame(&mut self, doc_id: DocumentId) {
+        if !self.config().gutters.layout.contains(&GutterType::Blame) {
+            return;
+        }
+        let path = match self.documents.get(&doc_id).and_then(|doc| doc.path()) {
+            Some(path) => path.clone(),
+            None => return,
+        };
+        let providers = self.diff_providers.clone();
+        let events = self.blame_events.0.clone();
+        tokio::task::spawn_blocking(move || {
+            let _ = events.send((doc_id, providers.blame(&path)));
+        });
+    }
+
+    /// Computes the state of the repository containing a document in the background if it's
+    /// shown in the statusline, which is set on the document once done.
+    pub fn refresh_repo_status(&mut self, doc_id: DocumentId) {
+        let config = self.config();
+        let statusline = &config.statusline;
+        let shown = [&statusline.left, &statusline.center, &statusline.right]
+            .into_iter()
+            .flatten()
+            .any(|element| *element == StatusLineElement::VersionControl);
+        if !shown {
+            return;
+        }
+        let dir = match self
+            .documents
+            .get(&doc_id)
+            .and_then(|doc| doc.path()?.parent())
+        {
+            Some(dir) => dir.to_owned(),
+            None => return,
+        };
+        let providers = self.diff_providers.clone();
+        let events = self.repo_status_events.0.clone();
+        tokio::task::spawn_blocking(move || {
+            let _ = events.send((doc_id, providers.repo_status(&dir)));
+        });
+    }
+
+    /// Compares the document `doc_id` to the text of the document `base_id` from now on.
+    pub fn set_diff_base_document(&mut self, doc_id: DocumentId, base_id: DocumentId) {
+        let base = doc!(self, &base_id);
+        let (text, version) = (base.text().clone(), base.version());
+        let redraw_handle = self.redraw_handle.clone();
+        let doc = doc_mut!(self, &doc_id);
+        doc.diff_base = DiffBase::Document(base_id, version);
+        doc.set_diff_base_text(text, redraw_handle);
+    }
+
+    /// Shows the document `base_id` in the focused view and the document `doc_id` in a split to
+    /// the right of it, comparing `doc_id` to `base_id` side by side.
+    pub fn diff_split(&mut self, base_id: DocumentId, doc_id: DocumentId) {
+        self.switch(base_id, Action::Replace);
+        self.switch(doc_id, Action::VerticalSplit);
+        self.set_diff_base_document(doc_id, base_id);
+    }
+
+    /// Keeps the documents compared to another document up to date with its text, aligns the
+    /// lines of both with filler rows and scrolls their views along with the focused view when
+    /// it shows either side of the comparison.
+    pub fn sync_diff_buffers(&mut self) {
+        let pairs: Vec<_> = self
+            .documents
+            .values()
+            .filter_map(|doc| match doc.diff_base {
+                DiffBase::Document(base_id, version) => Some((doc.id, base_id, version)),
+                _ => None,
+            })
+            .collect();
+        let focused_view = view!(self);
+        let (focused_doc, focused_row) = (focused_view.doc, focused_view.offset.row);
+        let mut filler_rows: HashMap<DocumentId, Vec<(usize, usize)>> = HashMap::new();
+
+        for (doc_id, base_id, version) in pairs {
+            let base = self
+                .documents
+                .get(&base_id)
+                .map(|base| (base.version(), base.text().clone()));
+            let redraw_handle = self.redraw_handle.clone();
+            let doc = doc_mut!(self, &doc_id);
+            match base {
+                Some((base_version, text)) if base_version != version => {
+                    doc.diff_base = DiffBase::Document(base_id, base_version);
+                    doc.set_diff_base_text(text, redraw_handle);
+                }
+                Some(_) => (),
+                // the other document was closed, compare to the last commit again
+                None => {
+                    doc.load_diff_base(DiffBase::Head, &self.diff_providers, redraw_handle);
+                    continue;
+                }
+            }
+
+            let hunks = match doc.diff_handle() {
+                Some(diff_handle) => diff_handle.hunks(),
+                None => continue,
+            };
+            // a document can be compared to several others at once
+            let (doc_rows, base_rows) = hunks.filler_rows();
+            merge_filler_rows(filler_rows.entry(doc_id).or_default(), doc_rows);
+            merge_filler_rows(filler_rows.entry(base_id).or_default(), base_rows);
+
+            let (from_base, other_doc) = if focused_doc == doc_id {
+                (false, base_id)
+            } else if focused_doc == base_id {
+                (true, doc_id)
+            } else {
+                continue;
+            };
+            let row = hunks.corresponding_line(focused_row as u32, from_base) as usize;
+            for (view, _) in self.tree.views_mut() {
+                if view.doc == other_doc {
+                    view.offset.row = row;
+                }
+            }
+        }
+
+        // this runs on every render, the rows are only replaced when they changed
+        for (view, _) in self.tree.views_mut() {
+            match filler_rows.get(&view.doc) {
+                Some(rows) if *rows != view.filler_rows => view.filler_rows = rows.clone(),
+                None if !view.filler_rows.is_empty() => view.filler_rows.clear(),
+                _ => (),
+            }
+        }
+    }
+
     pub fn close_document(&mut self, doc_id: DocumentId, force: bool) -> Result<(), CloseError> {
         let doc = match self.documents.get_mut(&doc_id) {
             Some(doc) => doc,
@@ -1253,6 +1586,20 @@ pub fn close_document(&mut self, doc_id: DocumentId, force: bool) -> Result<(),
 
         // This will also disallow any follow-up writes
         self.saves.remove(&doc_id);
+        self.search_buffers.remove(&doc_id);
+        self.memory_views.remove(&doc_id);
+        if matches!(&self.disassembly, Some(disassembly) if disassembly.doc == doc_id) {
+            self.disassembly = None;
+        }
+        // closing the output of a REPL stops it
+        let mut stopped_repls = Vec::new();
+        self.repls.retain(|language, repl| {
+            let keep = repl.doc != doc_id;
+            if !keep {
+                stopped_repls.push(language.clone());
+            }
+            keep
+        });
 
         if let Some(language_server) = doc.language_server() {
             // TODO: track error
@@ -1315,6 +1662,9 @@ enum Action {
 
         self._refresh();
 
+        if !stopped_repls.is_empty() {
+            self.set_status(format!("Stopped the {} REPL", stopped_repls.join(", ")));
+        }
         Ok(())
     }
 
@@ -1436,6 +1786,10 @@ pub fn document_by_path_mut<P: AsRef<Path>>(&mut self, path: P) -> Option<&mut D
     pub fn cursor(&self) -> (Option<Position>, CursorKind) {
         let config = self.config();
         let (view, doc) = current_ref!(self);
+        // the cursor of terminals is drawn along with their screen
+        if self.terminals.contains_key(&view.id) {
+            return (None, CursorKind::Hidden);
+        }
         let cursor = doc
             .selection(view.id)
             .primary()
@@ -1486,9 +1840,27 @@ pub async fn wait_event(&mut self) -> EditorEvent {
                 Some(message) = self.language_servers.incoming.next() => {
                     return EditorEvent::LanguageServerMessage(message)
                 }
-                Some(event) = self.debugger_events.next() => {
+                Some(event) = self.debuggers.incoming.next() => {
                     return EditorEvent::DebuggerEvent(event)
                 }
+                Some(event) = self.terminal_events.1.recv() => {
+                    return EditorEvent::TerminalEvent(event)
+                }
+                Some(event) = self.repl_events.1.recv() => {
+                    return EditorEvent::ReplEvent(event)
+                }
+                Some((doc, blame)) = self.blame_events.1.recv() => {
+                    if let Some(doc) = self.documents.get_mut(&doc) {
+                        doc.set_blame(blame);
+                        self.redraw_handle.0.notify_one();
+                    }
+                }
+                Some((doc, repo_status)) = self.repo_status_events.1.recv() => {
+                    if let Some(doc) = self.documents.get_mut(&doc) {
+                        doc.set_repo_status(repo_status);
+                        self.redraw_handle.0.notify_one();
+                    }
+                }
 
                 _ = self.redraw_handle.0.notified() => {
                     if  !self.needs_redraw{
@@ -1536,6 +1908,12 @@ pub fn enter_normal_mode(&mut self) {
             return;
         }
 
+        // terminal mode doesn't touch the document of the view
+        if self.mode == Mode::Terminal {
+            self.mode = Mode::Normal;
+            return;
+        }
+
         self.mode = Mode::Normal;
         let (view, doc) = current!(self);
 
@@ -1557,6 +1935,21 @@ pub fn enter_normal_mode(&mut self) {
     }
 }
 
+/// Adds the filler `rows` of another comparison to those of a document, keeping the most rows
+/// above each line.
+fn merge_filler_rows(merged: &mut Vec<(usize, usize)>, rows: Vec<(usize, usize)>) {
+    if merged.is_empty() {
+        *merged = rows;
+        return;
+    }
+    for (line, count) in rows {
+        match merged.binary_search_by_key(&line, |(line, _)| *line) {
+            Ok(i) => merged[i].1 = merged[i].1.max(count),
+            Err(i) => merged.insert(i, (line, count)),
+        }
+    }
+}
+
 fn try_restore_indent(doc: &mut Document, view: &mut View) {
     use helix_core::{
         chars::char_is_whitespace, line_ending::line_end_char_index, Operation, Transaction,
@@ -1591,3 +1984,16 @@ fn inserted_a_new_blank_line(changes: &[Operation], pos: usize, line_end_pos: us
         doc.apply(&transaction, view.id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filler_rows_of_several_comparisons_are_merged() {
+        let mut rows = Vec::new();
+        merge_filler_rows(&mut rows, vec![(1, 2), (5, 1)]);
+        merge_filler_rows(&mut rows, vec![(3, 1), (5, 3)]);
+        assert_eq!(rows, [(1, 2), (3, 1), (5, 3)]);
+    }
+}
@@ -32,6 +32,7 @@ pub fn style<'doc>(
             GutterType::LineNumbers => line_numbers(editor, doc, view, theme, is_focused),
             GutterType::Spacer => padding(editor, doc, view, theme, is_focused),
             GutterType::Diff => diff(editor, doc, view, theme, is_focused),
+            GutterType::Blame => blame(editor, doc, view, theme, is_focused),
         }
     }
 
@@ -41,6 +42,7 @@ pub fn width(self, view: &View, doc: &Document) -> usize {
             GutterType::LineNumbers => line_numbers_width(view, doc),
             GutterType::Spacer => 1,
             GutterType::Diff => 1,
+            GutterType::Blame => blame_width(doc),
         }
     }
 }
@@ -132,6 +134,55 @@ pub fn diff<'doc>(
     }
 }
 
+/// Number of characters of the author names shown by the blame gutter.
+const BLAME_AUTHOR_WIDTH: usize = 12;
+
+pub fn blame<'doc>(
+    _editor: &'doc Editor,
+    doc: &'doc Document,
+    _view: &View,
+    theme: &Theme,
+    _is_focused: bool,
+) -> GutterFn<'doc> {
+    let style = theme.get("ui.linenr");
+    let blame = match doc.blame() {
+        Some(blame) => blame,
+        None => return Box::new(move |_, _, _| None),
+    };
+    let hunks = doc.diff_handle().map(|diff_handle| diff_handle.hunks());
+    let mut previous = None;
+    Box::new(move |line: usize, _selected: bool, out: &mut String| {
+        let commit = blame.line_commit(hunks.as_ref(), line as u32);
+        // only the first of consecutive lines changed by the same commit is annotated
+        let first = previous != commit.map(|commit| &commit.id);
+        previous = commit.map(|commit| &commit.id);
+        match commit {
+            Some(commit) if first => {
+                let author: String = commit.author.chars().take(BLAME_AUTHOR_WIDTH).collect();
+                write!(
+                    out,
+                    "{} {:width$} ",
+                    commit.date(),
+                    author,
+                    width = BLAME_AUTHOR_WIDTH
+                )
+                .unwrap();
+                Some(style)
+            }
+            _ => None,
+        }
+    })
+}
+
+/// The blame gutter is only shown once the blame of the document was computed.
+fn blame_width(doc: &Document) -> usize {
+    match doc.blame() {
+        // the date, the author and spaces after them
+        Some(_) => "YYYY-MM-DD".len() + BLAME_AUTHOR_WIDTH + 2,
+        None => 0,
+    }
+}
+
 pub fn line_numbers<'doc>(
     editor: &'doc Editor,
     doc: &'doc Document,
@@ -234,6 +285,8 @@ pub fn breakpoints<'doc>(
     let info = theme.get("info");
 
     let breakpoints = doc.path().and_then(|path| editor.breakpoints.get(path));
+    // the state shown is the one of the active session
+    let session = editor.debuggers.active_id();
 
     let breakpoints = match breakpoints {
         Some(breakpoints) => breakpoints,
@@ -255,7 +308,8 @@ pub fn breakpoints<'doc>(
             warning
         };
 
-        if !breakpoint.verified {
+        let verified = breakpoint.verified(session);
+        if !verified {
             // Faded colors
             style = if let Some(Color::Rgb(r, g, b)) = style.fg {
                 style.fg(Color::Rgb(
@@ -268,7 +322,13 @@ pub fn breakpoints<'doc>(
             }
         };
 
-        let sym = if breakpoint.verified { "▲" } else { "⊚" };
+        let sym = if !breakpoint.enabled {
+            "○"
+        } else if verified {
+            "▲"
+        } else {
+            "⊚"
+        };
         write!(out, "{}", sym).unwrap();
         Some(style)
     })
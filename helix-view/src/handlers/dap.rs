@@ -1,16 +1,24 @@
-use crate::editor::{Action, Breakpoint};
+use crate::debug_console::{ConsoleEntry, OutputCategory, VariableNode, Watch};
+use crate::editor::{
+    Action, Breakpoint, BreakpointState, DataBreakpoint, ExceptionBreakpoint, FunctionBreakpoint,
+    TerminalConfig,
+};
 use crate::{align_view, Align, Editor};
-use helix_core::Selection;
-use helix_dap::{self as dap, Client, Payload, Request, ThreadId};
+use futures_util::future::{BoxFuture, FutureExt};
+use helix_core::syntax::DebugAdapterConfig;
+use helix_core::{Assoc, ChangeSet, Rope, Selection};
+use helix_dap::{self as dap, launch::quote_shell_arg, Client, Payload, Request, ThreadId};
 use helix_lsp::block_on;
 use log::warn;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
-use std::path::PathBuf;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 
 #[macro_export]
 macro_rules! debugger {
     ($editor:expr) => {{
-        match &mut $editor.debugger {
+        match $editor.debuggers.get_active_mut() {
             Some(debugger) => debugger,
             None => return,
         }
@@ -40,6 +48,7 @@ pub async fn select_thread_id(editor: &mut Editor, thread_id: ThreadId, force: b
     if let Some(frame) = &frame {
         jump_to_stack_frame(editor, frame);
     }
+    editor.refresh_debug_values().await;
 }
 
 pub async fn fetch_stack_trace(debugger: &mut Client, thread_id: ThreadId) {
@@ -62,7 +71,33 @@ pub fn jump_to_stack_frame(editor: &mut Editor, frame: &helix_dap::StackFrame) {
         return;
     };
 
-    if let Err(e) = editor.open(&path, Action::Replace) {
+    // keep the disassembly in view while stepping through it, showing the source next to it
+    let disassembly = editor
+        .disassembly
+        .as_ref()
+        .map(|disassembly| disassembly.doc)
+        .filter(|doc| *doc == view!(editor).doc);
+    let focus = editor.tree.focus;
+    let action = match disassembly {
+        Some(disassembly) => {
+            let other = editor
+                .tree
+                .views()
+                .find(|(view, _)| view.doc != disassembly)
+                .map(|(view, _)| view.id);
+            match other {
+                Some(other) => {
+                    editor.focus(other);
+                    Action::Replace
+                }
+                None => Action::VerticalSplit,
+            }
+        }
+        None => Action::Replace,
+    };
+
+    if let Err(e) = editor.open(&path, action) {
+        editor.focus(focus);
         editor.set_error(format!("Unable to jump to stack frame: {}", e));
         return;
     }
@@ -79,6 +114,7 @@ pub fn jump_to_stack_frame(editor: &mut Editor, frame: &helix_dap::StackFrame) {
     let selection = Selection::single(start.min(text_end), end.min(text_end));
     doc.set_selection(view.id, selection);
     align_view(doc, view, Align::Center);
+    editor.focus(focus);
 }
 
 pub fn breakpoints_changed(
@@ -109,41 +145,442 @@ pub fn breakpoints_changed(
     // {
     //     bail!("Can't edit breakpoint: debugger does not support logpoints")
     // }
-    let source_breakpoints = breakpoints
+    let request = debugger.set_breakpoints(path, source_breakpoints(breakpoints));
+    match block_on(request) {
+        Ok(Some(dap_breakpoints)) => {
+            set_breakpoint_states(debugger.id(), breakpoints, dap_breakpoints)
+        }
+        Err(e) => anyhow::bail!("Failed to set breakpoints: {}", e),
+        _ => {}
+    };
+    Ok(())
+}
+
+/// The breakpoints to send to a debugger, the enabled ones. The disabled ones are unknown to
+/// the debugger from now on.
+pub fn source_breakpoints(breakpoints: &mut [Breakpoint]) -> Vec<dap::SourceBreakpoint> {
+    for breakpoint in breakpoints
+        .iter_mut()
+        .filter(|breakpoint| !breakpoint.enabled)
+    {
+        breakpoint.states.clear();
+    }
+
+    breakpoints
         .iter()
+        .filter(|breakpoint| breakpoint.enabled)
         .map(|breakpoint| helix_dap::SourceBreakpoint {
             line: breakpoint.line + 1, // convert from 0-indexing to 1-indexing (TODO: could set debugger to 0-indexing on init)
+            condition: breakpoint.condition.clone(),
+            hit_condition: breakpoint.hit_condition.clone(),
+            log_message: breakpoint.log_message.clone(),
             ..Default::default()
         })
-        .collect::<Vec<_>>();
+        .collect()
+}
+
+/// Updates the enabled breakpoints with the state the debug session `session` reported for each
+/// of them, in response to [`source_breakpoints`].
+pub fn set_breakpoint_states(
+    session: usize,
+    breakpoints: &mut [Breakpoint],
+    dap_breakpoints: Vec<dap::Breakpoint>,
+) {
+    let enabled = breakpoints
+        .iter_mut()
+        .filter(|breakpoint| breakpoint.enabled);
+    for (breakpoint, dap_breakpoint) in enabled.zip(dap_breakpoints) {
+        // TODO: handle breakpoint.message
+        // TODO: verify source matches
+        // keep our line if the debugger didn't report one, it's persisted
+        if let Some(line) = dap_breakpoint.line {
+            breakpoint.line = line.saturating_sub(1); // convert to 0-indexing
+        }
+        breakpoint.states.insert(
+            session,
+            BreakpointState {
+                id: dap_breakpoint.id,
+                verified: dap_breakpoint.verified,
+                message: dap_breakpoint.message,
+                column: dap_breakpoint.column,
+            },
+        );
+        // TODO: verify end_linef/col instruction reference, offset
+    }
+}
 
-    let request = debugger.set_breakpoints(path, source_breakpoints);
+pub fn function_breakpoints_changed(
+    debugger: &mut dap::Client,
+    breakpoints: &mut [FunctionBreakpoint],
+) -> Result<(), anyhow::Error> {
+    if !debugger
+        .capabilities()
+        .supports_function_breakpoints
+        .unwrap_or_default()
+    {
+        anyhow::bail!("The debugger does not support function breakpoints");
+    }
+    let function_breakpoints = breakpoints
+        .iter()
+        .map(|breakpoint| dap::FunctionBreakpoint {
+            name: breakpoint.name.clone(),
+            condition: breakpoint.condition.clone(),
+            hit_condition: None,
+        })
+        .collect();
+
+    let request = debugger.set_function_breakpoints(function_breakpoints);
     match block_on(request) {
-        Ok(Some(dap_breakpoints)) => {
+        Ok(dap_breakpoints) => {
             for (breakpoint, dap_breakpoint) in breakpoints.iter_mut().zip(dap_breakpoints) {
-                breakpoint.id = dap_breakpoint.id;
                 breakpoint.verified = dap_breakpoint.verified;
-                breakpoint.message = dap_breakpoint.message;
-                // TODO: handle breakpoint.message
-                // TODO: verify source matches
-                breakpoint.line = dap_breakpoint.line.unwrap_or(0).saturating_sub(1); // convert to 0-indexing
-                                                                                      // TODO: no unwrap
-                breakpoint.column = dap_breakpoint.column;
-                // TODO: verify end_linef/col instruction reference, offset
             }
+            Ok(())
         }
-        Err(e) => anyhow::bail!("Failed to set breakpoints: {}", e),
-        _ => {}
+        Err(e) => anyhow::bail!("Failed to set function breakpoints: {}", e),
+    }
+}
+
+pub fn data_breakpoints_changed(
+    debugger: &mut dap::Client,
+    breakpoints: &mut [DataBreakpoint],
+) -> Result<(), anyhow::Error> {
+    if !debugger
+        .capabilities()
+        .supports_data_breakpoints
+        .unwrap_or_default()
+    {
+        anyhow::bail!("The debugger does not support data breakpoints");
+    }
+    let data_breakpoints = breakpoints
+        .iter()
+        .map(|breakpoint| dap::DataBreakpoint {
+            data_id: breakpoint.data_id.clone(),
+            ..Default::default()
+        })
+        .collect();
+
+    let request = debugger.set_data_breakpoints(data_breakpoints);
+    match block_on(request) {
+        Ok(dap_breakpoints) => {
+            for (breakpoint, dap_breakpoint) in breakpoints.iter_mut().zip(dap_breakpoints) {
+                breakpoint.verified = dap_breakpoint.verified;
+            }
+            Ok(())
+        }
+        Err(e) => anyhow::bail!("Failed to set data breakpoints: {}", e),
+    }
+}
+
+pub fn exception_breakpoints_changed(
+    debugger: &mut dap::Client,
+    breakpoints: &[ExceptionBreakpoint],
+) -> Result<(), anyhow::Error> {
+    let filter_options = debugger
+        .capabilities()
+        .supports_exception_filter_options
+        .unwrap_or_default();
+
+    // filters with a condition have to be sent as options
+    let (filters, options) = if filter_options {
+        let options = breakpoints
+            .iter()
+            .map(|breakpoint| dap::ExceptionFilterOptions {
+                filter_id: breakpoint.filter.clone(),
+                condition: breakpoint.condition.clone(),
+            })
+            .collect();
+        (Vec::new(), Some(options))
+    } else {
+        let filters = breakpoints
+            .iter()
+            .map(|breakpoint| breakpoint.filter.clone())
+            .collect();
+        (filters, None)
     };
-    Ok(())
+
+    let request = debugger.set_exception_breakpoints(filters, options);
+    match block_on(request) {
+        Ok(_) => Ok(()),
+        Err(e) => anyhow::bail!("Failed to set exception breakpoints: {}", e),
+    }
 }
 
 impl Editor {
-    pub async fn handle_debugger_message(&mut self, payload: helix_dap::Payload) -> bool {
-        use dap::requests::RunInTerminal;
+    /// Starts a session of the debug adapter `config`, or connects to the adapter listening at
+    /// `socket`, and sends it the `launch` or `attach` `request` with `args`. The session becomes
+    /// the active one, and the future of the response to the request is returned.
+    pub fn start_debug_session(
+        &mut self,
+        name: String,
+        config: &DebugAdapterConfig,
+        socket: Option<SocketAddr>,
+        request: &str,
+        args: serde_json::Value,
+    ) -> anyhow::Result<BoxFuture<'static, dap::Result<serde_json::Value>>> {
+        if !matches!(request, "launch" | "attach") {
+            anyhow::bail!("Unsupported request '{}'", request);
+        }
+
+        let id = self.debuggers.next_id();
+        let result = match socket {
+            Some(socket) => block_on(Client::tcp(socket, id)),
+            None => block_on(Client::process(
+                &config.transport,
+                &config.command,
+                config.args.iter().map(|arg| arg.as_str()).collect(),
+                config.port_arg.as_deref(),
+                id,
+            )),
+        };
+
+        let (mut debugger, events) = match result {
+            Ok(r) => r,
+            Err(e) => anyhow::bail!("Failed to start debug session: {}", e),
+        };
+
+        let initialize = debugger.initialize(config.name.clone());
+        if let Err(e) = block_on(initialize) {
+            anyhow::bail!("Failed to initialize debug adapter: {}", e);
+        }
+
+        debugger.quirks = config.quirks.clone();
+        debugger.name = name;
+        debugger.config = Some(config.clone());
+
+        let call = match request {
+            "launch" => debugger.launch(args).boxed(),
+            _ => debugger.attach(args).boxed(),
+        };
+
+        // TODO: either await "initialized" or buffer commands until event is received
+        self.debuggers.insert(debugger, events);
+        Ok(call)
+    }
+
+    /// Starts the session a `startDebugging` request of the session `parent` asks for, with the
+    /// same adapter, which it ends with.
+    fn start_child_debug_session(
+        &mut self,
+        parent: usize,
+        arguments: serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let arguments: dap::requests::StartDebuggingRequestArguments =
+            serde_json::from_value(arguments)?;
+        let parent_debugger = self
+            .debuggers
+            .get(parent)
+            .ok_or_else(|| anyhow::anyhow!("The debug session no longer exists"))?;
+        let config = parent_debugger
+            .config
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("The debug adapter of the session is unknown"))?;
+        // adapters listening on a port expect the child sessions to connect to it as well
+        let socket = parent_debugger.socket;
+        let name = arguments
+            .configuration
+            .get("name")
+            .and_then(|name| name.as_str())
+            .map_or_else(|| parent_debugger.name.clone(), |name| name.to_string());
+
+        let request = arguments.request;
+        let call = self.start_debug_session(
+            name,
+            &config,
+            socket,
+            &request,
+            serde_json::Value::Object(arguments.configuration),
+        )?;
+        if let Some(debugger) = self.debuggers.get_active_mut() {
+            debugger.parent = Some(parent);
+        }
+        tokio::spawn(async move {
+            if let Err(err) = call.await {
+                log::error!("Failed to {} the debuggee: {}", request, err);
+            }
+        });
+        Ok(())
+    }
+
+    /// Removes the session `id` along with the child sessions it started.
+    pub fn remove_debug_session(&mut self, id: usize) {
+        let children: Vec<_> = self
+            .debuggers
+            .iter()
+            .filter(|debugger| debugger.parent == Some(id))
+            .map(|debugger| debugger.id())
+            .collect();
+        for child in children {
+            self.remove_debug_session(child);
+        }
+
+        let was_active = self.debuggers.active_id() == Some(id);
+        self.debuggers.remove(id);
+        for breakpoint in self.breakpoints.values_mut().flatten() {
+            breakpoint.states.remove(&id);
+        }
+        // data breakpoints refer to values of the session
+        self.data_breakpoints.remove(&id);
+        if was_active {
+            // the values were those of the removed session
+            self.variables.clear();
+            self.inline_values.clear();
+        }
+    }
+
+    /// Makes the session `id` the active one, showing its breakpoints, stack frames and values.
+    pub async fn select_debug_session(&mut self, id: usize) {
+        if !self.debuggers.set_active(id) {
+            return;
+        }
+        let debugger = debugger!(self);
+        let frame = debugger.current_stack_frame().cloned();
+        if let Some(frame) = &frame {
+            jump_to_stack_frame(self, frame);
+        }
+        self.refresh_debug_values().await;
+    }
+
+    /// Sends the breakpoints of `path` to all the debug sessions.
+    pub fn send_breakpoints(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        self.track_breakpoint_edits();
+        self.moved_breakpoints.remove(&path);
+        let breakpoints = self.breakpoints.entry(path.clone()).or_default();
+        let mut result = Ok(());
+        for debugger in self.debuggers.iter_mut() {
+            if let Err(err) = breakpoints_changed(debugger, path.clone(), breakpoints) {
+                log::error!("Debug session '{}': {}", debugger.name, err);
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    /// Sends the function breakpoints to all the debug sessions.
+    pub fn send_function_breakpoints(&mut self) -> anyhow::Result<()> {
+        let mut result = Ok(());
+        for debugger in self.debuggers.iter_mut() {
+            if let Err(err) = function_breakpoints_changed(debugger, &mut self.function_breakpoints)
+            {
+                log::error!("Debug session '{}': {}", debugger.name, err);
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    /// Sends the exception breakpoints to all the debug sessions.
+    pub fn send_exception_breakpoints(&mut self) -> anyhow::Result<()> {
+        let mut result = Ok(());
+        for debugger in self.debuggers.iter_mut() {
+            if let Err(err) = exception_breakpoints_changed(debugger, &self.exception_breakpoints) {
+                log::error!("Debug session '{}': {}", debugger.name, err);
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    /// Removes the breakpoints set to run to a line, once a debugger stopped.
+    fn remove_temporary_breakpoints(&mut self) {
+        let mut changed = Vec::new();
+        for (path, breakpoints) in &mut self.breakpoints {
+            if breakpoints.iter().any(|breakpoint| breakpoint.temporary) {
+                breakpoints.retain(|breakpoint| !breakpoint.temporary);
+                changed.push(path.clone());
+            }
+        }
+        for path in changed {
+            if let Err(err) = self.send_breakpoints(path) {
+                log::error!("{}", err);
+            }
+        }
+    }
+
+    /// Evaluates the watches again and fetches the variables of the current stack frame, after
+    /// it changed. Values that were expanded before are expanded again, and the disassembly and
+    /// memory views are updated.
+    pub async fn refresh_debug_values(&mut self) {
+        self.refresh_disassembly().await;
+        self.refresh_memory_views().await;
+
+        let debugger = match self.debuggers.get_active() {
+            Some(debugger) => debugger,
+            None => return,
+        };
+        for watch in &mut self.watches {
+            watch.evaluate(debugger).await;
+        }
+
+        let old_variables = std::mem::take(&mut self.variables);
+        self.inline_values.clear();
+        let frame_id = match debugger.current_stack_frame() {
+            Some(frame) => frame.id,
+            None => return,
+        };
+        let scopes = match debugger.scopes(frame_id).await {
+            Ok(scopes) => scopes,
+            Err(_) => return,
+        };
+        for scope in scopes {
+            let mut node =
+                VariableNode::new(scope.name, String::new(), None, scope.variables_reference);
+            match old_variables.iter().find(|old| old.name == node.name) {
+                Some(old) => node.restore_expanded(old, debugger).await,
+                // expensive scopes, like the globals, are only fetched once expanded
+                None if !scope.expensive => {
+                    node.children = node.fetch_children(debugger).await.ok();
+                    node.expanded = node.children.is_some();
+                }
+                None => (),
+            }
+
+            if !self.show_inline_values || scope.expensive {
+                self.variables.push(node);
+                continue;
+            }
+            if node.children.is_none() {
+                node.children = node.fetch_children(debugger).await.ok();
+            }
+            for variable in node.children.iter().flatten() {
+                // inner scopes are listed first and shadow the outer ones
+                self.inline_values
+                    .entry(variable.name.clone())
+                    .or_insert_with(|| variable.value.clone());
+            }
+            self.variables.push(node);
+        }
+    }
+
+    /// Adds a watch, which is evaluated right away if the debugger is stopped.
+    pub fn add_watch(&mut self, expression: String) {
+        let mut watch = Watch::new(expression);
+        if let Some(debugger) = self.debuggers.get_active() {
+            if debugger.current_stack_frame().is_some() {
+                block_on(watch.evaluate(debugger));
+            }
+        }
+        self.watches.push(watch);
+    }
+
+    /// Handles a message of the debug session `id`.
+    pub async fn handle_debugger_message(
+        &mut self,
+        id: usize,
+        payload: helix_dap::Payload,
+    ) -> bool {
+        use dap::requests::{RunInTerminal, StartDebugging};
         use helix_dap::{events, Event};
 
-        let debugger = match self.debugger.as_mut() {
+        if let Payload::Event(event) = &payload {
+            if let Event::Stopped(_) = **event {
+                // the session that stopped is the one to look at
+                self.debuggers.set_active(id);
+                self.remove_temporary_breakpoints();
+            }
+        }
+
+        let debugger = match self.debuggers.get_mut(id) {
             Some(debugger) => debugger,
             None => return false,
         };
@@ -203,40 +640,48 @@ pub async fn handle_debugger_message(&mut self, payload: helix_dap::Payload) ->
                     // TODO: update thread_states, make threads request
                 }
                 Event::Breakpoint(events::Breakpoint { reason, breakpoint }) => {
+                    // breakpoint ids are only meaningful to the session that reported them
+                    let has_id = |b: &Breakpoint| {
+                        b.states
+                            .get(&id)
+                            .map_or(false, |state| state.id == breakpoint.id)
+                    };
                     match &reason[..] {
                         "new" => {
                             if let Some(source) = breakpoint.source {
+                                let state = BreakpointState {
+                                    id: breakpoint.id,
+                                    verified: breakpoint.verified,
+                                    message: breakpoint.message,
+                                    column: breakpoint.column,
+                                };
                                 self.breakpoints
                                     .entry(source.path.unwrap()) // TODO: no unwraps
                                     .or_default()
                                     .push(Breakpoint {
-                                        id: breakpoint.id,
-                                        verified: breakpoint.verified,
-                                        message: breakpoint.message,
+                                        states: [(id, state)].into_iter().collect(),
                                         line: breakpoint.line.unwrap().saturating_sub(1), // TODO: no unwrap
-                                        column: breakpoint.column,
                                         ..Default::default()
                                     });
+                                self.track_breakpoint_edits();
                             }
                         }
                         "changed" => {
                             for breakpoints in self.breakpoints.values_mut() {
-                                if let Some(i) =
-                                    breakpoints.iter().position(|b| b.id == breakpoint.id)
-                                {
-                                    breakpoints[i].verified = breakpoint.verified;
-                                    breakpoints[i].message = breakpoint.message.clone();
+                                if let Some(i) = breakpoints.iter().position(has_id) {
+                                    let state = breakpoints[i].states.get_mut(&id).unwrap();
+                                    state.verified = breakpoint.verified;
+                                    state.message = breakpoint.message.clone();
+                                    state.column = breakpoint.column;
                                     breakpoints[i].line =
-                                        breakpoint.line.unwrap().saturating_sub(1); // TODO: no unwrap
-                                    breakpoints[i].column = breakpoint.column;
+                                        breakpoint.line.unwrap().saturating_sub(1);
+                                    // TODO: no unwrap
                                 }
                             }
                         }
                         "removed" => {
                             for breakpoints in self.breakpoints.values_mut() {
-                                if let Some(i) =
-                                    breakpoints.iter().position(|b| b.id == breakpoint.id)
-                                {
+                                if let Some(i) = breakpoints.iter().position(has_id) {
                                     breakpoints.remove(i);
                                 }
                             }
@@ -247,20 +692,33 @@ pub async fn handle_debugger_message(&mut self, payload: helix_dap::Payload) ->
                     }
                 }
                 Event::Output(events::Output {
-                    category, output, ..
+                    category,
+                    output,
+                    variables_reference,
+                    ..
                 }) => {
-                    let prefix = match category {
-                        Some(category) => {
-                            if &category == "telemetry" {
-                                return false;
-                            }
-                            format!("Debug ({}):", category)
-                        }
-                        None => "Debug:".to_owned(),
-                    };
-
+                    if category.as_deref() == Some("telemetry") {
+                        return false;
+                    }
                     log::info!("{}", output);
-                    self.set_status(format!("{} {}", prefix, output));
+
+                    let category = OutputCategory::from_dap(category.as_deref());
+                    if category == OutputCategory::Important {
+                        self.set_status(format!("Debug: {}", output.trim_end()));
+                    }
+                    match variables_reference {
+                        // structured output, which can be expanded like an evaluation result
+                        Some(reference) if reference > 0 => {
+                            self.debug_console
+                                .push(ConsoleEntry::Value(VariableNode::new(
+                                    String::new(),
+                                    output.trim_end().to_string(),
+                                    None,
+                                    reference,
+                                )))
+                        }
+                        _ => self.debug_console.push_output(category, &output),
+                    }
                 }
                 Event::Initialized(_) => {
                     // send existing breakpoints
@@ -268,67 +726,355 @@ pub async fn handle_debugger_message(&mut self, payload: helix_dap::Payload) ->
                         // TODO: call futures in parallel, await all
                         let _ = breakpoints_changed(debugger, path.clone(), breakpoints);
                     }
+                    if !self.function_breakpoints.is_empty() {
+                        let _ =
+                            function_breakpoints_changed(debugger, &mut self.function_breakpoints);
+                    }
+                    if !self.exception_breakpoints.is_empty() {
+                        let _ =
+                            exception_breakpoints_changed(debugger, &self.exception_breakpoints);
+                    }
                     // TODO: fetch breakpoints (in case we're attaching)
 
                     if debugger.configuration_done().await.is_ok() {
                         self.set_status("Debugged application started");
                     }; // TODO: do we need to handle error?
                 }
+                Event::Terminated(_) => {
+                    let name = debugger.name.clone();
+                    self.remove_debug_session(id);
+                    self.set_status(format!("Debug session '{}' ended", name));
+                }
                 ev => {
                     log::warn!("Unhandled event {:?}", ev);
                     return false; // return early to skip render
                 }
             },
             Payload::Response(_) => unreachable!(),
-            Payload::Request(request) => match request.command.as_str() {
-                RunInTerminal::COMMAND => {
-                    let arguments: dap::requests::RunInTerminalArguments =
-                        serde_json::from_value(request.arguments.unwrap_or_default()).unwrap();
-                    // TODO: no unwrap
-
-                    let config = match self.config().terminal.clone() {
-                        Some(config) => config,
-                        None => {
-                            self.set_error("No external terminal defined");
-                            return true;
-                        }
-                    };
+            Payload::Request(request) => {
+                // the adapter waits for a reply, even if the request fails
+                let result = match request.command.as_str() {
+                    RunInTerminal::COMMAND => {
+                        let arguments = request.arguments.unwrap_or_default();
+                        let result = match self.config().terminal.clone() {
+                            Some(terminal) => run_in_terminal(terminal, arguments),
+                            None => Err(anyhow::anyhow!("No external terminal defined")),
+                        };
+                        result.and_then(|response| Ok(serde_json::to_value(response)?))
+                    }
+                    StartDebugging::COMMAND => self
+                        .start_child_debug_session(id, request.arguments.unwrap_or_default())
+                        .map(|()| serde_json::Value::Null),
+                    _ => {
+                        log::error!("DAP reverse request not implemented: {:?}", request);
+                        Err(anyhow::anyhow!("Unsupported request '{}'", request.command))
+                    }
+                };
+                if let Err(err) = &result {
+                    self.set_error(err.to_string());
+                }
 
-                    // Re-borrowing debugger to avoid issues when loading config
-                    let debugger = match self.debugger.as_mut() {
-                        Some(debugger) => debugger,
-                        None => return false,
-                    };
+                // Re-borrowing debugger to avoid issues when loading config
+                let debugger = match self.debuggers.get_mut(id) {
+                    Some(debugger) => debugger,
+                    None => return false,
+                };
+                let _ = debugger
+                    .reply(request.seq, &request.command, result.map_err(Into::into))
+                    .await;
+            }
+        }
+        true
+    }
+}
 
-                    let process = match std::process::Command::new(config.command)
-                        .args(config.args)
-                        .arg(arguments.args.join(" "))
-                        .spawn()
-                    {
-                        Ok(process) => process,
-                        Err(err) => {
-                            // TODO replace the pretty print {:?} with a regular format {}
-                            // when the MSRV is raised to 1.60.0
-                            self.set_error(format!("Error starting external terminal: {:?}", err));
-                            return true;
-                        }
-                    };
+/// Runs the command of a `runInTerminal` request in the external `terminal`.
+fn run_in_terminal(
+    terminal: TerminalConfig,
+    arguments: serde_json::Value,
+) -> anyhow::Result<dap::requests::RunInTerminalResponse> {
+    let arguments: dap::requests::RunInTerminalArguments = serde_json::from_value(arguments)?;
+    let command_line = arguments
+        .args
+        .iter()
+        .map(|arg| quote_shell_arg(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
 
-                    let _ = debugger
-                        .reply(
-                            request.seq,
-                            dap::requests::RunInTerminal::COMMAND,
-                            serde_json::to_value(dap::requests::RunInTerminalResponse {
-                                process_id: Some(process.id()),
-                                shell_process_id: None,
-                            })
-                            .map_err(|e| e.into()),
-                        )
-                        .await;
-                }
-                _ => log::error!("DAP reverse request not implemented: {:?}", request),
-            },
+    let mut command = std::process::Command::new(terminal.command);
+    command.args(terminal.args).arg(command_line);
+    if !arguments.cwd.is_empty() {
+        command.current_dir(&arguments.cwd);
+    }
+    for (key, value) in arguments.env.unwrap_or_default() {
+        match value {
+            Some(value) => command.env(key, value),
+            None => command.env_remove(key),
+        };
+    }
+
+    let process = command.spawn().map_err(|err| {
+        // TODO replace the pretty print {:?} with a regular format {}
+        // when the MSRV is raised to 1.60.0
+        anyhow::anyhow!("Error starting external terminal: {:?}", err)
+    })?;
+    Ok(dap::requests::RunInTerminalResponse {
+        process_id: Some(process.id()),
+        shell_process_id: None,
+    })
+}
+
+/// Breakpoints of all workspaces, keyed by workspace root and then by file.
+type PersistedBreakpoints = BTreeMap<PathBuf, BTreeMap<PathBuf, Vec<Breakpoint>>>;
+
+fn read_persisted_breakpoints(path: &Path) -> Result<PersistedBreakpoints, anyhow::Error> {
+    if !path.exists() {
+        return Ok(PersistedBreakpoints::new());
+    }
+    let file = std::fs::File::open(path)?;
+    Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+}
+
+/// Replaces the breakpoints of `workspace` in the file at `path`, keeping those of other
+/// workspaces. Temporary breakpoints aren't persisted.
+fn write_persisted_breakpoints(
+    path: &Path,
+    workspace: PathBuf,
+    breakpoints: &HashMap<PathBuf, Vec<Breakpoint>>,
+) -> Result<(), anyhow::Error> {
+    // a broken file shouldn't prevent saving the current breakpoints
+    let mut persisted = read_persisted_breakpoints(path).unwrap_or_default();
+    let breakpoints: BTreeMap<_, _> = breakpoints
+        .iter()
+        .map(|(path, breakpoints)| {
+            let breakpoints: Vec<_> = breakpoints
+                .iter()
+                .filter(|breakpoint| !breakpoint.temporary)
+                .cloned()
+                .collect();
+            (path.clone(), breakpoints)
+        })
+        .filter(|(_, breakpoints)| !breakpoints.is_empty())
+        .collect();
+    if breakpoints.is_empty() {
+        if persisted.remove(&workspace).is_none() {
+            return Ok(());
         }
-        true
+    } else {
+        persisted.insert(workspace, breakpoints);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_vec_pretty(&persisted)?)?;
+    Ok(())
+}
+
+/// Maps the lines of `breakpoints` through `changes`, made to `old_text` to get `text`. Returns
+/// whether any of them moved.
+fn remap_breakpoint_lines(
+    breakpoints: &mut Vec<Breakpoint>,
+    old_text: &Rope,
+    text: &Rope,
+    changes: &ChangeSet,
+) -> bool {
+    let mut changed = false;
+    for breakpoint in breakpoints.iter_mut() {
+        if breakpoint.line >= old_text.len_lines() {
+            continue;
+        }
+        let pos = old_text.line_to_char(breakpoint.line);
+        let line = text.char_to_line(changes.map_pos(pos, Assoc::After));
+        if line != breakpoint.line {
+            breakpoint.line = line;
+            changed = true;
+        }
+    }
+    if changed {
+        // breakpoints on deleted lines collapse onto the next one, merged so that none of
+        // their settings are lost
+        breakpoints.sort_by_key(|breakpoint| breakpoint.line);
+        breakpoints.dedup_by(|removed, kept| {
+            if removed.line != kept.line {
+                return false;
+            }
+            kept.condition = kept.condition.take().or_else(|| removed.condition.take());
+            kept.hit_condition = kept
+                .hit_condition
+                .take()
+                .or_else(|| removed.hit_condition.take());
+            kept.log_message = kept
+                .log_message
+                .take()
+                .or_else(|| removed.log_message.take());
+            kept.enabled |= removed.enabled;
+            kept.temporary &= removed.temporary;
+            true
+        });
+    }
+    changed
+}
+
+impl Editor {
+    /// Restores the breakpoints that were set in the current workspace.
+    pub fn load_breakpoints(&mut self) -> Result<(), anyhow::Error> {
+        let workspace = helix_core::find_root(None, &[]);
+        let path = helix_loader::breakpoints_file();
+        if let Some(breakpoints) = read_persisted_breakpoints(&path)?.remove(&workspace) {
+            self.breakpoints = breakpoints.into_iter().collect();
+            self.track_breakpoint_edits();
+        }
+        Ok(())
+    }
+
+    /// Persists the breakpoints of the current workspace, keeping those of other workspaces.
+    pub fn save_breakpoints(&self) -> Result<(), anyhow::Error> {
+        let workspace = helix_core::find_root(None, &[]);
+        write_persisted_breakpoints(
+            &helix_loader::breakpoints_file(),
+            workspace,
+            &self.breakpoints,
+        )
+    }
+
+    /// Records the edits of the documents with breakpoints, for the breakpoints to move along
+    /// with them in [`Editor::remap_breakpoints`].
+    pub fn track_breakpoint_edits(&mut self) {
+        for doc in self.documents.values_mut() {
+            let has_breakpoints = doc
+                .path()
+                .and_then(|path| self.breakpoints.get(path))
+                .map_or(false, |breakpoints| !breakpoints.is_empty());
+            doc.set_track_edits(has_breakpoints);
+        }
+    }
+
+    /// Maps the lines of breakpoints through the edits made to their documents since the last
+    /// call. The files whose breakpoints moved are added to [`Editor::moved_breakpoints`], to be
+    /// sent to the debuggers once editing pauses.
+    pub fn remap_breakpoints(&mut self) {
+        for doc in self.documents.values_mut() {
+            let (old_text, changes) = match doc.take_edits() {
+                Some(edits) => edits,
+                None => continue,
+            };
+            let breakpoints = match doc.path().and_then(|path| self.breakpoints.get_mut(path)) {
+                Some(breakpoints) if !breakpoints.is_empty() => breakpoints,
+                _ => continue,
+            };
+
+            if remap_breakpoint_lines(breakpoints, &old_text, doc.text(), &changes) {
+                self.moved_breakpoints.insert(doc.path().unwrap().clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helix_core::Transaction;
+
+    fn breakpoint(line: usize) -> Breakpoint {
+        Breakpoint {
+            line,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn breakpoints_move_with_edits() {
+        let old_text = Rope::from("a\nb\nc\nd\n");
+        let mut breakpoints = vec![breakpoint(1), breakpoint(3)];
+        // insert a line before the first breakpoint
+        let transaction = Transaction::change(&old_text, [(2, 2, Some("x\n".into()))].into_iter());
+        let mut text = old_text.clone();
+        transaction.apply(&mut text);
+
+        assert!(remap_breakpoint_lines(
+            &mut breakpoints,
+            &old_text,
+            &text,
+            transaction.changes()
+        ));
+        let lines: Vec<_> = breakpoints.iter().map(|b| b.line).collect();
+        assert_eq!(lines, [2, 4]);
+    }
+
+    #[test]
+    fn collapsed_breakpoints_are_merged() {
+        let old_text = Rope::from("a\nb\nc\n");
+        let mut breakpoints = vec![
+            Breakpoint {
+                line: 0,
+                enabled: false,
+                ..Default::default()
+            },
+            Breakpoint {
+                line: 1,
+                condition: Some("x > 1".into()),
+                log_message: Some("x is {x}".into()),
+                ..Default::default()
+            },
+        ];
+        // delete the first line, the breakpoint on it moves onto the second one
+        let transaction = Transaction::change(&old_text, [(0, 2, None)].into_iter());
+        let mut text = old_text.clone();
+        transaction.apply(&mut text);
+
+        assert!(remap_breakpoint_lines(
+            &mut breakpoints,
+            &old_text,
+            &text,
+            transaction.changes()
+        ));
+        assert_eq!(breakpoints.len(), 1);
+        assert_eq!(breakpoints[0].line, 0);
+        assert_eq!(breakpoints[0].condition.as_deref(), Some("x > 1"));
+        assert_eq!(breakpoints[0].log_message.as_deref(), Some("x is {x}"));
+        assert!(breakpoints[0].enabled);
+    }
+
+    #[test]
+    fn persisted_breakpoints_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("breakpoints.json");
+        let other_workspace = PathBuf::from("/other");
+        let other: HashMap<_, _> = [(PathBuf::from("/other/main.rs"), vec![breakpoint(2)])]
+            .into_iter()
+            .collect();
+        write_persisted_breakpoints(&file, other_workspace.clone(), &other).unwrap();
+
+        let workspace = PathBuf::from("/project");
+        let path = PathBuf::from("/project/main.rs");
+        let breakpoints: HashMap<_, _> = [(
+            path.clone(),
+            vec![
+                Breakpoint {
+                    line: 4,
+                    condition: Some("x > 1".into()),
+                    enabled: false,
+                    ..Default::default()
+                },
+                Breakpoint {
+                    line: 7,
+                    temporary: true,
+                    ..Default::default()
+                },
+            ],
+        )]
+        .into_iter()
+        .collect();
+        write_persisted_breakpoints(&file, workspace.clone(), &breakpoints).unwrap();
+
+        let mut persisted = read_persisted_breakpoints(&file).unwrap();
+        assert_eq!(persisted[&other_workspace].len(), 1);
+        let restored = persisted.remove(&workspace).unwrap().remove(&path).unwrap();
+        // temporary breakpoints aren't persisted
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].line, 4);
+        assert_eq!(restored[0].condition.as_deref(), Some("x > 1"));
+        assert!(!restored[0].enabled);
     }
 }
@@ -2,6 +2,8 @@
 pub mod macros;
 
 pub mod clipboard;
+pub mod debug_console;
+pub mod debug_memory;
 pub mod document;
 pub mod editor;
 pub mod env;
@@ -15,6 +17,8 @@ pub mod handlers {
 pub mod info;
 pub mod input;
 pub mod keyboard;
+pub mod repl;
+pub mod terminal;
 pub mod theme;
 pub mod tree;
 pub mod view;
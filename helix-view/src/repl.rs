@@ -0,0 +1,187 @@
+//! Long lived interpreters that selections are sent to, one per language.
+
+use std::process::Stdio;
+
+use anyhow::Error;
+use helix_core::{encoding, syntax::ReplConfiguration, Rope, Selection, Transaction};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    process::Command,
+    sync::{
+        mpsc::{unbounded_channel, UnboundedSender},
+        oneshot,
+    },
+};
+
+use crate::{document::to_writer, editor::Action, Document, DocumentId, Editor};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplEvent {
+    /// The process wrote to stdout or stderr.
+    Output { doc: DocumentId, text: String },
+    /// The process exited.
+    Exit { doc: DocumentId },
+}
+
+pub struct Repl {
+    /// The scratch buffer the output of the process is appended to.
+    pub doc: DocumentId,
+    input: UnboundedSender<Rope>,
+    /// Kills the process when sent or dropped.
+    _kill: oneshot::Sender<()>,
+}
+
+impl Repl {
+    pub fn spawn(
+        config: &ReplConfiguration,
+        doc: DocumentId,
+        events: UnboundedSender<ReplEvent>,
+    ) -> Result<Self, Error> {
+        let mut process = Command::new(&config.command)
+            .args(&config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let mut stdin = process.stdin.take().expect("Failed to open stdin");
+        let stdout = process.stdout.take().expect("Failed to open stdout");
+        let stderr = process.stderr.take().expect("Failed to open stderr");
+
+        let (input, mut input_rx) = unbounded_channel::<Rope>();
+        tokio::spawn(async move {
+            while let Some(text) = input_rx.recv().await {
+                // written like the selections piped to shell commands
+                if let Err(err) = to_writer(&mut stdin, encoding::UTF_8, &text).await {
+                    log::error!("failed to write to repl: {}", err);
+                    break;
+                }
+            }
+        });
+
+        tokio::spawn(read_output(doc, stdout, events.clone()));
+        tokio::spawn(read_output(doc, stderr, events.clone()));
+
+        let (kill, kill_rx) = oneshot::channel();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = process.wait() => {}
+                _ = kill_rx => {
+                    let _ = process.kill().await;
+                }
+            }
+            let _ = events.send(ReplEvent::Exit { doc });
+        });
+
+        Ok(Self {
+            doc,
+            input,
+            _kill: kill,
+        })
+    }
+
+    pub fn send(&self, text: Rope) -> Result<(), Error> {
+        self.input
+            .send(text)
+            .map_err(|_| anyhow::anyhow!("the REPL is not running anymore"))
+    }
+}
+
+/// Forwards the output of the process as it arrives, since prompts usually don't end with a
+/// newline.
+async fn read_output(
+    doc: DocumentId,
+    mut reader: impl AsyncRead + Unpin,
+    events: UnboundedSender<ReplEvent>,
+) {
+    let mut buf = [0u8; 4096];
+    // bytes of a character that was split between two reads
+    let mut pending = Vec::new();
+    loop {
+        let n = match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        pending.extend_from_slice(&buf[..n]);
+        let valid = match std::str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => pending.len(),
+        };
+        let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+        pending.drain(..valid);
+
+        if !text.is_empty() && events.send(ReplEvent::Output { doc, text }).is_err() {
+            break;
+        }
+    }
+}
+
+impl Editor {
+    /// Writes `text` to the REPL of `language`, starting it first if it isn't running. The
+    /// output buffer of the REPL is shown in a split if it isn't visible.
+    pub fn send_to_repl(
+        &mut self,
+        language: &str,
+        config: &ReplConfiguration,
+        text: Rope,
+    ) -> Result<(), Error> {
+        if !self.repls.contains_key(language) {
+            // the buffer only shows the output, which is appended by the process
+            let mut doc = Document::default();
+            doc.readonly = true;
+            let doc = self.new_document(doc);
+            let events = self.repl_events.0.clone();
+            let repl = match Repl::spawn(config, doc, events) {
+                Ok(repl) => repl,
+                Err(err) => {
+                    let _ = self.close_document(doc, true);
+                    return Err(err);
+                }
+            };
+            self.repls.insert(language.to_string(), repl);
+        }
+        let repl = &self.repls[language];
+        repl.send(text)?;
+
+        let doc = repl.doc;
+        if !self.tree.views().any(|(view, _)| view.doc == doc) {
+            let focus = self.tree.focus;
+            self.switch(doc, Action::VerticalSplit);
+            self.focus(focus);
+        }
+        Ok(())
+    }
+
+    pub fn handle_repl_event(&mut self, event: ReplEvent) {
+        match event {
+            ReplEvent::Output { doc, text } => self.append_repl_output(doc, &text),
+            ReplEvent::Exit { doc } => {
+                // a new process is started on the next send
+                self.repls.retain(|_, repl| repl.doc != doc);
+                self.append_repl_output(doc, "\n[process exited]\n");
+            }
+        }
+    }
+
+    fn append_repl_output(&mut self, doc_id: DocumentId, text: &str) {
+        let doc = match self.documents.get_mut(&doc_id) {
+            Some(doc) => doc,
+            None => return,
+        };
+
+        let end = doc.text().len_chars();
+        let transaction = Transaction::insert(doc.text(), &Selection::point(end), text.into());
+        doc.apply_output(transaction.changes().clone());
+
+        // follow the output in the views showing it
+        let end = doc.text().len_chars();
+        for (view, _) in self.tree.views_mut() {
+            if view.doc == doc_id {
+                doc.set_selection(view.id, Selection::point(end));
+                view.ensure_cursor_in_view(doc, 0);
+            }
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! Terminal emulators running in a pseudo terminal, displayed in a view of the editor tree.
+
+use std::{
+    io::{Read, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::Duration,
+};
+
+use anyhow::Error;
+use portable_pty::{native_pty_system, Child, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    graphics::Color,
+    input::KeyEvent,
+    keyboard::{KeyCode, KeyModifiers},
+    ViewId,
+};
+
+/// Number of lines kept once they scrolled off the top of the screen.
+const SCROLLBACK_LEN: usize = 1000;
+/// How often a process that closed the pseudo terminal is checked for having exited.
+const REAP_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalEvent {
+    /// The process wrote new output, the terminal needs to be redrawn.
+    Output(ViewId),
+    /// The process exited or closed the pseudo terminal.
+    Exit(ViewId),
+}
+
+pub struct Terminal {
+    /// The command line the terminal was started with, displayed in its statusline.
+    pub title: String,
+    parser: Arc<Mutex<vt100::Parser>>,
+    /// Set by the reader thread when new output arrives and cleared once it was drawn, so
+    /// that a burst of output only triggers a single redraw.
+    dirty: Arc<AtomicBool>,
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    /// Shared with the reader thread, which reaps the process once it closed the pseudo
+    /// terminal.
+    child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    exited: bool,
+}
+
+impl Terminal {
+    /// Spawns `command` (an interactive shell when empty) in a new pseudo terminal of the given
+    /// size. Output and exit of the process are reported on `events`.
+    pub fn spawn(
+        view_id: ViewId,
+        command: &[String],
+        cwd: &Path,
+        (rows, cols): (u16, u16),
+        events: UnboundedSender<TerminalEvent>,
+    ) -> Result<Self, Error> {
+        let pty = native_pty_system().openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })?;
+
+        let mut cmd = match command.split_first() {
+            Some((program, args)) => {
+                let mut cmd = CommandBuilder::new(program);
+                cmd.args(args);
+                cmd
+            }
+            None => CommandBuilder::new_default_prog(),
+        };
+        cmd.cwd(cwd);
+        cmd.env("TERM", "xterm-256color");
+
+        let child = Arc::new(Mutex::new(pty.slave.spawn_command(cmd)?));
+        // the slave is owned by the child from now on, keeping it open would prevent us from
+        // noticing that the child exited
+        drop(pty.slave);
+
+        let mut reader = pty.master.try_clone_reader()?;
+        let writer = pty.master.take_writer()?;
+        let parser = Arc::new(Mutex::new(vt100::Parser::new(rows, cols, SCROLLBACK_LEN)));
+        let dirty = Arc::new(AtomicBool::new(false));
+
+        {
+            let parser = parser.clone();
+            let dirty = dirty.clone();
+            let child = child.clone();
+            std::thread::spawn(move || {
+                let mut buf = [0u8; 4096];
+                loop {
+                    match reader.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => {
+                            parser.lock().unwrap().process(&buf[..n]);
+                            if !dirty.swap(true, Ordering::AcqRel)
+                                && events.send(TerminalEvent::Output(view_id)).is_err()
+                            {
+                                break;
+                            }
+                        }
+                    }
+                }
+                let _ = events.send(TerminalEvent::Exit(view_id));
+                // the process may still be running after closing the pseudo terminal, the lock
+                // isn't held while waiting so that dropping the terminal can kill it meanwhile
+                loop {
+                    let status = child.lock().unwrap().try_wait();
+                    if !matches!(status, Ok(None)) {
+                        break;
+                    }
+                    std::thread::sleep(REAP_INTERVAL);
+                }
+            });
+        }
+
+        Ok(Self {
+            title: command.join(" "),
+            parser,
+            dirty,
+            master: pty.master,
+            writer,
+            child,
+            exited: false,
+        })
+    }
+
+    /// Locks the emulator state. The lock should only be held for as long as it takes to draw
+    /// the screen since the reader thread is blocked meanwhile.
+    pub fn parser(&self) -> MutexGuard<vt100::Parser> {
+        self.parser.lock().unwrap()
+    }
+
+    /// Returns whether there is output that was not drawn yet, and marks it as drawn.
+    pub fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::AcqRel)
+    }
+
+    pub fn is_exited(&self) -> bool {
+        self.exited
+    }
+
+    pub fn set_exited(&mut self) {
+        self.exited = true;
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        self.parser().screen().size()
+    }
+
+    /// Resizes the emulator and the pseudo terminal, which notifies the process with `SIGWINCH`.
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        if self.size() == (rows, cols) || rows == 0 || cols == 0 {
+            return;
+        }
+        self.parser().set_size(rows, cols);
+        let size = PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
+        if let Err(err) = self.master.resize(size) {
+            log::warn!("failed to resize terminal: {}", err);
+        }
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        if self.exited {
+            return Ok(());
+        }
+        self.writer.write_all(bytes)?;
+        self.writer.flush()
+    }
+
+    pub fn send_key(&mut self, key: KeyEvent) -> std::io::Result<()> {
+        let application_cursor = self.parser().screen().application_cursor();
+        match key_to_bytes(key, application_cursor) {
+            Some(bytes) => self.write(&bytes),
+            None => Ok(()),
+        }
+    }
+
+    /// Sends text as if it was typed, wrapped in bracketed paste sequences when the running
+    /// program asked for them.
+    pub fn paste(&mut self, text: &str) -> std::io::Result<()> {
+        if self.parser().screen().bracketed_paste() {
+            self.write(format!("\x1b[200~{}\x1b[201~", text).as_bytes())
+        } else {
+            self.write(text.as_bytes())
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let mut child = self.child.lock().unwrap();
+        if let Ok(None) = child.try_wait() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Converts the color of a cell to the color it is drawn with, `None` meaning the default one.
+pub fn color(color: vt100::Color) -> Option<Color> {
+    match color {
+        vt100::Color::Default => None,
+        vt100::Color::Idx(index) => Some(Color::Indexed(index)),
+        vt100::Color::Rgb(r, g, b) => Some(Color::Rgb(r, g, b)),
+    }
+}
+
+/// Encodes a key as the bytes an xterm compatible terminal would send for it.
+pub fn key_to_bytes(key: KeyEvent, application_cursor: bool) -> Option<Vec<u8>> {
+    let KeyEvent { code, modifiers } = key;
+    let csi = |application: &str, normal: &str| {
+        if application_cursor {
+            application.as_bytes().to_vec()
+        } else {
+            normal.as_bytes().to_vec()
+        }
+    };
+
+    let mut bytes = match code {
+        KeyCode::Char(ch) if modifiers.contains(KeyModifiers::CONTROL) => {
+            match ch.to_ascii_lowercase() {
+                ch @ 'a'..='z' => vec![ch as u8 - b'a' + 1],
+                '@' | ' ' => vec![0],
+                '[' => vec![0x1b],
+                '\\' => vec![0x1c],
+                ']' => vec![0x1d],
+                '^' => vec![0x1e],
+                '_' => vec![0x1f],
+                _ => return None,
+            }
+        }
+        KeyCode::Char(ch) => ch.to_string().into_bytes(),
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => b"\x1b[Z".to_vec(),
+        KeyCode::Tab => vec![b'\t'],
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => csi("\x1bOA", "\x1b[A"),
+        KeyCode::Down => csi("\x1bOB", "\x1b[B"),
+        KeyCode::Right => csi("\x1bOC", "\x1b[C"),
+        KeyCode::Left => csi("\x1bOD", "\x1b[D"),
+        KeyCode::Home => csi("\x1bOH", "\x1b[H"),
+        KeyCode::End => csi("\x1bOF", "\x1b[F"),
+        KeyCode::Insert => b"\x1b[2~".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        KeyCode::F(n @ 1..=4) => format!("\x1bO{}", (b'P' + n - 1) as char).into_bytes(),
+        KeyCode::F(n) => {
+            let code = match n {
+                5 => 15,
+                6 => 17,
+                7 => 18,
+                8 => 19,
+                9 => 20,
+                10 => 21,
+                11 => 23,
+                12 => 24,
+                _ => return None,
+            };
+            format!("\x1b[{}~", code).into_bytes()
+        }
+        _ => return None,
+    };
+
+    if modifiers.contains(KeyModifiers::ALT) {
+        bytes.insert(0, 0x1b);
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent { code, modifiers }
+    }
+
+    #[test]
+    fn encoding_keys() {
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Char('x'), none), false),
+            Some(b"x".to_vec())
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Char('c'), KeyModifiers::CONTROL), false),
+            Some(vec![0x03])
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Char('b'), KeyModifiers::ALT), false),
+            Some(b"\x1bb".to_vec())
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Enter, none), false),
+            Some(b"\r".to_vec())
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Tab, KeyModifiers::SHIFT), false),
+            Some(b"\x1b[Z".to_vec())
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::F(1), none), false),
+            Some(b"\x1bOP".to_vec())
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::F(12), none), false),
+            Some(b"\x1b[24~".to_vec())
+        );
+        assert_eq!(key_to_bytes(key(KeyCode::CapsLock, none), false), None);
+    }
+
+    #[test]
+    fn encoding_cursor_keys() {
+        let up = key(KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(key_to_bytes(up, false), Some(b"\x1b[A".to_vec()));
+        assert_eq!(key_to_bytes(up, true), Some(b"\x1bOA".to_vec()));
+    }
+}
@@ -111,6 +111,9 @@ pub struct View {
     pub object_selections: Vec<Selection>,
     /// all gutter-related configuration settings, used primarily for gutter rendering
     pub gutters: GutterConfig,
+    /// Empty rows shown above lines of the document as `(line, rows)`, sorted by line. They
+    /// keep the two sides of a side-by-side diff aligned.
+    pub filler_rows: Vec<(usize, usize)>,
     /// A mapping between documents and the last history revision the view was updated at.
     /// Changes between documents and views are synced lazily when switching windows. This
     /// mapping keeps track of the last applied history revision so that only new changes
@@ -140,6 +143,7 @@ pub fn new(doc: DocumentId, gutters: GutterConfig) -> Self {
             last_modified_docs: [None, None],
             object_selections: Vec::new(),
             gutters,
+            filler_rows: Vec::new(),
             doc_revisions: HashMap::new(),
         }
     }
@@ -195,7 +199,9 @@ pub fn offset_coords_to_in_view_center(
             visual_coords_at_pos(doc.text().slice(..), cursor, doc.tab_width());
 
         let inner_area = self.inner_area(doc);
-        let last_line = (self.offset.row + inner_area.height as usize).saturating_sub(1);
+        let last_line = (self.offset.row + inner_area.height as usize)
+            .saturating_sub(1)
+            .saturating_sub(self.fillers_before(line));
         let last_col = self.offset.col + inner_area.width.saturating_sub(1) as usize;
 
         let new_offset = |scrolloff: usize| {
@@ -266,11 +272,40 @@ pub fn is_cursor_in_view(&mut self, doc: &Document, scrolloff: usize) -> bool {
     /// Calculates the last visible line on screen
     #[inline]
     pub fn last_line(&self, doc: &Document) -> usize {
-        std::cmp::min(
+        let mut last_line = std::cmp::min(
             // Saturating subs to make it inclusive zero indexing.
             (self.offset.row + self.inner_height()).saturating_sub(1),
             doc.text().len_lines().saturating_sub(1),
-        )
+        );
+        while last_line > self.offset.row && self.screen_row(last_line) >= self.inner_height() {
+            last_line -= 1;
+        }
+        last_line
+    }
+
+    /// The number of filler rows shown between the first line in view and `line`.
+    fn fillers_before(&self, line: usize) -> usize {
+        self.filler_rows
+            .iter()
+            .skip_while(|(filler_line, _)| *filler_line <= self.offset.row)
+            .take_while(|(filler_line, _)| *filler_line <= line)
+            .map(|(_, rows)| rows)
+            .sum()
+    }
+
+    /// The row of the view, from its top, that a `line` in view is shown on.
+    pub fn screen_row(&self, line: usize) -> usize {
+        line.saturating_sub(self.offset.row) + self.fillers_before(line)
+    }
+
+    /// The line shown on a `row` of the view, from its top. Filler rows belong to the line below
+    /// them.
+    pub fn line_at_screen_row(&self, row: usize) -> usize {
+        let mut line = self.offset.row + row;
+        while line > self.offset.row && self.screen_row(line - 1) >= row {
+            line -= 1;
+        }
+        line
     }
 
     /// Translates a document position to an absolute position in the terminal.
@@ -294,7 +329,7 @@ pub fn screen_coords_at_pos(
         let Position { col, .. } = visual_coords_at_pos(text, pos, tab_width);
 
         // It is possible for underflow to occur if the buffer length is larger than the terminal width.
-        let row = line.saturating_sub(self.offset.row);
+        let row = self.screen_row(line);
         let col = col.saturating_sub(self.offset.col);
 
         Some(Position::new(row, col))
@@ -318,7 +353,7 @@ pub fn text_pos_at_screen_coords(
             return None;
         }
 
-        let text_row = (row - inner.y) as usize + self.offset.row;
+        let text_row = self.line_at_screen_row((row - inner.y) as usize);
         if text_row > text.len_lines() - 1 {
             return Some(text.len_chars());
         }
@@ -431,6 +466,28 @@ mod tests {
     use crate::document::Document;
     use crate::editor::{GutterConfig, GutterLineNumbersConfig, GutterType};
 
+    #[test]
+    fn test_filler_rows() {
+        let mut view = View::new(DocumentId::default(), GutterConfig::default());
+        view.area = Rect::new(0, 0, 40, 6);
+        view.filler_rows = vec![(1, 1), (3, 2)];
+        let doc = Document::from(Rope::from_str("a\nb\nc\nd\ne\nf\n"), None);
+
+        assert_eq!(view.screen_row(0), 0);
+        assert_eq!(view.screen_row(1), 2);
+        assert_eq!(view.screen_row(3), 6);
+        assert_eq!(view.line_at_screen_row(1), 1);
+        assert_eq!(view.line_at_screen_row(3), 2);
+        assert_eq!(view.line_at_screen_row(4), 3);
+        // 5 rows for text below the statusline
+        assert_eq!(view.last_line(&doc), 2);
+
+        // the fillers above the first line in view are scrolled past
+        view.offset.row = 1;
+        assert_eq!(view.screen_row(1), 0);
+        assert_eq!(view.screen_row(3), 4);
+    }
+
     #[test]
     fn test_text_pos_at_screen_coords() {
         let mut view = View::new(DocumentId::default(), GutterConfig::default());